@@ -26,6 +26,21 @@ impl BlobExcessGasAndPrice {
         }
     }
 
+    /// Creates a new instance with the blob gas price calculated with the given blob base fee
+    /// update fraction, as set by the blob schedule of the chain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `base_fee_update_fraction` is zero.
+    pub fn new_with_update_fraction(excess_blob_gas: u64, base_fee_update_fraction: u64) -> Self {
+        let blob_gasprice =
+            fake_exponential(MIN_BLOB_GASPRICE, excess_blob_gas, base_fee_update_fraction);
+        Self {
+            excess_blob_gas,
+            blob_gasprice,
+        }
+    }
+
     /// Calculate this block excess gas and price from the parent excess gas and gas used
     /// and the target blob gas per block.
     ///
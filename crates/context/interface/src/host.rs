@@ -1,5 +1,5 @@
 pub use crate::journaled_state::StateLoad;
-use database_interface::Database;
+use database_interface::{DBErrorContext, DBErrorMarker, Database, DatabaseOp};
 
 use crate::{context::ContextTr, journaled_state::AccountLoad, Block, Journal};
 use primitives::{Address, Bytes, Log, B256, BLOCK_HASH_HISTORY, U256};
@@ -24,11 +24,17 @@ pub trait Host: ContextTr {
         }
 
         if diff <= BLOCK_HASH_HISTORY {
+            let depth = self.journal().depth();
             return self
                 .journal()
                 .db()
                 .block_hash(requested_number)
-                .map_err(|e| self.set_error(e))
+                .map_err(|e| {
+                    self.set_error(e.with_context(DBErrorContext {
+                        op: DatabaseOp::BlockHash(requested_number),
+                        depth,
+                    }))
+                })
                 .ok();
         }
 
@@ -351,6 +351,10 @@ pub enum InvalidTransaction {
     },
     /// Blob transaction contains a versioned hash with an incorrect version
     BlobVersionNotSupported,
+    /// Blob sidecar commitments do not match the versioned hashes of the transaction.
+    BlobSidecarMismatch,
+    /// Blob sidecar KZG proofs are invalid.
+    BlobKzgProofInvalid,
     /// EOF create should have `to` address
     EofCreateShouldHaveToAddress,
     /// EIP-7702 is not enabled.
@@ -429,6 +433,10 @@ impl fmt::Display for InvalidTransaction {
                 write!(f, "too many blobs, have {have}, max {max}")
             }
             Self::BlobVersionNotSupported => write!(f, "blob version not supported"),
+            Self::BlobSidecarMismatch => {
+                write!(f, "blob sidecar does not match blob versioned hashes")
+            }
+            Self::BlobKzgProofInvalid => write!(f, "invalid blob kzg proof"),
             Self::EofCreateShouldHaveToAddress => write!(f, "EOF crate should have `to` address"),
             Self::AuthorizationListNotSupported => write!(f, "authorization list not supported"),
             Self::AuthorizationListInvalidFields => {
@@ -1,4 +1,6 @@
-use context_interface::block::{BlobExcessGasAndPrice, Block};
+pub use context_interface::block::BlobExcessGasAndPrice;
+
+use context_interface::block::Block;
 use primitives::{Address, B256, U256};
 
 /// The block environment
@@ -50,6 +52,84 @@ impl BlockEnv {
         self.blob_excess_gas_and_price =
             Some(BlobExcessGasAndPrice::new(excess_blob_gas, is_prague));
     }
+
+    /// Takes `blob_excess_gas` saves it inside env and calculates `blob_fee` with the given
+    /// blob base fee update fraction, see [`BlobExcessGasAndPrice::new_with_update_fraction`].
+    pub fn set_blob_excess_gas_and_update_fraction(
+        &mut self,
+        excess_blob_gas: u64,
+        base_fee_update_fraction: u64,
+    ) {
+        self.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice::new_with_update_fraction(
+            excess_blob_gas,
+            base_fee_update_fraction,
+        ));
+    }
+}
+
+/// Block overrides, as accepted by `eth_call` and `debug_traceCall`.
+///
+/// Fields that are `None` keep the value of the block they are applied to.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BlockOverrides {
+    /// Overrides the block number.
+    pub number: Option<u64>,
+    /// Overrides the difficulty of the block.
+    pub difficulty: Option<U256>,
+    /// Overrides the block timestamp.
+    pub time: Option<u64>,
+    /// Overrides the block gas limit.
+    pub gas_limit: Option<u64>,
+    /// Overrides the block beneficiary.
+    #[cfg_attr(feature = "serde", serde(alias = "feeRecipient"))]
+    pub coinbase: Option<Address>,
+    /// Overrides the prevrandao value of the block.
+    #[cfg_attr(feature = "serde", serde(alias = "prevRandao"))]
+    pub random: Option<B256>,
+    /// Overrides the block base fee.
+    #[cfg_attr(feature = "serde", serde(alias = "baseFeePerGas"))]
+    pub base_fee: Option<u64>,
+    /// Overrides the blob gas price, keeping the excess blob gas of the block.
+    pub blob_base_fee: Option<u128>,
+}
+
+impl BlockOverrides {
+    /// Applies the overrides to the block environment.
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(difficulty) = self.difficulty {
+            block.difficulty = difficulty;
+        }
+        if let Some(time) = self.time {
+            block.timestamp = time;
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(coinbase) = self.coinbase {
+            block.beneficiary = coinbase;
+        }
+        if let Some(random) = self.random {
+            block.prevrandao = Some(random);
+        }
+        if let Some(base_fee) = self.base_fee {
+            block.basefee = base_fee;
+        }
+        if let Some(blob_gasprice) = self.blob_base_fee {
+            let excess_blob_gas = block
+                .blob_excess_gas_and_price
+                .map(|blob| blob.excess_blob_gas)
+                .unwrap_or_default();
+            block.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                excess_blob_gas,
+                blob_gasprice,
+            });
+        }
+    }
 }
 
 impl Block for BlockEnv {
@@ -108,3 +188,61 @@ impl Default for BlockEnv {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_overrides_apply() {
+        let mut block = BlockEnv {
+            difficulty: U256::from(7),
+            ..Default::default()
+        };
+        block.set_blob_excess_gas_and_price(1000, false);
+        let original = block.clone();
+
+        // Empty overrides keep the block.
+        BlockOverrides::default().apply(&mut block);
+        assert_eq!(block, original);
+
+        let overrides = BlockOverrides {
+            number: Some(10),
+            time: Some(20),
+            gas_limit: Some(30),
+            coinbase: Some(Address::with_last_byte(1)),
+            random: Some(B256::with_last_byte(2)),
+            base_fee: Some(40),
+            ..Default::default()
+        };
+        overrides.apply(&mut block);
+        assert_eq!(
+            block,
+            BlockEnv {
+                number: 10,
+                beneficiary: Address::with_last_byte(1),
+                timestamp: 20,
+                gas_limit: 30,
+                basefee: 40,
+                prevrandao: Some(B256::with_last_byte(2)),
+                // Unset fields are left alone.
+                difficulty: U256::from(7),
+                blob_excess_gas_and_price: original.blob_excess_gas_and_price,
+            }
+        );
+
+        // Blob base fee override keeps the excess blob gas.
+        BlockOverrides {
+            blob_base_fee: Some(5),
+            ..Default::default()
+        }
+        .apply(&mut block);
+        assert_eq!(
+            block.blob_excess_gas_and_price,
+            Some(BlobExcessGasAndPrice {
+                excess_blob_gas: 1000,
+                blob_gasprice: 5,
+            })
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use bytecode::{Bytecode, EOF_MAGIC_BYTES, EOF_MAGIC_HASH};
 use context_interface::journaled_state::{AccountLoad, Journal, JournalCheckpoint, TransferError};
-use database_interface::Database;
+use database_interface::{DBErrorContext, DBErrorMarker, Database, DatabaseOp};
 use interpreter::{SStoreResult, SelfDestructResult, StateLoad};
 use primitives::{
     hash_map::Entry, Address, Bytes, HashMap, HashSet, Log, B256, KECCAK_EMPTY, PRECOMPILE3, U256,
@@ -784,7 +784,13 @@ impl<DB: Database> JournaledState<DB> {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(vac) => vac.insert(
                 self.database
-                    .basic(address)?
+                    .basic(address)
+                    .map_err(|e| {
+                        e.with_context(DBErrorContext {
+                            op: DatabaseOp::Basic(address),
+                            depth: self.depth,
+                        })
+                    })?
                     .map(|i| i.into())
                     .unwrap_or(Account::new_not_existing()),
             ),
@@ -792,7 +798,12 @@ impl<DB: Database> JournaledState<DB> {
         // preload storages.
         for storage_key in storage_keys.into_iter() {
             if let Entry::Vacant(entry) = account.storage.entry(storage_key) {
-                let storage = self.database.storage(address, storage_key)?;
+                let storage = self.database.storage(address, storage_key).map_err(|e| {
+                    e.with_context(DBErrorContext {
+                        op: DatabaseOp::Storage(address, storage_key),
+                        depth: self.depth,
+                    })
+                })?;
                 entry.insert(EvmStorageSlot::new(storage));
             }
         }
@@ -853,7 +864,12 @@ impl<DB: Database> JournaledState<DB> {
                 }
             }
             Entry::Vacant(vac) => {
-                let account = if let Some(account) = self.database.basic(address)? {
+                let account = if let Some(account) = self.database.basic(address).map_err(|e| {
+                    e.with_context(DBErrorContext {
+                        op: DatabaseOp::Basic(address),
+                        depth: self.depth,
+                    })
+                })? {
                     account.into()
                 } else {
                     Account::new_not_existing()
@@ -881,7 +897,13 @@ impl<DB: Database> JournaledState<DB> {
                 let code = if info.code_hash == KECCAK_EMPTY {
                     Bytecode::default()
                 } else {
-                    self.database.code_by_hash(info.code_hash)?
+                    let code_hash = info.code_hash;
+                    self.database.code_by_hash(code_hash).map_err(|e| {
+                        e.with_context(DBErrorContext {
+                            op: DatabaseOp::CodeByHash(code_hash),
+                            depth: self.depth,
+                        })
+                    })?
                 };
                 info.code = Some(code);
             }
@@ -912,7 +934,12 @@ impl<DB: Database> JournaledState<DB> {
                 let value = if is_newly_created {
                     U256::ZERO
                 } else {
-                    self.database.storage(address, key)?
+                    self.database.storage(address, key).map_err(|e| {
+                        e.with_context(DBErrorContext {
+                            op: DatabaseOp::Storage(address, key),
+                            depth: self.depth,
+                        })
+                    })?
                 };
 
                 vac.insert(EvmStorageSlot::new(value));
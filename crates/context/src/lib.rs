@@ -15,7 +15,7 @@ mod journal_init;
 pub mod journaled_state;
 pub mod tx;
 
-pub use block::BlockEnv;
+pub use block::{BlockEnv, BlockOverrides};
 pub use cfg::{Cfg, CfgEnv};
 pub use context::*;
 pub use journal_init::JournalInit;
@@ -1,9 +1,9 @@
-use crate::{DBErrorMarker, Database, DatabaseRef};
+use crate::{DBErrorMarker, Database, DatabaseIter, DatabaseRef};
 use core::error::Error;
 use core::{convert::Infallible, fmt, marker::PhantomData};
 use primitives::{keccak256, Address, B256, U256};
 use state::{AccountInfo, Bytecode};
-use std::string::ToString;
+use std::{string::ToString, vec::Vec};
 
 /// An empty database that always returns default values when queried
 pub type EmptyDB = EmptyDBTyped<Infallible>;
@@ -101,6 +101,29 @@ impl<E: DBErrorMarker + Error> DatabaseRef for EmptyDBTyped<E> {
     }
 }
 
+impl<E: DBErrorMarker + Error> DatabaseIter for EmptyDBTyped<E> {
+    type Error = E;
+
+    #[inline]
+    fn accounts_range(
+        &self,
+        _start: Address,
+        _limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    #[inline]
+    fn storage_range(
+        &self,
+        _address: Address,
+        _start: U256,
+        _limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        Ok(Vec::new())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,171 @@
+//! Context of failed database calls.
+use crate::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use core::{error::Error, fmt};
+use primitives::{Address, HashMap, B256, U256};
+use state::{Account, AccountInfo, Bytecode};
+
+/// Database call with its key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DatabaseOp {
+    /// Loading basic account information.
+    Basic(Address),
+    /// Loading code by its hash.
+    CodeByHash(B256),
+    /// Loading storage slot of the account.
+    Storage(Address, U256),
+    /// Loading block hash by block number.
+    BlockHash(u64),
+}
+
+impl fmt::Display for DatabaseOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic(address) => write!(f, "basic {address}"),
+            Self::CodeByHash(code_hash) => write!(f, "code_by_hash {code_hash}"),
+            Self::Storage(address, index) => write!(f, "storage {index} of {address}"),
+            Self::BlockHash(number) => write!(f, "block_hash {number}"),
+        }
+    }
+}
+
+/// Context of a failed database call, passed to [`DBErrorMarker::with_context`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DBErrorContext {
+    /// Database call that failed.
+    pub op: DatabaseOp,
+    /// Call depth of the execution when the call failed.
+    pub depth: usize,
+}
+
+/// Error of an [`ErrorContextDB`] that carries the failed database call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DBErrorWithContext<E> {
+    /// Database call that failed.
+    pub op: DatabaseOp,
+    /// Call depth of the execution when the call failed.
+    ///
+    /// `None` if the database was not called by the journal.
+    pub depth: Option<usize>,
+    /// Error returned by the database.
+    pub error: E,
+}
+
+impl<E> DBErrorWithContext<E> {
+    /// Creates a new error of the database call without the call depth.
+    pub fn new(op: DatabaseOp, error: E) -> Self {
+        Self {
+            op,
+            depth: None,
+            error,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for DBErrorWithContext<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.op)?;
+        if let Some(depth) = self.depth {
+            write!(f, " at depth {depth}")?;
+        }
+        write!(f, " failed: {}", self.error)
+    }
+}
+
+impl<E: Error> Error for DBErrorWithContext<E> {}
+
+impl<E> DBErrorMarker for DBErrorWithContext<E> {
+    fn with_context(mut self, context: DBErrorContext) -> Self {
+        self.depth.get_or_insert(context.depth);
+        self
+    }
+}
+
+/// Database wrapper whose errors carry the failed call, its key and the call depth.
+///
+/// Useful for diagnosing failures of remote databases, as `EVMError::Database` then tells
+/// which account, storage slot, code or block hash was being loaded.
+#[derive(Clone, Debug, Default)]
+pub struct ErrorContextDB<DB>(pub DB);
+
+impl<DB> ErrorContextDB<DB> {
+    /// Wraps the database.
+    pub fn new(db: DB) -> Self {
+        Self(db)
+    }
+
+    /// Returns the wrapped database.
+    pub fn into_inner(self) -> DB {
+        self.0
+    }
+}
+
+impl<DB: Database> Database for ErrorContextDB<DB> {
+    type Error = DBErrorWithContext<DB::Error>;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0
+            .basic(address)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::Basic(address), e))
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0
+            .code_by_hash(code_hash)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::CodeByHash(code_hash), e))
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0
+            .storage(address, index)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::Storage(address, index), e))
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.0
+            .block_hash(number)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::BlockHash(number), e))
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for ErrorContextDB<DB> {
+    type Error = DBErrorWithContext<DB::Error>;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0
+            .basic_ref(address)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::Basic(address), e))
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0
+            .code_by_hash_ref(code_hash)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::CodeByHash(code_hash), e))
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0
+            .storage_ref(address, index)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::Storage(address, index), e))
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.0
+            .block_hash_ref(number)
+            .map_err(|e| DBErrorWithContext::new(DatabaseOp::BlockHash(number), e))
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for ErrorContextDB<DB> {
+    #[inline]
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.0.commit(changes)
+    }
+}
@@ -0,0 +1,192 @@
+//! Enumeration of accounts and storage.
+use crate::DBErrorMarker;
+use auto_impl::auto_impl;
+use core::error::Error;
+use primitives::{Address, U256};
+use state::AccountInfo;
+use std::vec::Vec;
+
+/// Database that can enumerate its accounts and storage.
+///
+/// Ranges are paginated: each call returns up to `limit` entries with keys greater or equal to
+/// `start`, in ascending key order. Pass the key following the last returned one to get the
+/// next page. Not existing accounts and zero storage slots are skipped.
+///
+/// Accounts are ordered by address and slots by their key, not by hashed keys as in the state
+/// trie.
+#[auto_impl(&, &mut, Box, Rc, Arc)]
+pub trait DatabaseIter {
+    /// The database error type.
+    type Error: DBErrorMarker + Error;
+
+    /// Returns up to `limit` accounts with address greater or equal to `start`.
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error>;
+
+    /// Returns up to `limit` non-zero storage slots of the account with key greater or equal to
+    /// `start`.
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error>;
+}
+
+/// Applies `overlay` changes on top of an inner range and returns up to `limit` entries with
+/// keys greater or equal to `start`.
+///
+/// The overlay yields changes with keys greater or equal to `start` in ascending key order, e.g.
+/// [`SortedOverlay::range`]. `None` in the overlay removes the key. The overlay is consumed only
+/// up to the last change that can be part of the page, so a page costs `O(limit)` plus the
+/// removals in it. `inner` is called with the number of entries to request from the inner range,
+/// which is `limit` increased by the number of consumed removals, as each of them can remove at
+/// most one inner entry.
+pub fn overlay_range<K: Ord + Copy, V, E>(
+    start: K,
+    limit: usize,
+    overlay: impl IntoIterator<Item = (K, Option<V>)>,
+    inner: impl FnOnce(usize) -> Result<Vec<(K, V)>, E>,
+) -> Result<Vec<(K, V)>, E> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    // Changes after the `limit`-th inserted entry can not be part of the page.
+    let mut changes: Vec<(K, Option<V>)> = Vec::new();
+    let mut inserted = 0;
+    for (key, value) in overlay {
+        debug_assert!(
+            key >= start && changes.last().is_none_or(|(last, _)| *last < key),
+            "overlay is not sorted"
+        );
+        inserted += value.is_some() as usize;
+        changes.push((key, value));
+        if inserted == limit {
+            break;
+        }
+    }
+    let inner_limit = limit.saturating_add(changes.len() - inserted);
+    let inner = inner(inner_limit)?;
+    // If the inner page is full, keys after its last key may be missing from it.
+    let bound = (inner.len() >= inner_limit)
+        .then(|| inner.last().map(|(key, _)| *key))
+        .flatten();
+
+    let mut page = Vec::with_capacity(limit);
+    let mut inner = inner.into_iter().peekable();
+    let mut changes = changes.into_iter().peekable();
+    while page.len() < limit {
+        let (key, value) = match (inner.peek(), changes.peek()) {
+            (None, None) => break,
+            (Some(_), None) => inner.next().map(|(key, value)| (key, Some(value))),
+            (Some((inner_key, _)), Some((key, _))) if inner_key < key => {
+                inner.next().map(|(key, value)| (key, Some(value)))
+            }
+            (inner_entry, Some((key, _))) => {
+                // The change replaces or removes the inner entry with the same key.
+                if inner_entry.is_some_and(|(inner_key, _)| inner_key == key) {
+                    inner.next();
+                }
+                changes.next()
+            }
+        }
+        .expect("peeked entry");
+        if bound.is_some_and(|bound| key > bound) {
+            break;
+        }
+        if let Some(value) = value {
+            page.push((key, value));
+        }
+    }
+    Ok(page)
+}
+
+/// Entries of an unordered map sorted by key, used as the overlay of [overlay_range].
+///
+/// Entries are sorted once, finding the start of a range is a binary search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedOverlay<K, V> {
+    entries: Vec<(K, Option<V>)>,
+}
+
+impl<K: Ord + Copy, V> SortedOverlay<K, V> {
+    /// Sorts the entries, `None` values remove the key from the inner range.
+    pub fn new(entries: impl IntoIterator<Item = (K, Option<V>)>) -> Self {
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        Self { entries }
+    }
+
+    /// Returns the entries with keys greater or equal to `start` in ascending key order.
+    pub fn range(&self, start: K) -> impl Iterator<Item = (K, Option<&V>)> + '_ {
+        let first = self.entries.partition_point(|(key, _)| *key < start);
+        self.entries[first..]
+            .iter()
+            .map(|(key, value)| (*key, value.as_ref()))
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+
+    #[test]
+    fn overlay_range_pages() {
+        let inner: Vec<(u8, u8)> = (0..10).map(|i| (i * 2, i)).collect();
+        let inner = &inner;
+        let inner_range = |start: u8| {
+            move |limit: usize| {
+                Ok::<_, Infallible>(
+                    inner
+                        .iter()
+                        .filter(|(key, _)| *key >= start)
+                        .take(limit)
+                        .copied()
+                        .collect(),
+                )
+            }
+        };
+        let overlay = SortedOverlay::new([(19, Some(101)), (2, None), (1, Some(100)), (4, None)]);
+        let range = |start| {
+            overlay
+                .range(start)
+                .map(|(key, value)| (key, value.copied()))
+        };
+
+        let page = overlay_range(0, 3, range(0), inner_range(0)).unwrap();
+        assert_eq!(page, [(0, 0), (1, 100), (6, 3)]);
+        let page = overlay_range(7, 10, range(7), inner_range(7)).unwrap();
+        assert_eq!(
+            page,
+            [
+                (8, 4),
+                (10, 5),
+                (12, 6),
+                (14, 7),
+                (16, 8),
+                (18, 9),
+                (19, 101)
+            ]
+        );
+        // Removed keys are not counted towards the limit.
+        let page = overlay_range(1, 2, range(1), inner_range(1)).unwrap();
+        assert_eq!(page, [(1, 100), (6, 3)]);
+        assert!(overlay_range(0, 0, range(0), inner_range(0))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -16,13 +16,29 @@ use std::string::String;
 #[cfg(feature = "asyncdb")]
 pub mod async_db;
 pub mod empty_db;
+pub mod error_context;
+pub mod iter;
 
 #[cfg(feature = "asyncdb")]
 pub use async_db::{DatabaseAsync, WrapDatabaseAsync};
 pub use empty_db::{EmptyDB, EmptyDBTyped};
+pub use error_context::{DBErrorContext, DBErrorWithContext, DatabaseOp, ErrorContextDB};
+pub use iter::{overlay_range, DatabaseIter, SortedOverlay};
 
 /// Database error marker is needed to implement From conversion for Error type.
-pub trait DBErrorMarker {}
+pub trait DBErrorMarker {
+    /// Adds the context of the failed database call to the error.
+    ///
+    /// Called by the journal when a database call fails. The context is ignored by default,
+    /// see [`ErrorContextDB`] for a database whose errors keep it.
+    #[inline]
+    fn with_context(self, _context: DBErrorContext) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
 
 /// Implement marker for `()`.
 impl DBErrorMarker for () {}
@@ -1,3 +1,5 @@
+use crate::CacheDB;
+use alloy_eips::eip2930::AccessList;
 pub use alloy_eips::BlockId;
 use alloy_provider::{
     network::{
@@ -6,12 +8,15 @@ use alloy_provider::{
     },
     Network, Provider,
 };
-use alloy_transport::TransportError;
-use core::error::Error;
+use alloy_rpc_client::BatchRequest;
+use alloy_rpc_types_eth::EIP1186AccountProofResponse;
+use alloy_transport::{TransportError, TransportErrorKind};
+use core::{error::Error, mem};
 use database_interface::{async_db::DatabaseAsyncRef, DBErrorMarker};
-use primitives::{Address, B256, U256};
+use primitives::{ruint::aliases::U64, Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
 use state::{AccountInfo, Bytecode};
-use std::fmt::Display;
+use std::{fmt::Display, sync::Mutex, vec::Vec};
+use tokio::sync::oneshot;
 
 #[derive(Debug)]
 pub struct DBTransportError(pub TransportError);
@@ -32,6 +37,27 @@ impl From<TransportError> for DBTransportError {
     }
 }
 
+/// RPC methods used by [AlloyDB] to fetch accounts and storage.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FetchMode {
+    /// `eth_getTransactionCount`, `eth_getBalance` and `eth_getCode` for every account.
+    #[default]
+    Plain,
+    /// `eth_getProof` for account and storage, `eth_getCode` only for accounts with code.
+    Proof,
+}
+
+/// Account and storage fetched by [AlloyDB::prefetch].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrefetchedAccount {
+    /// Account address.
+    pub address: Address,
+    /// Account info, including code.
+    pub info: AccountInfo,
+    /// Storage slots and their values.
+    pub storage: Vec<(U256, U256)>,
+}
+
 /// An alloy-powered REVM [Database][database_interface::Database].
 ///
 /// When accessing the database, it'll use the given provider to fetch the corresponding account's data.
@@ -41,15 +67,29 @@ pub struct AlloyDB<N: Network, P: Provider<N>> {
     provider: P,
     /// The block number on which the queries will be based on.
     block_number: BlockId,
+    /// RPC methods used to fetch accounts and storage.
+    fetch_mode: FetchMode,
+    /// Storage reads waiting to be sent in the next JSON-RPC batch.
+    storage_queue: Mutex<Vec<QueuedStorage>>,
     _marker: core::marker::PhantomData<fn() -> N>,
 }
 
+/// Storage read queued by [DatabaseAsyncRef::storage_async_ref].
+#[derive(Debug)]
+struct QueuedStorage {
+    address: Address,
+    index: U256,
+    sender: oneshot::Sender<Result<U256, DBTransportError>>,
+}
+
 impl<N: Network, P: Provider<N>> AlloyDB<N, P> {
     /// Creates a new AlloyDB instance, with a [Provider] and a block.
     pub fn new(provider: P, block_number: BlockId) -> Self {
         Self {
             provider,
             block_number,
+            fetch_mode: FetchMode::default(),
+            storage_queue: Mutex::default(),
             _marker: core::marker::PhantomData,
         }
     }
@@ -58,12 +98,267 @@ impl<N: Network, P: Provider<N>> AlloyDB<N, P> {
     pub fn set_block_number(&mut self, block_number: BlockId) {
         self.block_number = block_number;
     }
+
+    /// Sets RPC methods used to fetch accounts and storage.
+    pub fn set_fetch_mode(&mut self, fetch_mode: FetchMode) {
+        self.fetch_mode = fetch_mode;
+    }
+
+    /// Fetches multiple storage slots of an account in one request.
+    ///
+    /// With [FetchMode::Proof] a single `eth_getProof` is used, otherwise the
+    /// `eth_getStorageAt` calls are sent as one JSON-RPC batch.
+    pub async fn storage_batch_async_ref(
+        &self,
+        address: Address,
+        indices: &[U256],
+    ) -> Result<Vec<U256>, DBTransportError> {
+        let slots = indices
+            .iter()
+            .map(|index| (address, *index))
+            .collect::<Vec<_>>();
+        self.fetch_storage(&slots).await
+    }
+
+    /// Fetches storage slots of any accounts in one JSON-RPC batch.
+    ///
+    /// With [FetchMode::Proof] there is one `eth_getProof` per account, and the values are
+    /// matched to the slots by their key.
+    async fn fetch_storage(
+        &self,
+        slots: &[(Address, U256)],
+    ) -> Result<Vec<U256>, DBTransportError> {
+        if slots.is_empty() {
+            return Ok(Vec::new());
+        }
+        let block = self.block_number;
+        let mut batch = BatchRequest::new(self.provider.client());
+        match self.fetch_mode {
+            FetchMode::Proof => {
+                let mut keys: HashMap<Address, Vec<B256>> = HashMap::default();
+                for (address, index) in slots {
+                    keys.entry(*address).or_default().push(B256::from(*index));
+                }
+                let waiters = keys
+                    .iter()
+                    .map(|(address, keys)| {
+                        batch.add_call::<_, EIP1186AccountProofResponse>(
+                            "eth_getProof",
+                            &(address, keys, block),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                batch.send().await?;
+
+                let mut values: HashMap<_, U256> = HashMap::default();
+                for waiter in waiters {
+                    let proof = waiter.await?;
+                    values.extend(
+                        proof
+                            .storage_proof
+                            .into_iter()
+                            .map(|slot| ((proof.address, slot.key.as_b256()), slot.value)),
+                    );
+                }
+                slots
+                    .iter()
+                    .map(|(address, index)| {
+                        values
+                            .get(&(*address, B256::from(*index)))
+                            .copied()
+                            .ok_or_else(|| {
+                                DBTransportError(TransportErrorKind::custom_str(&format!(
+                                    "missing proof of storage slot {index} of {address}"
+                                )))
+                            })
+                    })
+                    .collect()
+            }
+            FetchMode::Plain => {
+                let waiters = slots
+                    .iter()
+                    .map(|(address, index)| {
+                        batch.add_call::<_, U256>("eth_getStorageAt", &(address, index, block))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                batch.send().await?;
+
+                let mut values = Vec::with_capacity(waiters.len());
+                for waiter in waiters {
+                    values.push(waiter.await?);
+                }
+                Ok(values)
+            }
+        }
+    }
+
+    /// Fetches all accounts and storage slots of the access list.
+    ///
+    /// All requests are sent as one JSON-RPC batch. With [FetchMode::Proof] a second batch
+    /// fetches code of accounts that have it.
+    pub async fn prefetch(
+        &self,
+        access_list: &AccessList,
+    ) -> Result<Vec<PrefetchedAccount>, DBTransportError> {
+        if access_list.is_empty() {
+            return Ok(Vec::new());
+        }
+        match self.fetch_mode {
+            FetchMode::Proof => self.prefetch_proof(access_list).await,
+            FetchMode::Plain => self.prefetch_plain(access_list).await,
+        }
+    }
+
+    /// Fetches all accounts and storage slots of the access list and inserts them into `cache_db`.
+    ///
+    /// Used to warm the cache before execution, see [AlloyDB::prefetch].
+    pub async fn prefetch_into<ExtDB>(
+        &self,
+        access_list: &AccessList,
+        cache_db: &mut CacheDB<ExtDB>,
+    ) -> Result<(), DBTransportError> {
+        for account in self.prefetch(access_list).await? {
+            cache_db.insert_account_info(account.address, account.info);
+            let db_account = cache_db.accounts.entry(account.address).or_default();
+            db_account.storage.extend(account.storage);
+        }
+        Ok(())
+    }
+
+    async fn prefetch_plain(
+        &self,
+        access_list: &AccessList,
+    ) -> Result<Vec<PrefetchedAccount>, DBTransportError> {
+        let block = self.block_number;
+        let mut batch = BatchRequest::new(self.provider.client());
+        let mut waiters = Vec::with_capacity(access_list.len());
+        for item in access_list.iter() {
+            let address = item.address;
+            let nonce = batch.add_call::<_, U64>("eth_getTransactionCount", &(address, block))?;
+            let balance = batch.add_call::<_, U256>("eth_getBalance", &(address, block))?;
+            let code = batch.add_call::<_, Bytes>("eth_getCode", &(address, block))?;
+            let storage = item
+                .storage_keys
+                .iter()
+                .map(|key| {
+                    let index = U256::from_be_bytes(key.0);
+                    batch
+                        .add_call::<_, U256>("eth_getStorageAt", &(address, index, block))
+                        .map(|waiter| (index, waiter))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            waiters.push((address, nonce, balance, code, storage));
+        }
+        batch.send().await?;
+
+        let mut accounts = Vec::with_capacity(waiters.len());
+        for (address, nonce, balance, code, storage_waiters) in waiters {
+            let code = Bytecode::new_raw(code.await?);
+            let mut storage = Vec::with_capacity(storage_waiters.len());
+            for (index, waiter) in storage_waiters {
+                storage.push((index, waiter.await?));
+            }
+            accounts.push(PrefetchedAccount {
+                address,
+                info: AccountInfo::new(balance.await?, nonce.await?.to(), code.hash_slow(), code),
+                storage,
+            });
+        }
+        Ok(accounts)
+    }
+
+    async fn prefetch_proof(
+        &self,
+        access_list: &AccessList,
+    ) -> Result<Vec<PrefetchedAccount>, DBTransportError> {
+        let block = self.block_number;
+        let mut batch = BatchRequest::new(self.provider.client());
+        let waiters = access_list
+            .iter()
+            .map(|item| {
+                batch.add_call::<_, EIP1186AccountProofResponse>(
+                    "eth_getProof",
+                    &(item.address, &item.storage_keys, block),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        batch.send().await?;
+
+        let mut proofs = Vec::with_capacity(waiters.len());
+        for waiter in waiters {
+            proofs.push(waiter.await?);
+        }
+
+        let mut batch = BatchRequest::new(self.provider.client());
+        let code_waiters = proofs
+            .iter()
+            .map(|proof| {
+                has_code(proof.code_hash)
+                    .then(|| batch.add_call::<_, Bytes>("eth_getCode", &(proof.address, block)))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if code_waiters.iter().any(Option::is_some) {
+            batch.send().await?;
+        }
+
+        let mut accounts = Vec::with_capacity(proofs.len());
+        for (proof, code) in proofs.into_iter().zip(code_waiters) {
+            let code = match code {
+                Some(waiter) => Bytecode::new_raw(waiter.await?),
+                None => Bytecode::default(),
+            };
+            accounts.push(PrefetchedAccount {
+                address: proof.address,
+                info: AccountInfo::new(proof.balance, proof.nonce, code.hash_slow(), code),
+                storage: proof
+                    .storage_proof
+                    .into_iter()
+                    .map(|slot| (U256::from_be_bytes(slot.key.as_b256().0), slot.value))
+                    .collect(),
+            });
+        }
+        Ok(accounts)
+    }
+
+    async fn basic_proof(&self, address: Address) -> Result<Option<AccountInfo>, DBTransportError> {
+        let proof = self
+            .provider
+            .get_proof(address, Vec::new())
+            .block_id(self.block_number)
+            .await?;
+        let code = if has_code(proof.code_hash) {
+            let code = self
+                .provider
+                .get_code_at(address)
+                .block_id(self.block_number)
+                .await?;
+            Bytecode::new_raw(code.0.into())
+        } else {
+            Bytecode::default()
+        };
+        let code_hash = code.hash_slow();
+        Ok(Some(AccountInfo::new(
+            proof.balance,
+            proof.nonce,
+            code_hash,
+            code,
+        )))
+    }
+}
+
+/// Nodes return either zero or empty hash for accounts without code.
+fn has_code(code_hash: B256) -> bool {
+    code_hash != KECCAK_EMPTY && !code_hash.is_zero()
 }
 
 impl<N: Network, P: Provider<N>> DatabaseAsyncRef for AlloyDB<N, P> {
     type Error = DBTransportError;
 
     async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if self.fetch_mode == FetchMode::Proof {
+            return self.basic_proof(address).await;
+        }
         let nonce = self
             .provider
             .get_transaction_count(address)
@@ -102,20 +397,259 @@ impl<N: Network, P: Provider<N>> DatabaseAsyncRef for AlloyDB<N, P> {
         // This is not needed, as the code is already loaded with basic_ref
     }
 
+    /// Storage reads that run concurrently are sent together in one JSON-RPC batch.
     async fn storage_async_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
-        Ok(self
-            .provider
-            .get_storage_at(address, index)
-            .block_id(self.block_number)
-            .await?)
+        let (sender, receiver) = oneshot::channel();
+        self.storage_queue.lock().unwrap().push(QueuedStorage {
+            address,
+            index,
+            sender,
+        });
+        // Reads queued by other tasks until then join the batch. Whoever finds the queue
+        // non-empty sends it, so every read is sent even if the task that queued it is dropped.
+        tokio::task::yield_now().await;
+        let queued = mem::take(&mut *self.storage_queue.lock().unwrap());
+        if !queued.is_empty() {
+            let slots = queued
+                .iter()
+                .map(|read| (read.address, read.index))
+                .collect::<Vec<_>>();
+            match self.fetch_storage(&slots).await {
+                Ok(values) => {
+                    for (read, value) in queued.into_iter().zip(values) {
+                        let _ = read.sender.send(Ok(value));
+                    }
+                }
+                Err(error) => {
+                    let message = error.to_string();
+                    for read in queued {
+                        let error = TransportErrorKind::custom_str(&message);
+                        let _ = read.sender.send(Err(DBTransportError(error)));
+                    }
+                }
+            }
+        }
+        receiver.await.unwrap_or_else(|_| {
+            Err(DBTransportError(TransportErrorKind::custom_str(
+                "batched storage read was dropped",
+            )))
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_provider::ProviderBuilder;
+    use alloy_eips::eip2930::AccessListItem;
+    use alloy_json_rpc::{RequestPacket, ResponsePacket};
+    use alloy_provider::{network::Ethereum, ProviderBuilder, RootProvider};
+    use alloy_rpc_client::RpcClient;
+    use alloy_rpc_types_eth::EIP1186StorageProof;
+    use alloy_transport::TransportFut;
     use database_interface::{DatabaseRef, WrapDatabaseAsync};
+    use primitives::{bytes, keccak256};
+    use serde_json::{json, Value};
+    use std::{
+        sync::Arc,
+        task::{Context, Poll},
+    };
+
+    const CONTRACT: Address = Address::with_last_byte(2);
+    const CODE: Bytes = bytes!("6001600055");
+
+    /// Transport answering every request locally and recording the methods of each packet.
+    #[derive(Clone, Debug, Default)]
+    struct MockTransport {
+        packets: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    impl MockTransport {
+        fn packets(&self) -> Vec<Vec<String>> {
+            self.packets.lock().unwrap().clone()
+        }
+
+        /// Storage values are the slot plus one, only [CONTRACT] has code.
+        fn respond(method: &str, params: &Value) -> Value {
+            let address: Address = serde_json::from_value(params[0].clone()).unwrap();
+            let code = if address == CONTRACT {
+                CODE
+            } else {
+                Bytes::new()
+            };
+            match method {
+                "eth_getStorageAt" => {
+                    let index: U256 = serde_json::from_value(params[1].clone()).unwrap();
+                    json!(index + U256::from(1))
+                }
+                "eth_getProof" => {
+                    let keys: Vec<B256> = serde_json::from_value(params[1].clone()).unwrap();
+                    let storage_proof = keys
+                        .into_iter()
+                        // Nodes are not required to keep the order of the keys.
+                        .rev()
+                        .map(|key| EIP1186StorageProof {
+                            key: key.into(),
+                            value: U256::from_be_bytes(key.0) + U256::from(1),
+                            proof: Vec::new(),
+                        })
+                        .collect();
+                    json!(EIP1186AccountProofResponse {
+                        address,
+                        balance: U256::from(100),
+                        code_hash: keccak256(&code),
+                        nonce: 1,
+                        storage_proof,
+                        ..Default::default()
+                    })
+                }
+                "eth_getTransactionCount" => json!(U64::from(1)),
+                "eth_getBalance" => json!(U256::from(100)),
+                "eth_getCode" => json!(code),
+                _ => panic!("unexpected method {method}"),
+            }
+        }
+    }
+
+    impl tower::Service<RequestPacket> for MockTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, packet: RequestPacket) -> Self::Future {
+            let is_batch = matches!(packet, RequestPacket::Batch(_));
+            let requests: Value = serde_json::from_str(packet.serialize().unwrap().get()).unwrap();
+            let requests = match requests {
+                Value::Array(requests) => requests,
+                request => vec![request],
+            };
+            let mut methods = Vec::new();
+            let mut responses = requests
+                .into_iter()
+                .map(|request| {
+                    let method = request["method"].as_str().unwrap();
+                    methods.push(method.to_string());
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": request["id"],
+                        "result": Self::respond(method, &request["params"]),
+                    })
+                })
+                .collect::<Vec<_>>();
+            self.packets.lock().unwrap().push(methods);
+            let response = if is_batch {
+                Value::Array(responses)
+            } else {
+                responses.remove(0)
+            };
+            let response: ResponsePacket = serde_json::from_str(&response.to_string()).unwrap();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    fn mock_db(fetch_mode: FetchMode) -> (AlloyDB<Ethereum, RootProvider>, MockTransport) {
+        let transport = MockTransport::default();
+        let provider = RootProvider::new(RpcClient::new(transport.clone(), true));
+        let mut db = AlloyDB::new(provider, BlockId::latest());
+        db.set_fetch_mode(fetch_mode);
+        (db, transport)
+    }
+
+    #[tokio::test]
+    async fn proof_storage_matched_by_key() {
+        let (db, transport) = mock_db(FetchMode::Proof);
+        let indices = [U256::from(1), U256::from(2), U256::from(3)];
+        assert_eq!(
+            db.storage_batch_async_ref(CONTRACT, &indices)
+                .await
+                .unwrap(),
+            [U256::from(2), U256::from(3), U256::from(4)]
+        );
+        assert_eq!(transport.packets(), [["eth_getProof"]]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_storage_reads_are_batched() {
+        let (db, transport) = mock_db(FetchMode::Plain);
+        let (a, b, c) = tokio::join!(
+            db.storage_async_ref(CONTRACT, U256::from(1)),
+            db.storage_async_ref(CONTRACT, U256::from(2)),
+            db.storage_async_ref(Address::with_last_byte(3), U256::from(3)),
+        );
+        assert_eq!(
+            (a.unwrap(), b.unwrap(), c.unwrap()),
+            (U256::from(2), U256::from(3), U256::from(4))
+        );
+        assert_eq!(transport.packets(), [["eth_getStorageAt"; 3]]);
+
+        // One proof per account.
+        let (db, transport) = mock_db(FetchMode::Proof);
+        let (a, b, c) = tokio::join!(
+            db.storage_async_ref(CONTRACT, U256::from(1)),
+            db.storage_async_ref(CONTRACT, U256::from(2)),
+            db.storage_async_ref(Address::with_last_byte(3), U256::from(3)),
+        );
+        assert_eq!(
+            (a.unwrap(), b.unwrap(), c.unwrap()),
+            (U256::from(2), U256::from(3), U256::from(4))
+        );
+        assert_eq!(transport.packets(), [["eth_getProof"; 2]]);
+
+        // Reads that do not overlap are sent on their own.
+        assert_eq!(
+            db.storage_async_ref(CONTRACT, U256::from(5)).await.unwrap(),
+            U256::from(6)
+        );
+        assert_eq!(transport.packets().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn prefetch_access_list() {
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: CONTRACT,
+                storage_keys: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+            },
+            AccessListItem {
+                address: Address::with_last_byte(3),
+                storage_keys: vec![],
+            },
+        ]);
+        let code = Bytecode::new_raw(CODE);
+        let expected = vec![
+            PrefetchedAccount {
+                address: CONTRACT,
+                info: AccountInfo::new(U256::from(100), 1, code.hash_slow(), code),
+                storage: vec![
+                    (U256::from(1), U256::from(2)),
+                    (U256::from(2), U256::from(3)),
+                ],
+            },
+            PrefetchedAccount {
+                address: Address::with_last_byte(3),
+                info: AccountInfo::new(U256::from(100), 1, KECCAK_EMPTY, Bytecode::default()),
+                storage: vec![],
+            },
+        ];
+
+        let (db, transport) = mock_db(FetchMode::Plain);
+        assert_eq!(db.prefetch(&access_list).await.unwrap(), expected);
+        let packets = transport.packets();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].len(), 8);
+
+        let (db, transport) = mock_db(FetchMode::Proof);
+        let mut accounts = db.prefetch(&access_list).await.unwrap();
+        accounts[0].storage.sort();
+        assert_eq!(accounts, expected);
+        assert_eq!(
+            transport.packets(),
+            [vec!["eth_getProof"; 2], vec!["eth_getCode"]]
+        );
+    }
 
     #[test]
     #[ignore = "flaky RPC"]
@@ -0,0 +1,287 @@
+//! EIP-2935 block hash provider.
+use database_interface::{Database, DatabaseCommit, DatabaseRef};
+use primitives::{Address, HashMap, B256, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, U256};
+use state::{Account, AccountInfo, Bytecode, EvmStorageSlot};
+
+/// Returns the storage slot of the history contract holding the hash of the block `number`.
+pub fn history_slot(number: u64) -> U256 {
+    U256::from(number % BLOCKHASH_SERVE_WINDOW as u64)
+}
+
+/// Database wrapper that serves block hashes from the EIP-2935 history contract.
+///
+/// Since Prague the hashes of the last [`BLOCKHASH_SERVE_WINDOW`] blocks are stored in a ring
+/// buffer in the storage of [`BLOCKHASH_STORAGE_ADDRESS`]. Hashes of blocks inside that window
+/// are read from the contract storage of the wrapped database, other hashes and hashes that are
+/// not yet stored are requested from [`Database::block_hash`].
+///
+/// The ring buffer is kept up to date by calling [`BlockHashHistoryDB::apply_block`] before each
+/// block is executed.
+///
+/// Served hashes can be cross-checked against [`Database::block_hash`] in debug builds, see
+/// [`BlockHashHistoryDB::with_cross_check`].
+#[derive(Clone, Debug)]
+pub struct BlockHashHistoryDB<DB> {
+    db: DB,
+    /// Number of the block that is executed.
+    block_number: u64,
+    /// Compare the hashes served from the contract with the database in debug builds.
+    cross_check: bool,
+}
+
+impl<DB> BlockHashHistoryDB<DB> {
+    /// Wraps the database for executing the block `block_number`.
+    pub fn new(db: DB, block_number: u64) -> Self {
+        Self {
+            db,
+            block_number,
+            cross_check: false,
+        }
+    }
+
+    /// Enables or disables cross-checking of the served hashes in debug builds.
+    ///
+    /// Disabled by default. Only enable it for databases that know the hashes of all blocks in
+    /// the window, a mismatch fails a debug assertion.
+    pub fn with_cross_check(mut self, cross_check: bool) -> Self {
+        self.cross_check = cross_check;
+        self
+    }
+
+    /// Returns the number of the block that is executed.
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Returns the wrapped database.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Returns the wrapped database mutably.
+    pub fn db_mut(&mut self) -> &mut DB {
+        &mut self.db
+    }
+
+    /// Consumes the wrapper and returns the database.
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+
+    /// Returns true if the hash of the block `number` is in the ring buffer window.
+    fn in_window(&self, number: u64) -> bool {
+        number < self.block_number && self.block_number - number <= BLOCKHASH_SERVE_WINDOW as u64
+    }
+}
+
+impl<DB: Database + DatabaseCommit> BlockHashHistoryDB<DB> {
+    /// Prepares the block `number` for execution by storing the hash of its parent block in the
+    /// history contract.
+    ///
+    /// Does the same as the EIP-2935 system call. Nothing is stored if the history contract is
+    /// not deployed.
+    pub fn apply_block(&mut self, number: u64, parent_hash: B256) -> Result<(), DB::Error> {
+        self.block_number = number;
+        let Some(parent) = number.checked_sub(1) else {
+            return Ok(());
+        };
+        let Some(info) = self.db.basic(BLOCKHASH_STORAGE_ADDRESS)? else {
+            return Ok(());
+        };
+        let slot = history_slot(parent);
+        let original = self.db.storage(BLOCKHASH_STORAGE_ADDRESS, slot)?;
+
+        let mut account = Account::from(info);
+        account.storage.insert(
+            slot,
+            EvmStorageSlot::new_changed(original, parent_hash.into()),
+        );
+        account.mark_touch();
+        self.db
+            .commit(HashMap::from_iter([(BLOCKHASH_STORAGE_ADDRESS, account)]));
+        Ok(())
+    }
+}
+
+impl<DB: Database> Database for BlockHashHistoryDB<DB> {
+    type Error = DB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        if !self.in_window(number) {
+            return self.db.block_hash(number);
+        }
+        let hash = B256::from(
+            self.db
+                .storage(BLOCKHASH_STORAGE_ADDRESS, history_slot(number))?,
+        );
+        if hash.is_zero() {
+            return self.db.block_hash(number);
+        }
+        if cfg!(debug_assertions) && self.cross_check {
+            let expected = self.db.block_hash(number)?;
+            debug_assert_eq!(
+                hash, expected,
+                "history contract hash of block {number} does not match the database"
+            );
+        }
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for BlockHashHistoryDB<DB> {
+    type Error = DB::Error;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        if !self.in_window(number) {
+            return self.db.block_hash_ref(number);
+        }
+        let hash = B256::from(
+            self.db
+                .storage_ref(BLOCKHASH_STORAGE_ADDRESS, history_slot(number))?,
+        );
+        if hash.is_zero() {
+            return self.db.block_hash_ref(number);
+        }
+        if cfg!(debug_assertions) && self.cross_check {
+            let expected = self.db.block_hash_ref(number)?;
+            debug_assert_eq!(
+                hash, expected,
+                "history contract hash of block {number} does not match the database"
+            );
+        }
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for BlockHashHistoryDB<DB> {
+    #[inline]
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheDB, EmptyDB};
+    use primitives::{address, keccak256};
+
+    fn db_with_contract() -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            BLOCKHASH_STORAGE_ADDRESS,
+            AccountInfo::new(U256::ZERO, 1, B256::ZERO, Bytecode::default()),
+        );
+        db
+    }
+
+    #[test]
+    fn serves_hashes_from_history_contract() {
+        // EmptyDB returns keccak of the block number as its hash.
+        let mut db = BlockHashHistoryDB::new(db_with_contract(), 0).with_cross_check(true);
+        for number in 1..=10u64 {
+            db.apply_block(number, keccak256((number - 1).to_string().as_bytes()))
+                .unwrap();
+        }
+        assert_eq!(
+            db.db()
+                .storage_ref(BLOCKHASH_STORAGE_ADDRESS, history_slot(9))
+                .unwrap(),
+            U256::from_be_bytes(keccak256(9.to_string().as_bytes()).0)
+        );
+        assert_eq!(
+            db.block_hash(9).unwrap(),
+            keccak256(9.to_string().as_bytes())
+        );
+        assert_eq!(
+            db.block_hash_ref(0).unwrap(),
+            keccak256(0.to_string().as_bytes())
+        );
+        // Current block is not in the window.
+        assert_eq!(
+            db.block_hash(10).unwrap(),
+            keccak256(10.to_string().as_bytes())
+        );
+    }
+
+    #[test]
+    fn ring_buffer_wraps() {
+        let window = BLOCKHASH_SERVE_WINDOW as u64;
+        // Hashes differ from `EmptyDB`, which is not cross-checked by default.
+        let mut db = BlockHashHistoryDB::new(db_with_contract(), 0);
+        db.apply_block(2, B256::with_last_byte(1)).unwrap();
+        db.apply_block(window + 2, B256::with_last_byte(2)).unwrap();
+
+        // Slot of block 1 was overwritten by block `window + 1`.
+        assert_eq!(db.block_hash(window + 1).unwrap(), B256::with_last_byte(2));
+        // Block 1 is out of the window and is served by the database.
+        assert_eq!(
+            db.block_hash(1).unwrap(),
+            keccak256(1.to_string().as_bytes())
+        );
+    }
+
+    #[test]
+    fn history_contract_address() {
+        // Address of the deployed EIP-2935 history contract.
+        let address = address!("0x0000F90827F1C53a10cb7A02335B175320002935");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            address,
+            AccountInfo::new(U256::ZERO, 1, B256::ZERO, Bytecode::default()),
+        );
+        let mut db = BlockHashHistoryDB::new(db, 0);
+        db.apply_block(2, B256::with_last_byte(1)).unwrap();
+        assert_eq!(
+            db.db().storage_ref(address, history_slot(1)).unwrap(),
+            U256::from(1)
+        );
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(1));
+    }
+
+    #[test]
+    fn missing_contract() {
+        let mut db = BlockHashHistoryDB::new(CacheDB::new(EmptyDB::default()), 0);
+        db.apply_block(5, B256::with_last_byte(1)).unwrap();
+        assert_eq!(db.block_number(), 5);
+        assert!(db
+            .db()
+            .storage_ref(BLOCKHASH_STORAGE_ADDRESS, history_slot(4))
+            .unwrap()
+            .is_zero());
+        assert_eq!(
+            db.block_hash(4).unwrap(),
+            keccak256(4.to_string().as_bytes())
+        );
+    }
+}
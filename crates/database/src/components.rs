@@ -0,0 +1,332 @@
+//! Database that is composed of separate account, code, storage and block hash components.
+pub mod account;
+pub mod block_hash;
+pub mod code;
+pub mod storage;
+
+pub use account::{AccountStore, AccountStoreCommit, AccountStoreRef};
+pub use block_hash::{BlockHashStore, BlockHashStoreRef, BlockHashTable, MissingBlockHash};
+pub use code::{CodeCache, CodeStore, CodeStoreCommit, CodeStoreRef};
+pub use storage::{StorageStore, StorageStoreCommit, StorageStoreRef};
+
+use core::{error::Error, fmt};
+use database_interface::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use primitives::{Address, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{Account, AccountInfo, Bytecode};
+
+/// Database composed of components that serve its methods.
+///
+/// Every component can be a different store, for example accounts and storage served by a
+/// fork database, code from a local [`CodeCache`] and block hashes from a [`BlockHashTable`].
+/// Whole databases are used as components by wrapping them in [`DatabaseStore`].
+///
+/// Committed changes are split between the components: account information goes to the account
+/// store, bytecode of deployed contracts to the code store and changed storage slots to the
+/// storage store, see [`AccountStoreCommit`], [`CodeStoreCommit`] and [`StorageStoreCommit`].
+/// Components that are shared, e.g. through an `Arc`, can not be committed to.
+#[derive(Clone, Debug, Default)]
+pub struct DatabaseComponents<A, C, S, BH> {
+    /// Account store.
+    pub accounts: A,
+    /// Code store.
+    pub code: C,
+    /// Storage store.
+    pub storage: S,
+    /// Block hash store.
+    pub block_hash: BH,
+}
+
+impl<A, C, S, BH> DatabaseComponents<A, C, S, BH> {
+    /// Creates a new database from its components.
+    pub fn new(accounts: A, code: C, storage: S, block_hash: BH) -> Self {
+        Self {
+            accounts,
+            code,
+            storage,
+            block_hash,
+        }
+    }
+}
+
+/// Error of the [`DatabaseComponents`] component that failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DatabaseComponentError<AE, CE, SE, BHE> {
+    /// Account store error.
+    Account(AE),
+    /// Code store error.
+    Code(CE),
+    /// Storage store error.
+    Storage(SE),
+    /// Block hash store error.
+    BlockHash(BHE),
+}
+
+impl<AE, CE, SE, BHE> fmt::Display for DatabaseComponentError<AE, CE, SE, BHE>
+where
+    AE: fmt::Display,
+    CE: fmt::Display,
+    SE: fmt::Display,
+    BHE: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(e) => e.fmt(f),
+            Self::Code(e) => e.fmt(f),
+            Self::Storage(e) => e.fmt(f),
+            Self::BlockHash(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<AE, CE, SE, BHE> Error for DatabaseComponentError<AE, CE, SE, BHE>
+where
+    AE: Error,
+    CE: Error,
+    SE: Error,
+    BHE: Error,
+{
+}
+
+impl<AE, CE, SE, BHE> DBErrorMarker for DatabaseComponentError<AE, CE, SE, BHE> {}
+
+impl<A, C, S, BH> Database for DatabaseComponents<A, C, S, BH>
+where
+    A: AccountStore,
+    C: CodeStore,
+    S: StorageStore,
+    BH: BlockHashStore,
+{
+    type Error = DatabaseComponentError<A::Error, C::Error, S::Error, BH::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.accounts
+            .basic(address)
+            .map_err(DatabaseComponentError::Account)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code
+            .code_by_hash(code_hash)
+            .map_err(DatabaseComponentError::Code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage
+            .storage(address, index)
+            .map_err(DatabaseComponentError::Storage)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash
+            .block_hash(number)
+            .map_err(DatabaseComponentError::BlockHash)
+    }
+}
+
+impl<A, C, S, BH> DatabaseRef for DatabaseComponents<A, C, S, BH>
+where
+    A: AccountStoreRef,
+    C: CodeStoreRef,
+    S: StorageStoreRef,
+    BH: BlockHashStoreRef,
+{
+    type Error = DatabaseComponentError<A::Error, C::Error, S::Error, BH::Error>;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.accounts
+            .basic(address)
+            .map_err(DatabaseComponentError::Account)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code
+            .code_by_hash(code_hash)
+            .map_err(DatabaseComponentError::Code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage
+            .storage(address, index)
+            .map_err(DatabaseComponentError::Storage)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash
+            .block_hash(number)
+            .map_err(DatabaseComponentError::BlockHash)
+    }
+}
+
+impl<A, C, S, BH> DatabaseCommit for DatabaseComponents<A, C, S, BH>
+where
+    A: AccountStoreCommit,
+    C: CodeStoreCommit,
+    S: StorageStoreCommit,
+{
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        for (address, mut account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                self.accounts.commit_account(address, None);
+                self.storage
+                    .commit_storage(address, true, HashMap::default());
+                continue;
+            }
+            let info = &mut account.info;
+            if let Some(code) = info.code.as_ref().filter(|code| !code.is_empty()) {
+                if info.code_hash == KECCAK_EMPTY {
+                    info.code_hash = code.hash_slow();
+                }
+                self.code.commit_code(info.code_hash, code.clone());
+            }
+            if info.code_hash.is_zero() {
+                info.code_hash = KECCAK_EMPTY;
+            }
+            let is_created = account.is_created();
+            if is_created || !account.storage.is_empty() {
+                let storage = account
+                    .storage
+                    .into_iter()
+                    .map(|(key, value)| (key, value.present_value()))
+                    .collect();
+                self.storage.commit_storage(address, is_created, storage);
+            }
+            self.accounts.commit_account(address, Some(account.info));
+        }
+    }
+}
+
+/// Uses a whole database as any of the [`DatabaseComponents`].
+///
+/// Implements the component traits with [`Database`] and the `Ref` component traits with
+/// [`DatabaseRef`]. A shared reference or an `Arc` of the store implements the component
+/// traits too, which allows using one database for more than one component.
+#[derive(Clone, Debug, Default)]
+pub struct DatabaseStore<DB>(pub DB);
+
+impl<DB: Database> AccountStore for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic(address)
+    }
+}
+
+impl<DB: Database> CodeStore for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash(code_hash)
+    }
+}
+
+impl<DB: Database> StorageStore for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage(address, index)
+    }
+}
+
+impl<DB: Database> BlockHashStore for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.0.block_hash(number)
+    }
+}
+
+impl<DB: DatabaseRef> AccountStoreRef for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn basic(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.basic_ref(address)
+    }
+}
+
+impl<DB: DatabaseRef> CodeStoreRef for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn code_by_hash(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.code_by_hash_ref(code_hash)
+    }
+}
+
+impl<DB: DatabaseRef> StorageStoreRef for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn storage(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.0.storage_ref(address, index)
+    }
+}
+
+impl<DB: DatabaseRef> BlockHashStoreRef for DatabaseStore<DB> {
+    type Error = DB::Error;
+
+    fn block_hash(&self, number: u64) -> Result<B256, Self::Error> {
+        self.0.block_hash_ref(number)
+    }
+}
+
+impl<DB: AccountStoreCommit> AccountStoreCommit for DatabaseStore<DB> {
+    fn commit_account(&mut self, address: Address, info: Option<AccountInfo>) {
+        self.0.commit_account(address, info);
+    }
+}
+
+impl<DB: CodeStoreCommit> CodeStoreCommit for DatabaseStore<DB> {
+    fn commit_code(&mut self, code_hash: B256, code: Bytecode) {
+        self.0.commit_code(code_hash, code);
+    }
+}
+
+impl<DB: StorageStoreCommit> StorageStoreCommit for DatabaseStore<DB> {
+    fn commit_storage(&mut self, address: Address, wipe: bool, storage: HashMap<U256, U256>) {
+        self.0.commit_storage(address, wipe, storage);
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for DatabaseStore<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.0.commit(changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheDB, EmptyDB};
+    use std::sync::Arc;
+
+    #[test]
+    fn compose_components() {
+        let address = Address::with_last_byte(1);
+        let code = Bytecode::new_raw(primitives::bytes!("6001600055"));
+
+        let mut fork = CacheDB::new(EmptyDB::default());
+        fork.insert_account_info(address, AccountInfo::from_balance(U256::from(10)));
+        fork.insert_account_storage(address, U256::ZERO, U256::from(5))
+            .unwrap();
+        let fork = Arc::new(DatabaseStore(fork));
+
+        let mut code_cache = CodeCache::new(fork.clone());
+        let code_hash = code_cache.insert(code.clone());
+        let mut db = DatabaseComponents::new(
+            fork.clone(),
+            code_cache,
+            fork,
+            BlockHashTable::from_iter([(1, B256::with_last_byte(1))]),
+        );
+
+        assert_eq!(db.basic(address).unwrap().unwrap().balance, U256::from(10));
+        assert_eq!(db.storage(address, U256::ZERO).unwrap(), U256::from(5));
+        assert_eq!(db.code_by_hash(code_hash).unwrap(), code);
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(1));
+        assert_eq!(
+            db.block_hash(2),
+            Err(DatabaseComponentError::BlockHash(MissingBlockHash(2)))
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Account database component.
+use auto_impl::auto_impl;
+use core::{error::Error, ops::Deref};
+use primitives::Address;
+use state::AccountInfo;
+use std::sync::Arc;
+
+/// Account store component, serves [`Database::basic`][database_interface::Database::basic].
+#[auto_impl(&mut, Box)]
+pub trait AccountStore {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets basic account information.
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error>;
+}
+
+/// Account store component, serves [`DatabaseRef::basic_ref`][database_interface::DatabaseRef::basic_ref].
+#[auto_impl(&, &mut, Box, Rc, Arc)]
+pub trait AccountStoreRef {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets basic account information.
+    fn basic(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error>;
+}
+
+/// Account store component that account changes are committed to, see
+/// [`DatabaseComponents`][crate::DatabaseComponents].
+#[auto_impl(&mut, Box)]
+pub trait AccountStoreCommit {
+    /// Sets the account information, `None` if the account was destroyed.
+    fn commit_account(&mut self, address: Address, info: Option<AccountInfo>);
+}
+
+impl<T> AccountStore for &T
+where
+    T: AccountStoreRef,
+{
+    type Error = <T as AccountStoreRef>::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        AccountStoreRef::basic(*self, address)
+    }
+}
+
+impl<T> AccountStore for Arc<T>
+where
+    T: AccountStoreRef,
+{
+    type Error = <T as AccountStoreRef>::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.deref().basic(address)
+    }
+}
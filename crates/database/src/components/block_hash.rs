@@ -0,0 +1,85 @@
+//! Block hash database component.
+use auto_impl::auto_impl;
+use core::{error::Error, fmt, ops::Deref};
+use primitives::{HashMap, B256};
+use std::sync::Arc;
+
+/// Block hash store component, serves [`Database::block_hash`][database_interface::Database::block_hash].
+#[auto_impl(&mut, Box)]
+pub trait BlockHashStore {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets block hash by block number.
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error>;
+}
+
+/// Block hash store component, serves [`DatabaseRef::block_hash_ref`][database_interface::DatabaseRef::block_hash_ref].
+#[auto_impl(&, &mut, Box, Rc, Arc)]
+pub trait BlockHashStoreRef {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets block hash by block number.
+    fn block_hash(&self, number: u64) -> Result<B256, Self::Error>;
+}
+
+impl<T> BlockHashStore for &T
+where
+    T: BlockHashStoreRef,
+{
+    type Error = <T as BlockHashStoreRef>::Error;
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        BlockHashStoreRef::block_hash(*self, number)
+    }
+}
+
+impl<T> BlockHashStore for Arc<T>
+where
+    T: BlockHashStoreRef,
+{
+    type Error = <T as BlockHashStoreRef>::Error;
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.deref().block_hash(number)
+    }
+}
+
+/// Error of a [`BlockHashTable`] that does not contain the requested block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MissingBlockHash(pub u64);
+
+impl fmt::Display for MissingBlockHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "block hash {} not found", self.0)
+    }
+}
+
+impl Error for MissingBlockHash {}
+
+/// Static table of block hashes by block number.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockHashTable(pub HashMap<u64, B256>);
+
+impl FromIterator<(u64, B256)> for BlockHashTable {
+    fn from_iter<I: IntoIterator<Item = (u64, B256)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl BlockHashStoreRef for BlockHashTable {
+    type Error = MissingBlockHash;
+
+    fn block_hash(&self, number: u64) -> Result<B256, Self::Error> {
+        self.0.get(&number).copied().ok_or(MissingBlockHash(number))
+    }
+}
+
+impl BlockHashStore for BlockHashTable {
+    type Error = MissingBlockHash;
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        BlockHashStoreRef::block_hash(self, number)
+    }
+}
@@ -0,0 +1,104 @@
+//! Code database component.
+use auto_impl::auto_impl;
+use core::{error::Error, ops::Deref};
+use primitives::{HashMap, B256};
+use state::Bytecode;
+use std::sync::Arc;
+
+/// Code store component, serves [`Database::code_by_hash`][database_interface::Database::code_by_hash].
+#[auto_impl(&mut, Box)]
+pub trait CodeStore {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets account code by its hash.
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error>;
+}
+
+/// Code store component, serves [`DatabaseRef::code_by_hash_ref`][database_interface::DatabaseRef::code_by_hash_ref].
+#[auto_impl(&, &mut, Box, Rc, Arc)]
+pub trait CodeStoreRef {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets account code by its hash.
+    fn code_by_hash(&self, code_hash: B256) -> Result<Bytecode, Self::Error>;
+}
+
+/// Code store component that new bytecode is committed to, see
+/// [`DatabaseComponents`][crate::DatabaseComponents].
+#[auto_impl(&mut, Box)]
+pub trait CodeStoreCommit {
+    /// Inserts the bytecode of a deployed contract.
+    fn commit_code(&mut self, code_hash: B256, code: Bytecode);
+}
+
+impl<T> CodeStore for &T
+where
+    T: CodeStoreRef,
+{
+    type Error = <T as CodeStoreRef>::Error;
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        CodeStoreRef::code_by_hash(*self, code_hash)
+    }
+}
+
+impl<T> CodeStore for Arc<T>
+where
+    T: CodeStoreRef,
+{
+    type Error = <T as CodeStoreRef>::Error;
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.deref().code_by_hash(code_hash)
+    }
+}
+
+/// Local bytecode cache in front of another code store.
+///
+/// Bytecode is immutable, so every code returned by the inner store is kept and served from
+/// memory afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct CodeCache<C> {
+    /// Cached bytecodes by their hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Store that is asked on a cache miss.
+    pub inner: C,
+}
+
+impl<C> CodeCache<C> {
+    /// Creates an empty cache in front of the store.
+    pub fn new(inner: C) -> Self {
+        Self {
+            contracts: HashMap::default(),
+            inner,
+        }
+    }
+
+    /// Inserts the bytecode into the cache.
+    pub fn insert(&mut self, code: Bytecode) -> B256 {
+        let code_hash = code.hash_slow();
+        self.contracts.insert(code_hash, code);
+        code_hash
+    }
+}
+
+impl<C: CodeStore> CodeStore for CodeCache<C> {
+    type Error = C::Error;
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        let code = self.inner.code_by_hash(code_hash)?;
+        self.contracts.insert(code_hash, code.clone());
+        Ok(code)
+    }
+}
+
+impl<C> CodeStoreCommit for CodeCache<C> {
+    fn commit_code(&mut self, code_hash: B256, code: Bytecode) {
+        self.contracts.insert(code_hash, code);
+    }
+}
@@ -0,0 +1,58 @@
+//! Storage database component.
+use auto_impl::auto_impl;
+use core::{error::Error, ops::Deref};
+use primitives::{Address, HashMap, U256};
+use std::sync::Arc;
+
+/// Storage store component, serves [`Database::storage`][database_interface::Database::storage].
+#[auto_impl(&mut, Box)]
+pub trait StorageStore {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets storage value of address at index.
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error>;
+}
+
+/// Storage store component, serves [`DatabaseRef::storage_ref`][database_interface::DatabaseRef::storage_ref].
+#[auto_impl(&, &mut, Box, Rc, Arc)]
+pub trait StorageStoreRef {
+    /// The component error type.
+    type Error: Error;
+
+    /// Gets storage value of address at index.
+    fn storage(&self, address: Address, index: U256) -> Result<U256, Self::Error>;
+}
+
+/// Storage store component that storage changes are committed to, see
+/// [`DatabaseComponents`][crate::DatabaseComponents].
+#[auto_impl(&mut, Box)]
+pub trait StorageStoreCommit {
+    /// Sets the changed storage slots of the account.
+    ///
+    /// If `wipe` is set, the account storage was cleared before the change, by a selfdestruct
+    /// or by creating the account, and all other slots are zero.
+    fn commit_storage(&mut self, address: Address, wipe: bool, storage: HashMap<U256, U256>);
+}
+
+impl<T> StorageStore for &T
+where
+    T: StorageStoreRef,
+{
+    type Error = <T as StorageStoreRef>::Error;
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        StorageStoreRef::storage(*self, address, index)
+    }
+}
+
+impl<T> StorageStore for Arc<T>
+where
+    T: StorageStoreRef,
+{
+    type Error = <T as StorageStoreRef>::Error;
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.deref().storage(address, index)
+    }
+}
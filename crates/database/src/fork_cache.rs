@@ -0,0 +1,377 @@
+use core::{
+    error::Error,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use database_interface::{DBErrorMarker, Database, DatabaseOp, DatabaseRef};
+use primitives::{Address, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+/// Responses cached by [`ForkCacheDB`] for a single (chain id, block) pair.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ForkCache {
+    /// Account info without code. `None` means account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots by account.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Contracts by code hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl ForkCache {
+    /// Loads the cache from a JSON file. Returns an empty cache if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(io::Error::from),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes the cache to a JSON file.
+    ///
+    /// The file is written next to `path` first and then renamed, so a crash never leaves
+    /// a partially written cache behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(self).map_err(io::Error::from)?)?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// Error returned by [`ForkCacheDB`].
+#[derive(Debug)]
+pub enum ForkCacheError<E> {
+    /// Error from the wrapped database.
+    Database(E),
+    /// Value of the database call is not cached and the database is offline.
+    Offline(DatabaseOp),
+}
+
+impl<E> DBErrorMarker for ForkCacheError<E> {}
+
+impl<E: fmt::Display> fmt::Display for ForkCacheError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Database(e) => e.fmt(f),
+            Self::Offline(op) => write!(f, "{op} is not cached and database is offline"),
+        }
+    }
+}
+
+impl<E: Error> Error for ForkCacheError<E> {}
+
+/// A [DatabaseRef] wrapper that caches responses of a remote database on disk.
+///
+/// Intended to wrap `AlloyDB` (through `WrapDatabaseAsync`) when forking a chain. Responses
+/// are cached per chain id and block in `<cache_dir>/<chain_id>/<block>.json`, loaded on creation
+/// and written back with [`ForkCacheDB::flush`] or on drop.
+///
+/// Created with [`ForkCacheDB::offline`], all reads are served from the cache and a miss is an
+/// error.
+#[derive(Debug)]
+pub struct ForkCacheDB<DB> {
+    db: Option<DB>,
+    path: PathBuf,
+    cache: RwLock<ForkCache>,
+    dirty: AtomicBool,
+}
+
+impl<DB> ForkCacheDB<DB> {
+    /// Creates a new cache over `db`, loading previously cached responses for the chain and block.
+    pub fn new(
+        db: DB,
+        cache_dir: impl AsRef<Path>,
+        chain_id: u64,
+        block_number: u64,
+    ) -> io::Result<Self> {
+        let mut this = Self::offline(cache_dir, chain_id, block_number)?;
+        this.db = Some(db);
+        Ok(this)
+    }
+
+    /// Creates a cache that is served only from disk.
+    pub fn offline(
+        cache_dir: impl AsRef<Path>,
+        chain_id: u64,
+        block_number: u64,
+    ) -> io::Result<Self> {
+        let path = cache_dir
+            .as_ref()
+            .join(chain_id.to_string())
+            .join(format!("{block_number}.json"));
+        Ok(Self {
+            db: None,
+            cache: RwLock::new(ForkCache::load(&path)?),
+            path,
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if the cache is not backed by a database.
+    pub fn is_offline(&self) -> bool {
+        self.db.is_none()
+    }
+
+    /// Returns a copy of the cached responses.
+    pub fn cache(&self) -> ForkCache {
+        self.cache.read().unwrap().clone()
+    }
+
+    /// Writes cached responses to disk if anything new was fetched.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            if let Err(e) = self.cache.read().unwrap().save(&self.path) {
+                self.dirty.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<DB> Drop for ForkCacheDB<DB> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl<DB: DatabaseRef> ForkCacheDB<DB> {
+    fn db(&self, op: DatabaseOp) -> Result<&DB, ForkCacheError<DB::Error>> {
+        self.db.as_ref().ok_or(ForkCacheError::Offline(op))
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for ForkCacheDB<DB> {
+    type Error = ForkCacheError<DB::Error>;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.cache.read().unwrap().accounts.get(&address) {
+            return Ok(info.clone());
+        }
+        let info = self
+            .db(DatabaseOp::Basic(address))?
+            .basic_ref(address)
+            .map_err(ForkCacheError::Database)?;
+
+        let mut cache = self.cache.write().unwrap();
+        if let Some(AccountInfo {
+            code_hash,
+            code: Some(code),
+            ..
+        }) = &info
+        {
+            if *code_hash != KECCAK_EMPTY {
+                cache.contracts.insert(*code_hash, code.clone());
+            }
+        }
+        cache
+            .accounts
+            .insert(address, info.as_ref().map(AccountInfo::copy_without_code));
+        drop(cache);
+        self.mark_dirty();
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        if let Some(code) = self.cache.read().unwrap().contracts.get(&code_hash) {
+            return Ok(code.clone());
+        }
+        let code = self
+            .db(DatabaseOp::CodeByHash(code_hash))?
+            .code_by_hash_ref(code_hash)
+            .map_err(ForkCacheError::Database)?;
+        self.cache
+            .write()
+            .unwrap()
+            .contracts
+            .insert(code_hash, code.clone());
+        self.mark_dirty();
+        Ok(code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self
+            .cache
+            .read()
+            .unwrap()
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+        {
+            return Ok(*value);
+        }
+        let value = self
+            .db(DatabaseOp::Storage(address, index))?
+            .storage_ref(address, index)
+            .map_err(ForkCacheError::Database)?;
+        self.cache
+            .write()
+            .unwrap()
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+        self.mark_dirty();
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        if let Some(hash) = self.cache.read().unwrap().block_hashes.get(&number) {
+            return Ok(*hash);
+        }
+        let hash = self
+            .db(DatabaseOp::BlockHash(number))?
+            .block_hash_ref(number)
+            .map_err(ForkCacheError::Database)?;
+        self.cache
+            .write()
+            .unwrap()
+            .block_hashes
+            .insert(number, hash);
+        self.mark_dirty();
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseRef> Database for ForkCacheDB<DB> {
+    type Error = ForkCacheError<DB::Error>;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use core::cell::Cell;
+    use primitives::{address, Bytes};
+
+    const ADDRESS: Address = address!("0x0000000000000000000000000000000000000001");
+
+    /// Stand-in for a remote provider that counts requests.
+    #[derive(Debug, Default)]
+    struct MockProvider {
+        db: InMemoryDB,
+        requests: Cell<usize>,
+    }
+
+    impl DatabaseRef for MockProvider {
+        type Error = core::convert::Infallible;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.requests.set(self.requests.get() + 1);
+            self.db.basic_ref(address)
+        }
+
+        fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.requests.set(self.requests.get() + 1);
+            self.db.code_by_hash_ref(code_hash)
+        }
+
+        fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.requests.set(self.requests.get() + 1);
+            self.db.storage_ref(address, index)
+        }
+
+        fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            self.requests.set(self.requests.get() + 1);
+            self.db.block_hash_ref(number)
+        }
+    }
+
+    fn provider() -> MockProvider {
+        let mut provider = MockProvider::default();
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x00]));
+        provider.db.insert_account_info(
+            ADDRESS,
+            AccountInfo::new(U256::from(7), 1, code.hash_slow(), code),
+        );
+        provider
+            .db
+            .insert_account_storage(ADDRESS, U256::from(1), U256::from(2))
+            .unwrap();
+        provider
+    }
+
+    fn read_all<DB: DatabaseRef>(
+        db: &DB,
+    ) -> Result<(AccountInfo, Bytecode, U256, B256), DB::Error> {
+        let info = db.basic_ref(ADDRESS)?.unwrap();
+        let code = db.code_by_hash_ref(info.code_hash)?;
+        let value = db.storage_ref(ADDRESS, U256::from(1))?;
+        let hash = db.block_hash_ref(10)?;
+        Ok((info, code, value, hash))
+    }
+
+    #[test]
+    fn caches_responses_and_runs_offline() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let db = ForkCacheDB::new(provider(), dir.path(), 1, 100).unwrap();
+        let online = read_all(&db).unwrap();
+        // Account response contains code, so code is not requested separately.
+        assert_eq!(db.db.as_ref().unwrap().requests.get(), 3);
+        read_all(&db).unwrap();
+        assert_eq!(db.db.as_ref().unwrap().requests.get(), 3);
+        drop(db);
+        assert!(dir.path().join("1").join("100.json").exists());
+
+        let offline = ForkCacheDB::<MockProvider>::offline(dir.path(), 1, 100).unwrap();
+        let (info, code, value, hash) = read_all(&offline).unwrap();
+        assert_eq!(info, online.0.copy_without_code());
+        assert_eq!((code, value, hash), (online.1, online.2, online.3));
+
+        // Other blocks and chains have their own cache.
+        let other_block = ForkCacheDB::<MockProvider>::offline(dir.path(), 1, 101).unwrap();
+        assert!(matches!(
+            other_block.basic_ref(ADDRESS),
+            Err(ForkCacheError::Offline(DatabaseOp::Basic(ADDRESS)))
+        ));
+        let other_chain = ForkCacheDB::<MockProvider>::offline(dir.path(), 2, 100).unwrap();
+        assert!(matches!(
+            other_chain.storage_ref(ADDRESS, U256::from(1)),
+            Err(ForkCacheError::Offline(DatabaseOp::Storage(ADDRESS, index))) if index == U256::from(1)
+        ));
+    }
+}
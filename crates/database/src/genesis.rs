@@ -0,0 +1,624 @@
+//! Loader of geth-format `genesis.json` files.
+use crate::{BundleState, CacheDB, State};
+use bytecode::BytecodeDecodeError;
+use context::{BlockEnv, CfgEnv};
+use core::{error::Error, fmt};
+use database_interface::Database;
+use primitives::{Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use serde::{Deserialize, Serialize};
+use specification::hardfork::SpecId;
+use state::{AccountInfo, Bytecode};
+use std::{collections::BTreeMap, fs, io, path::Path, string::String, vec::Vec};
+
+/// Base fee of the genesis block if London is active at genesis and `baseFeePerGas` is not set.
+pub const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+/// Error returned when loading a [Genesis].
+#[derive(Debug)]
+pub enum GenesisError {
+    /// Genesis file could not be read.
+    Io(io::Error),
+    /// Genesis file is not valid JSON or does not match the genesis format.
+    Json(serde_json::Error),
+    /// Code of the allocated account could not be decoded.
+    Bytecode(Address, BytecodeDecodeError),
+}
+
+impl fmt::Display for GenesisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read genesis: {e}"),
+            Self::Json(e) => write!(f, "invalid genesis: {e}"),
+            Self::Bytecode(address, e) => write!(f, "invalid code of {address}: {e}"),
+        }
+    }
+}
+
+impl Error for GenesisError {}
+
+/// Genesis block and chain configuration, as found in geth `genesis.json` files.
+///
+/// Numbers can be given either as JSON numbers or as decimal or hex strings.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Genesis {
+    /// Chain configuration.
+    pub config: ChainConfig,
+    /// Nonce of the genesis block.
+    #[serde(with = "quantity")]
+    pub nonce: u64,
+    /// Timestamp of the genesis block.
+    #[serde(with = "quantity")]
+    pub timestamp: u64,
+    /// Extra data of the genesis block.
+    pub extra_data: Bytes,
+    /// Gas limit of the genesis block.
+    #[serde(with = "quantity")]
+    pub gas_limit: u64,
+    /// Difficulty of the genesis block.
+    pub difficulty: U256,
+    /// Mix hash of the genesis block, used as `prevrandao` after the merge.
+    pub mix_hash: B256,
+    /// Beneficiary of the genesis block.
+    pub coinbase: Address,
+    /// Accounts allocated in the genesis state.
+    pub alloc: BTreeMap<Address, GenesisAccount>,
+    /// Number of the genesis block. Usually zero.
+    #[serde(with = "quantity")]
+    pub number: u64,
+    /// Base fee of the genesis block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<u64>,
+    /// Excess blob gas of the genesis block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub excess_blob_gas: Option<u64>,
+    /// Blob gas used by the genesis block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<u64>,
+}
+
+/// Account allocated in the genesis state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenesisAccount {
+    /// Balance of the account.
+    pub balance: U256,
+    /// Nonce of the account.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage of the account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<U256, U256>>,
+}
+
+/// Chain configuration with fork activation blocks and timestamps.
+///
+/// Forks that are not set are never activated.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ChainConfig {
+    /// Chain ID of the chain.
+    #[serde(with = "quantity")]
+    pub chain_id: u64,
+    /// Homestead activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub homestead_block: Option<u64>,
+    /// DAO fork activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub dao_fork_block: Option<u64>,
+    /// Tangerine Whistle activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub eip150_block: Option<u64>,
+    /// EIP-155 activation block. Spec is selected by `eip158_block`.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub eip155_block: Option<u64>,
+    /// Spurious Dragon activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub eip158_block: Option<u64>,
+    /// Byzantium activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub byzantium_block: Option<u64>,
+    /// Constantinople activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub constantinople_block: Option<u64>,
+    /// Petersburg activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub petersburg_block: Option<u64>,
+    /// Istanbul activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub istanbul_block: Option<u64>,
+    /// Muir Glacier activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub muir_glacier_block: Option<u64>,
+    /// Berlin activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub berlin_block: Option<u64>,
+    /// London activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub london_block: Option<u64>,
+    /// Arrow Glacier activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub arrow_glacier_block: Option<u64>,
+    /// Gray Glacier activation block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub gray_glacier_block: Option<u64>,
+    /// Merge activation block, if the merge happened at a known block.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub merge_netsplit_block: Option<u64>,
+    /// Total difficulty at which the merge happens. Zero means the chain starts merged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_total_difficulty: Option<U256>,
+    /// Whether the terminal total difficulty was already reached.
+    pub terminal_total_difficulty_passed: bool,
+    /// Shanghai activation timestamp.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub shanghai_time: Option<u64>,
+    /// Cancun activation timestamp.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub cancun_time: Option<u64>,
+    /// Prague activation timestamp.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub prague_time: Option<u64>,
+    /// Osaka activation timestamp.
+    #[serde(with = "quantity::opt", skip_serializing_if = "Option::is_none")]
+    pub osaka_time: Option<u64>,
+    /// Blob target and max counts by lowercase fork name. EIP-7840 Add blob schedule to EL
+    /// config files.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub blob_schedule: BTreeMap<String, BlobParams>,
+}
+
+/// Blob parameters of a fork. See [`ChainConfig::blob_schedule`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobParams {
+    /// Target blob count per block.
+    pub target: u8,
+    /// Max blob count per block.
+    pub max: u8,
+    /// Blob base fee update fraction. Zero means the default fraction of the fork.
+    #[serde(default, with = "quantity")]
+    pub base_fee_update_fraction: u64,
+}
+
+impl ChainConfig {
+    /// Returns the spec active at the given block number and timestamp.
+    pub fn spec_at(&self, number: u64, timestamp: u64) -> SpecId {
+        let by_block = [
+            (self.homestead_block, SpecId::HOMESTEAD),
+            (self.dao_fork_block, SpecId::DAO_FORK),
+            (self.eip150_block, SpecId::TANGERINE),
+            (self.eip158_block, SpecId::SPURIOUS_DRAGON),
+            (self.byzantium_block, SpecId::BYZANTIUM),
+            (self.constantinople_block, SpecId::CONSTANTINOPLE),
+            (self.petersburg_block, SpecId::PETERSBURG),
+            (self.istanbul_block, SpecId::ISTANBUL),
+            (self.muir_glacier_block, SpecId::MUIR_GLACIER),
+            (self.berlin_block, SpecId::BERLIN),
+            (self.london_block, SpecId::LONDON),
+            (self.arrow_glacier_block, SpecId::ARROW_GLACIER),
+            (self.gray_glacier_block, SpecId::GRAY_GLACIER),
+            (self.merge_netsplit_block, SpecId::MERGE),
+        ];
+        let by_timestamp = [
+            (self.shanghai_time, SpecId::SHANGHAI),
+            (self.cancun_time, SpecId::CANCUN),
+            (self.prague_time, SpecId::PRAGUE),
+            (self.osaka_time, SpecId::OSAKA),
+        ];
+
+        let mut spec = by_block
+            .into_iter()
+            .filter(|(block, _)| block.is_some_and(|block| block <= number))
+            .map(|(_, spec)| spec)
+            .fold(SpecId::FRONTIER, SpecId::max);
+        if self.terminal_total_difficulty_passed
+            || self.terminal_total_difficulty == Some(U256::ZERO)
+        {
+            spec = spec.max(SpecId::MERGE);
+        }
+        by_timestamp
+            .into_iter()
+            .filter(|(time, _)| time.is_some_and(|time| time <= timestamp))
+            .map(|(_, spec)| spec)
+            .fold(spec, SpecId::max)
+    }
+
+    /// Returns the blob schedule as `(spec, target, max)` entries sorted by spec.
+    ///
+    /// Entries of unknown forks are skipped.
+    pub fn blob_target_and_max_count(&self) -> Vec<(SpecId, u8, u8)> {
+        self.blob_params_by_spec()
+            .map(|(spec, params)| (spec, params.target, params.max))
+            .collect()
+    }
+
+    /// Returns the blob parameters of the latest scheduled fork active in `spec`.
+    pub fn blob_params_at(&self, spec: SpecId) -> Option<BlobParams> {
+        self.blob_params_by_spec()
+            .take_while(|(scheduled, _)| spec.is_enabled_in(*scheduled))
+            .last()
+            .map(|(_, params)| params)
+    }
+
+    /// Returns the blob schedule of known forks sorted by spec.
+    fn blob_params_by_spec(&self) -> impl Iterator<Item = (SpecId, BlobParams)> {
+        let mut schedule: Vec<_> = self
+            .blob_schedule
+            .iter()
+            .filter_map(|(name, params)| {
+                let spec = match name.as_str() {
+                    "cancun" => SpecId::CANCUN,
+                    "prague" => SpecId::PRAGUE,
+                    "osaka" => SpecId::OSAKA,
+                    _ => return None,
+                };
+                Some((spec, *params))
+            })
+            .collect();
+        schedule.sort_by_key(|(spec, _)| *spec);
+        schedule.into_iter()
+    }
+}
+
+impl GenesisAccount {
+    /// Returns the account info, with code decoded and hashed.
+    pub fn account_info(&self) -> Result<AccountInfo, BytecodeDecodeError> {
+        let (code_hash, code) = match &self.code {
+            Some(code) if !code.is_empty() => {
+                let code = Bytecode::new_raw_checked(code.clone())?;
+                (code.hash_slow(), code)
+            }
+            _ => (KECCAK_EMPTY, Bytecode::default()),
+        };
+        Ok(AccountInfo::new(
+            self.balance,
+            self.nonce.unwrap_or_default(),
+            code_hash,
+            code,
+        ))
+    }
+
+    /// Returns the account storage.
+    pub fn storage(&self) -> HashMap<U256, U256> {
+        self.storage
+            .iter()
+            .flatten()
+            .map(|(slot, value)| (*slot, *value))
+            .collect()
+    }
+}
+
+impl Genesis {
+    /// Loads the genesis from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GenesisError> {
+        let json = fs::read(path).map_err(GenesisError::Io)?;
+        serde_json::from_slice(&json).map_err(GenesisError::Json)
+    }
+
+    /// Parses the genesis from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, GenesisError> {
+        serde_json::from_str(json).map_err(GenesisError::Json)
+    }
+
+    /// Returns the spec active at the genesis block.
+    pub fn spec(&self) -> SpecId {
+        self.config.spec_at(self.number, self.timestamp)
+    }
+
+    /// Returns the EVM configuration of the genesis block.
+    ///
+    /// Blob schedule of the chain config replaces the default one if set.
+    pub fn cfg_env(&self) -> CfgEnv {
+        let mut cfg = CfgEnv::new().with_chain_id(self.config.chain_id);
+        cfg.spec = self.spec();
+        let schedule = self.config.blob_target_and_max_count();
+        if !schedule.is_empty() {
+            cfg.set_blob_max_and_target_count(schedule);
+        }
+        cfg
+    }
+
+    /// Returns the environment of the genesis block.
+    ///
+    /// Base fee defaults to [INITIAL_BASE_FEE] if London is active, and excess blob gas to zero
+    /// if Cancun is active. Blob gas price uses the base fee update fraction of the blob
+    /// schedule if it is set for the active fork, and the fraction of the spec otherwise.
+    pub fn block_env(&self) -> BlockEnv {
+        let spec = self.spec();
+        let mut block = BlockEnv {
+            number: self.number,
+            beneficiary: self.coinbase,
+            timestamp: self.timestamp,
+            gas_limit: self.gas_limit,
+            basefee: 0,
+            difficulty: self.difficulty,
+            prevrandao: None,
+            blob_excess_gas_and_price: None,
+        };
+        if spec.is_enabled_in(SpecId::LONDON) {
+            block.basefee = self.base_fee_per_gas.unwrap_or(INITIAL_BASE_FEE);
+        }
+        if spec.is_enabled_in(SpecId::MERGE) {
+            block.prevrandao = Some(self.mix_hash);
+        }
+        if spec.is_enabled_in(SpecId::CANCUN) {
+            let excess_blob_gas = self.excess_blob_gas.unwrap_or_default();
+            match self
+                .config
+                .blob_params_at(spec)
+                .map(|params| params.base_fee_update_fraction)
+                .filter(|fraction| *fraction != 0)
+            {
+                Some(fraction) => {
+                    block.set_blob_excess_gas_and_update_fraction(excess_blob_gas, fraction)
+                }
+                None => block.set_blob_excess_gas_and_price(
+                    excess_blob_gas,
+                    spec.is_enabled_in(SpecId::PRAGUE),
+                ),
+            }
+        }
+        block
+    }
+
+    /// Returns the allocated accounts with their info and storage.
+    pub fn accounts(
+        &self,
+    ) -> impl Iterator<Item = Result<(Address, AccountInfo, HashMap<U256, U256>), GenesisError>> + '_
+    {
+        self.alloc.iter().map(|(address, account)| {
+            let info = account
+                .account_info()
+                .map_err(|e| GenesisError::Bytecode(*address, e))?;
+            Ok((*address, info, account.storage()))
+        })
+    }
+
+    /// Inserts the allocated accounts into the [CacheDB], replacing their storage.
+    pub fn insert_into_cache_db<ExtDB>(&self, db: &mut CacheDB<ExtDB>) -> Result<(), GenesisError> {
+        for account in self.accounts() {
+            let (address, info, storage) = account?;
+            db.insert_account_with_storage(address, info, storage);
+        }
+        Ok(())
+    }
+
+    /// Inserts the allocated accounts into the cache of the [State].
+    pub fn insert_into_state<DB: Database>(
+        &self,
+        state: &mut State<DB>,
+    ) -> Result<(), GenesisError> {
+        for account in self.accounts() {
+            let (address, info, storage) = account?;
+            if let Some(code) = &info.code {
+                if info.code_hash != KECCAK_EMPTY {
+                    state.cache.contracts.insert(info.code_hash, code.clone());
+                }
+            }
+            state.insert_account_with_storage(address, info, storage);
+        }
+        Ok(())
+    }
+
+    /// Returns the allocated accounts as a [BundleState] of newly created accounts.
+    ///
+    /// The bundle can be used as prestate with
+    /// [`StateBuilder::with_bundle_prestate`][crate::StateBuilder::with_bundle_prestate].
+    pub fn bundle_state(&self) -> Result<BundleState, GenesisError> {
+        let mut builder = BundleState::builder(self.number..=self.number);
+        for account in self.accounts() {
+            let (address, info, storage) = account?;
+            if let Some(code) = &info.code {
+                if info.code_hash != KECCAK_EMPTY {
+                    builder.set_contract(info.code_hash, code.clone());
+                }
+            }
+            builder
+                .set_state_address(address)
+                .set_state_present_account_info(address, info)
+                .set_state_storage(
+                    address,
+                    storage
+                        .into_iter()
+                        .map(|(slot, value)| (slot, (U256::ZERO, value)))
+                        .collect(),
+                );
+        }
+        Ok(builder.build())
+    }
+}
+
+/// Serde helpers for numbers given as JSON numbers, decimal or hex strings.
+mod quantity {
+    use primitives::U256;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        U256::from(*value).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        U256::deserialize(deserializer)?
+            .try_into()
+            .map_err(D::Error::custom)
+    }
+
+    pub(super) mod opt {
+        use super::*;
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &Option<u64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            value.map(U256::from).serialize(serializer)
+        }
+
+        pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<u64>, D::Error> {
+            Option::<U256>::deserialize(deserializer)?
+                .map(|value| value.try_into().map_err(D::Error::custom))
+                .transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyDB, StateBuilder};
+    use database_interface::DatabaseRef;
+    use primitives::{address, bytes};
+
+    const GENESIS: &str = r#"{
+        "config": {
+            "chainId": 1337,
+            "homesteadBlock": 0,
+            "eip150Block": 0,
+            "eip155Block": 0,
+            "eip158Block": 0,
+            "byzantiumBlock": 0,
+            "constantinopleBlock": 0,
+            "petersburgBlock": 0,
+            "istanbulBlock": 0,
+            "berlinBlock": 0,
+            "londonBlock": 0,
+            "terminalTotalDifficulty": 0,
+            "shanghaiTime": 0,
+            "cancunTime": 0,
+            "pragueTime": 100,
+            "blobSchedule": {
+                "cancun": { "target": 3, "max": 6, "baseFeeUpdateFraction": 3338477 },
+                "prague": { "target": 6, "max": 9, "baseFeeUpdateFraction": 5007716 }
+            }
+        },
+        "nonce": "0x0",
+        "timestamp": "0x10",
+        "extraData": "0x",
+        "gasLimit": "0x1c9c380",
+        "difficulty": "0x0",
+        "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "coinbase": "0x0000000000000000000000000000000000000000",
+        "alloc": {
+            "0x0000000000000000000000000000000000000001": {
+                "balance": "0xde0b6b3a7640000"
+            },
+            "0x0000000000000000000000000000000000000002": {
+                "balance": "0x0",
+                "nonce": "0x1",
+                "code": "0x6000",
+                "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn loads_genesis() {
+        let genesis = Genesis::from_json(GENESIS).unwrap();
+        let funded = address!("0x0000000000000000000000000000000000000001");
+        let contract = address!("0x0000000000000000000000000000000000000002");
+
+        assert_eq!(genesis.spec(), SpecId::CANCUN);
+        assert_eq!(genesis.config.spec_at(1, 100), SpecId::PRAGUE);
+        let cfg = genesis.cfg_env();
+        assert_eq!(cfg.chain_id, 1337);
+        assert_eq!(cfg.spec, SpecId::CANCUN);
+        assert_eq!(
+            cfg.blob_target_and_max_count,
+            [(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)]
+        );
+        let block = genesis.block_env();
+        assert_eq!(block.timestamp, 0x10);
+        assert_eq!(block.gas_limit, 30_000_000);
+        assert_eq!(block.basefee, INITIAL_BASE_FEE);
+        assert_eq!(block.prevrandao, Some(B256::with_last_byte(1)));
+        assert_eq!(block.blob_excess_gas_and_price.unwrap().excess_blob_gas, 0);
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        genesis.insert_into_cache_db(&mut db).unwrap();
+        let info = db.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.code, Some(Bytecode::new_raw(bytes!("6000"))));
+        assert_eq!(
+            db.storage_ref(contract, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(
+            db.basic_ref(funded).unwrap().unwrap().balance,
+            U256::from(10).pow(U256::from(18))
+        );
+
+        let mut state = StateBuilder::new().build();
+        genesis.insert_into_state(&mut state).unwrap();
+        assert_eq!(
+            state.storage(contract, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(
+            state.code_by_hash(info.code_hash).unwrap(),
+            info.code.unwrap()
+        );
+
+        let bundle = genesis.bundle_state().unwrap();
+        let account = bundle.account(&contract).unwrap();
+        assert_eq!(account.original_info, None);
+        assert_eq!(account.storage_slot(U256::from(1)), Some(U256::from(2)));
+        assert_eq!(
+            bundle.bytecode(&info.code_hash),
+            Some(Bytecode::new_raw(bytes!("6000")))
+        );
+    }
+
+    #[test]
+    fn blob_schedule_update_fraction() {
+        let mut genesis = Genesis::from_json(GENESIS).unwrap();
+        genesis.excess_blob_gas = Some(10_000_000);
+        genesis.config.blob_schedule.insert(
+            "cancun".into(),
+            BlobParams {
+                target: 3,
+                max: 6,
+                base_fee_update_fraction: 2_000_000,
+            },
+        );
+        assert_eq!(
+            genesis.config.blob_params_at(SpecId::CANCUN),
+            genesis.config.blob_schedule.get("cancun").copied()
+        );
+        let blob_gasprice = |fraction: Option<u64>, is_prague: bool| {
+            let mut block = BlockEnv::default();
+            match fraction {
+                Some(fraction) => {
+                    block.set_blob_excess_gas_and_update_fraction(10_000_000, fraction)
+                }
+                None => block.set_blob_excess_gas_and_price(10_000_000, is_prague),
+            }
+            block.blob_excess_gas_and_price
+        };
+        let blob = genesis.block_env().blob_excess_gas_and_price;
+        assert_eq!(blob, blob_gasprice(Some(2_000_000), false));
+        assert_ne!(blob, blob_gasprice(None, false));
+
+        // Prague schedule applies once Prague is active.
+        genesis.timestamp = 100;
+        let blob = genesis.block_env().blob_excess_gas_and_price;
+        assert_eq!(blob, blob_gasprice(None, true));
+
+        // Unset fraction falls back to the fraction of the spec.
+        genesis.timestamp = 0x10;
+        genesis
+            .config
+            .blob_schedule
+            .get_mut("cancun")
+            .unwrap()
+            .base_fee_update_fraction = 0;
+        let blob = genesis.block_env().blob_excess_gas_and_price;
+        assert_eq!(blob, blob_gasprice(None, false));
+    }
+}
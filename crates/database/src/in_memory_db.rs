@@ -1,5 +1,12 @@
-use core::convert::Infallible;
-use database_interface::{Database, DatabaseCommit, DatabaseRef, EmptyDB};
+use crate::components::{AccountStoreCommit, CodeStoreCommit, StorageStoreCommit};
+use core::{
+    cell::{OnceCell, RefCell},
+    convert::Infallible,
+    mem,
+};
+use database_interface::{
+    overlay_range, Database, DatabaseCommit, DatabaseIter, DatabaseRef, EmptyDB, SortedOverlay,
+};
 use primitives::{address, hash_map::Entry, Address, HashMap, Log, B256, KECCAK_EMPTY, U256};
 use state::{Account, AccountInfo, Bytecode};
 use std::vec::Vec;
@@ -26,6 +33,9 @@ pub struct CacheDB<ExtDB> {
     pub logs: Vec<Log>,
     /// All cached block hashes from the [DatabaseRef].
     pub block_hashes: HashMap<U256, B256>,
+    /// Undo log of changes made since the oldest snapshot, see [CacheDB::snapshot].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshots: CacheSnapshots,
     /// The underlying database ([DatabaseRef]) that is used to load data.
     ///
     /// Note: This is read-only, data is never written to this database.
@@ -52,6 +62,7 @@ impl<ExtDb> CacheDB<CacheDB<ExtDb>> {
             contracts,
             logs,
             block_hashes,
+            snapshots: _,
             db: mut inner,
         } = self;
 
@@ -79,6 +90,7 @@ impl<ExtDB> CacheDB<ExtDB> {
             contracts,
             logs: Vec::default(),
             block_hashes: HashMap::default(),
+            snapshots: CacheSnapshots::default(),
             db,
         }
     }
@@ -107,13 +119,155 @@ impl<ExtDB> CacheDB<ExtDB> {
     /// Inserts account info but not override storage
     pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
         self.insert_contract(&mut info);
+        self.journal_account(address);
         self.accounts.entry(address).or_default().info = info;
     }
 
+    /// Inserts account info and replaces its whole storage, without loading it from the
+    /// underlying database.
+    pub fn insert_account_with_storage(
+        &mut self,
+        address: Address,
+        mut info: AccountInfo,
+        storage: HashMap<U256, U256>,
+    ) {
+        self.insert_contract(&mut info);
+        self.journal_account(address);
+        self.clear_storage(address);
+        self.accounts.insert(
+            address,
+            DbAccount {
+                info,
+                account_state: AccountState::StorageCleared,
+                storage,
+            },
+        );
+    }
+
     /// Wraps the cache in a [CacheDB], creating a nested cache.
     pub fn nest(self) -> CacheDB<Self> {
         CacheDB::new(self)
     }
+
+    /// Takes a snapshot of the cache and returns its id.
+    ///
+    /// Ids increase with every snapshot and are never reused, also after the snapshot is reverted
+    /// or released.
+    ///
+    /// Snapshots are cheap: changes made after the oldest snapshot are recorded in an undo log
+    /// instead of copying the cache. Only changes made through [CacheDB] methods and
+    /// [DatabaseCommit::commit] are recorded, direct writes to the public fields are not reverted.
+    pub fn snapshot(&mut self) -> usize {
+        let id = self.snapshots.next_id;
+        self.snapshots.next_id += 1;
+        self.snapshots
+            .checkpoints
+            .push((id, self.snapshots.journal.len(), self.logs.len()));
+        id
+    }
+
+    /// Reverts all changes made since the snapshot was taken.
+    ///
+    /// The snapshot and all snapshots taken after it are removed. Returns `false` if the snapshot
+    /// does not exist.
+    pub fn revert_to(&mut self, id: usize) -> bool {
+        let Some(index) = self.snapshots.position(id) else {
+            return false;
+        };
+        let (_, journal_len, logs_len) = self.snapshots.checkpoints[index];
+        self.snapshots.checkpoints.truncate(index);
+        for undo in self.snapshots.journal.drain(journal_len..).rev() {
+            match undo {
+                CacheUndo::Account { address, previous } => match previous {
+                    Some((info, account_state)) => {
+                        let account = self.accounts.entry(address).or_default();
+                        account.info = info;
+                        account.account_state = account_state;
+                    }
+                    None => {
+                        self.accounts.remove(&address);
+                    }
+                },
+                CacheUndo::Slot {
+                    address,
+                    slot,
+                    previous,
+                } => {
+                    if let Some(account) = self.accounts.get_mut(&address) {
+                        match previous {
+                            Some(value) => account.storage.insert(slot, value),
+                            None => account.storage.remove(&slot),
+                        };
+                    }
+                }
+                CacheUndo::Storage { address, previous } => {
+                    if let Some(account) = self.accounts.get_mut(&address) {
+                        account.storage = previous;
+                    }
+                }
+            }
+        }
+        self.logs.truncate(logs_len);
+        true
+    }
+
+    /// Removes the snapshot and all snapshots taken after it, keeping the changes made since.
+    ///
+    /// The undo log is dropped once no snapshot is left, so release snapshots that are no longer
+    /// needed to keep the log from growing. Returns `false` if the snapshot does not exist.
+    pub fn release_snapshot(&mut self, id: usize) -> bool {
+        let Some(index) = self.snapshots.position(id) else {
+            return false;
+        };
+        self.snapshots.checkpoints.truncate(index);
+        if self.snapshots.checkpoints.is_empty() {
+            self.snapshots.journal = Vec::new();
+        }
+        true
+    }
+
+    /// Records account info and state in the undo log if there is a snapshot.
+    fn journal_account(&mut self, address: Address) {
+        if self.snapshots.checkpoints.is_empty() {
+            return;
+        }
+        let previous = self
+            .accounts
+            .get(&address)
+            .map(|account| (account.info.clone(), account.account_state.clone()));
+        self.snapshots
+            .journal
+            .push(CacheUndo::Account { address, previous });
+    }
+
+    /// Records the storage slot in the undo log if there is a snapshot.
+    fn journal_slot(&mut self, address: Address, slot: U256) {
+        if self.snapshots.checkpoints.is_empty() {
+            return;
+        }
+        let previous = self
+            .accounts
+            .get(&address)
+            .and_then(|account| account.storage.get(&slot).copied());
+        self.snapshots.journal.push(CacheUndo::Slot {
+            address,
+            slot,
+            previous,
+        });
+    }
+
+    /// Clears the account storage, moving it to the undo log if there is a snapshot.
+    fn clear_storage(&mut self, address: Address) {
+        let Some(account) = self.accounts.get_mut(&address) else {
+            return;
+        };
+        let previous = mem::take(&mut account.storage);
+        if !self.snapshots.checkpoints.is_empty() {
+            self.snapshots
+                .journal
+                .push(CacheUndo::Storage { address, previous });
+        }
+    }
 }
 
 impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
@@ -142,8 +296,14 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         slot: U256,
         value: U256,
     ) -> Result<(), ExtDB::Error> {
-        let account = self.load_account(address)?;
-        account.storage.insert(slot, value);
+        self.journal_account(address);
+        self.load_account(address)?;
+        self.journal_slot(address, slot);
+        self.accounts
+            .get_mut(&address)
+            .expect("account is loaded")
+            .storage
+            .insert(slot, value);
         Ok(())
     }
 
@@ -153,7 +313,10 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         address: Address,
         storage: HashMap<U256, U256>,
     ) -> Result<(), ExtDB::Error> {
-        let account = self.load_account(address)?;
+        self.journal_account(address);
+        self.load_account(address)?;
+        self.clear_storage(address);
+        let account = self.accounts.get_mut(&address).expect("account is loaded");
         account.account_state = AccountState::StorageCleared;
         account.storage = storage.into_iter().collect();
         Ok(())
@@ -166,21 +329,27 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            self.journal_account(address);
             if account.is_selfdestructed() {
+                self.clear_storage(address);
                 let db_account = self.accounts.entry(address).or_default();
-                db_account.storage.clear();
                 db_account.account_state = AccountState::NotExisting;
                 db_account.info = AccountInfo::default();
                 continue;
             }
             let is_newly_created = account.is_created();
             self.insert_contract(&mut account.info);
+            if is_newly_created {
+                self.clear_storage(address);
+            }
+            for slot in account.storage.keys() {
+                self.journal_slot(address, *slot);
+            }
 
             let db_account = self.accounts.entry(address).or_default();
             db_account.info = account.info;
 
             db_account.account_state = if is_newly_created {
-                db_account.storage.clear();
                 AccountState::StorageCleared
             } else if db_account.account_state.is_storage_cleared() {
                 // Preserve old account state if it already exists
@@ -198,6 +367,47 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
     }
 }
 
+impl<ExtDB> AccountStoreCommit for CacheDB<ExtDB> {
+    fn commit_account(&mut self, address: Address, info: Option<AccountInfo>) {
+        self.journal_account(address);
+        let Some(mut info) = info else {
+            let db_account = self.accounts.entry(address).or_default();
+            db_account.account_state = AccountState::NotExisting;
+            db_account.info = AccountInfo::default();
+            return;
+        };
+        self.insert_contract(&mut info);
+        let db_account = self.accounts.entry(address).or_default();
+        db_account.info = info;
+        if !db_account.account_state.is_storage_cleared() {
+            db_account.account_state = AccountState::Touched;
+        }
+    }
+}
+
+impl<ExtDB> CodeStoreCommit for CacheDB<ExtDB> {
+    fn commit_code(&mut self, code_hash: B256, code: Bytecode) {
+        self.contracts.entry(code_hash).or_insert(code);
+    }
+}
+
+impl<ExtDB> StorageStoreCommit for CacheDB<ExtDB> {
+    fn commit_storage(&mut self, address: Address, wipe: bool, storage: HashMap<U256, U256>) {
+        self.journal_account(address);
+        if wipe {
+            self.clear_storage(address);
+        }
+        for slot in storage.keys() {
+            self.journal_slot(address, *slot);
+        }
+        let db_account = self.accounts.entry(address).or_default();
+        if wipe {
+            db_account.account_state = AccountState::StorageCleared;
+        }
+        db_account.storage.extend(storage);
+    }
+}
+
 impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
@@ -323,6 +533,157 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
     }
 }
 
+/// Undo log backing [CacheDB::snapshot] and [CacheDB::revert_to].
+#[derive(Debug, Clone, Default)]
+struct CacheSnapshots {
+    /// Id, undo log length and logs length of each snapshot, ordered by id.
+    checkpoints: Vec<(usize, usize, usize)>,
+    /// Changes made since the oldest snapshot, with values before the change.
+    journal: Vec<CacheUndo>,
+    /// Id of the next snapshot.
+    next_id: usize,
+}
+
+impl CacheSnapshots {
+    /// Returns the index of the snapshot `id` in the checkpoints.
+    fn position(&self, id: usize) -> Option<usize> {
+        self.checkpoints
+            .binary_search_by_key(&id, |&(id, ..)| id)
+            .ok()
+    }
+}
+
+/// Change recorded in the [CacheSnapshots] undo log.
+#[derive(Debug, Clone)]
+enum CacheUndo {
+    /// Account info and state changed. `None` if the account was not cached.
+    Account {
+        address: Address,
+        previous: Option<(AccountInfo, AccountState)>,
+    },
+    /// Storage slot changed. `None` if the slot was not cached.
+    Slot {
+        address: Address,
+        slot: U256,
+        previous: Option<U256>,
+    },
+    /// Account storage was cleared.
+    Storage {
+        address: Address,
+        previous: HashMap<U256, U256>,
+    },
+}
+
+/// Pages through a fresh [CacheDBIndex], use [CacheDB::iter_index] to sort the cache only once
+/// when reading multiple pages.
+impl<ExtDB: DatabaseIter> DatabaseIter for CacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        self.iter_index().accounts_range(start, limit)
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        self.iter_index().storage_range(address, start, limit)
+    }
+}
+
+impl<ExtDB> CacheDB<ExtDB> {
+    /// Returns an index that iterates the cache on top of the underlying database.
+    ///
+    /// Cached accounts and storage are sorted on first use, after that a page costs
+    /// `O(limit + log N)`.
+    pub fn iter_index(&self) -> CacheDBIndex<'_, ExtDB> {
+        CacheDBIndex {
+            db: self,
+            accounts: OnceCell::new(),
+            storage: RefCell::new(HashMap::default()),
+        }
+    }
+}
+
+/// Sorted index of a borrowed [CacheDB], see [CacheDB::iter_index].
+#[derive(Debug)]
+pub struct CacheDBIndex<'a, ExtDB> {
+    db: &'a CacheDB<ExtDB>,
+    accounts: OnceCell<SortedOverlay<Address, AccountInfo>>,
+    storage: RefCell<HashMap<Address, SortedOverlay<U256, U256>>>,
+}
+
+impl<ExtDB: DatabaseIter> DatabaseIter for CacheDBIndex<'_, ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        let accounts = self.accounts.get_or_init(|| {
+            SortedOverlay::new(
+                self.db
+                    .accounts
+                    .iter()
+                    .map(|(address, account)| (*address, account.info())),
+            )
+        });
+        overlay_range(
+            start,
+            limit,
+            accounts
+                .range(start)
+                .map(|(address, info)| (address, info.cloned())),
+            |limit| self.db.db.accounts_range(start, limit),
+        )
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        let Some(account) = self.db.accounts.get(&address) else {
+            return self.db.db.storage_range(address, start, limit);
+        };
+        let is_storage_known = matches!(
+            account.account_state,
+            AccountState::StorageCleared | AccountState::NotExisting
+        );
+        let mut storage = self.storage.borrow_mut();
+        let overlay = storage.entry(address).or_insert_with(|| {
+            SortedOverlay::new(
+                account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (*slot, (!value.is_zero()).then_some(*value))),
+            )
+        });
+        overlay_range(
+            start,
+            limit,
+            overlay
+                .range(start)
+                .map(|(slot, value)| (slot, value.copied())),
+            |limit| {
+                if is_storage_known {
+                    Ok(Vec::new())
+                } else {
+                    self.db.db.storage_range(address, start, limit)
+                }
+            },
+        )
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DbAccount {
@@ -451,9 +812,9 @@ impl Database for BenchmarkDB {
 #[cfg(test)]
 mod tests {
     use super::{CacheDB, EmptyDB};
-    use database_interface::Database;
+    use database_interface::{Database, DatabaseCommit, DatabaseIter};
     use primitives::{Address, HashMap, U256};
-    use state::AccountInfo;
+    use state::{Account, AccountInfo, EvmStorageSlot};
 
     #[test]
     fn test_insert_account_storage() {
@@ -507,6 +868,146 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_snapshot_revert() {
+        let account = Address::with_last_byte(42);
+        let (key, value) = (U256::from(1), U256::from(2));
+        let mut state = CacheDB::new(EmptyDB::default());
+        state.insert_account_info(
+            account,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        state.insert_account_storage(account, key, value).unwrap();
+
+        let first = state.snapshot();
+        let mut changes = Account::from(AccountInfo {
+            nonce: 2,
+            ..Default::default()
+        });
+        changes.mark_touch();
+        changes
+            .storage
+            .insert(key, EvmStorageSlot::new_changed(value, U256::from(3)));
+        state.commit(HashMap::from_iter([(account, changes)]));
+
+        let second = state.snapshot();
+        state
+            .replace_account_storage(account, HashMap::default())
+            .unwrap();
+        state.insert_account_info(Address::with_last_byte(43), AccountInfo::default());
+        assert_eq!(state.storage(account, key), Ok(U256::ZERO));
+
+        assert!(state.revert_to(second));
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 2);
+        assert_eq!(state.storage(account, key), Ok(U256::from(3)));
+        assert!(!state.accounts.contains_key(&Address::with_last_byte(43)));
+
+        assert!(state.revert_to(first));
+        assert_eq!(state.basic(account).unwrap().unwrap().nonce, 1);
+        assert_eq!(state.storage(account, key), Ok(value));
+        assert!(!state.revert_to(first));
+
+        // Ids of reverted snapshots are not reused.
+        let third = state.snapshot();
+        assert!(third > second);
+        assert!(!state.revert_to(first));
+        assert!(!state.release_snapshot(second));
+        assert!(state.revert_to(third));
+    }
+
+    #[test]
+    fn test_snapshot_release() {
+        let account = Address::with_last_byte(42);
+        let mut state = CacheDB::new(EmptyDB::default());
+        let first = state.snapshot();
+        state.insert_account_info(account, AccountInfo::from_balance(U256::from(1)));
+        let second = state.snapshot();
+        state.insert_account_info(account, AccountInfo::from_balance(U256::from(2)));
+
+        // Releasing a nested snapshot keeps the undo log of the older one.
+        assert!(state.release_snapshot(second));
+        assert!(!state.snapshots.journal.is_empty());
+        assert!(!state.revert_to(second));
+
+        assert!(state.release_snapshot(first));
+        assert!(state.snapshots.journal.is_empty());
+        assert!(!state.revert_to(first));
+        assert_eq!(
+            state.basic(account).unwrap().unwrap().balance,
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_iter_nested() {
+        let mut inner = CacheDB::new(EmptyDB::default());
+        for i in 1..=3 {
+            let address = Address::with_last_byte(i);
+            inner.insert_account_info(address, AccountInfo::from_balance(U256::from(i)));
+            for slot in 0..3 {
+                inner
+                    .insert_account_storage(address, U256::from(slot), U256::from(1))
+                    .unwrap();
+            }
+        }
+
+        let mut outer = inner.nest();
+        let mut destroyed = Account::default();
+        destroyed.mark_touch();
+        destroyed.mark_selfdestruct();
+        outer.commit(HashMap::from_iter([(
+            Address::with_last_byte(2),
+            destroyed,
+        )]));
+        outer.insert_account_info(Address::with_last_byte(4), AccountInfo::default());
+        let address = Address::with_last_byte(1);
+        outer
+            .insert_account_storage(address, U256::from(1), U256::ZERO)
+            .unwrap();
+        outer
+            .insert_account_storage(address, U256::from(5), U256::from(2))
+            .unwrap();
+
+        let accounts = outer.accounts_range(Address::ZERO, 2).unwrap();
+        assert_eq!(
+            accounts.iter().map(|(a, _)| *a).collect::<Vec<_>>(),
+            [Address::with_last_byte(1), Address::with_last_byte(3)]
+        );
+        let accounts = outer
+            .accounts_range(Address::with_last_byte(2), 10)
+            .unwrap();
+        assert_eq!(
+            accounts.iter().map(|(a, _)| *a).collect::<Vec<_>>(),
+            [Address::with_last_byte(3), Address::with_last_byte(4)]
+        );
+
+        assert_eq!(
+            outer.storage_range(address, U256::ZERO, 10),
+            Ok(vec![
+                (U256::ZERO, U256::from(1)),
+                (U256::from(2), U256::from(1)),
+                (U256::from(5), U256::from(2)),
+            ])
+        );
+        assert_eq!(
+            outer.storage_range(Address::with_last_byte(2), U256::ZERO, 10),
+            Ok(vec![])
+        );
+
+        // Pages through the index continue where the previous page ended.
+        let index = outer.iter_index();
+        let mut pages = Vec::new();
+        let mut start = U256::ZERO;
+        while let [.., (last, _)] = index.storage_range(address, start, 1).unwrap()[..] {
+            pages.push(last);
+            start = last + U256::from(1);
+        }
+        assert_eq!(pages, [U256::ZERO, U256::from(2), U256::from(5)]);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
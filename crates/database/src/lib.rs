@@ -6,18 +6,54 @@ extern crate alloc as std;
 
 #[cfg(feature = "alloydb")]
 mod alloydb;
+#[cfg(feature = "fork-cache")]
+mod fork_cache;
+#[cfg(feature = "genesis")]
+mod genesis;
+#[cfg(feature = "std")]
+mod metrics_db;
+#[cfg(feature = "redb")]
+mod persistent_db;
+#[cfg(feature = "std")]
+mod shared_cache_db;
+#[cfg(feature = "state-dump")]
+mod state_dump;
 
 pub use database_interface::*;
 
+pub mod block_hash_history;
+pub mod components;
 pub mod in_memory_db;
+pub mod override_db;
+pub mod stateless;
 pub mod states;
+pub mod witness;
 
 #[cfg(feature = "alloydb")]
-pub use alloydb::{AlloyDB, BlockId};
+pub use alloydb::{AlloyDB, BlockId, FetchMode, PrefetchedAccount};
+#[cfg(feature = "fork-cache")]
+pub use fork_cache::{ForkCache, ForkCacheDB, ForkCacheError};
+#[cfg(feature = "genesis")]
+pub use genesis::{
+    BlobParams, ChainConfig, Genesis, GenesisAccount, GenesisError, INITIAL_BASE_FEE,
+};
+#[cfg(feature = "std")]
+pub use metrics_db::{DatabaseMethod, DatabaseReport, LatencyHistogram, MethodStats, MetricsDB};
+#[cfg(feature = "redb")]
+pub use persistent_db::{PersistentDB, PersistentDBError};
+#[cfg(feature = "std")]
+pub use shared_cache_db::SharedCacheDB;
+#[cfg(feature = "state-dump")]
+pub use state_dump::{dump_state, load_state, DumpAccount, DumpBlock};
 
+pub use block_hash_history::BlockHashHistoryDB;
+pub use components::{DatabaseComponentError, DatabaseComponents, DatabaseStore};
 pub use in_memory_db::*;
+pub use override_db::{AccountOverride, OverrideDB, OverrideError, StateOverride};
+pub use stateless::{StatelessDB, StatelessDBError};
 pub use states::{
-    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
-    OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
-    StorageWithOriginalValues, TransitionAccount, TransitionState,
+    AccountRevert, AccountStatus, BundleAccount, BundleState, BundleStateIndex, BundleView,
+    CacheState, DBBox, OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder,
+    StateDBBox, StateIndex, StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+pub use witness::{ExecutionWitness, WitnessDB, WitnessProofProvider};
@@ -0,0 +1,501 @@
+//! Database instrumentation.
+use core::{fmt, time::Duration};
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, HashMap, HashSet, B256, U256};
+use state::{AccountInfo, Bytecode};
+use std::{sync::Mutex, time::Instant, vec::Vec};
+
+/// Nanoseconds in a second.
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// [Database] method measured by [MetricsDB].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DatabaseMethod {
+    /// [`Database::basic`] or [`DatabaseRef::basic_ref`].
+    Basic,
+    /// [`Database::code_by_hash`] or [`DatabaseRef::code_by_hash_ref`].
+    CodeByHash,
+    /// [`Database::storage`] or [`DatabaseRef::storage_ref`].
+    Storage,
+    /// [`Database::block_hash`] or [`DatabaseRef::block_hash_ref`].
+    BlockHash,
+}
+
+impl DatabaseMethod {
+    /// All methods.
+    pub const ALL: [Self; 4] = [
+        Self::Basic,
+        Self::CodeByHash,
+        Self::Storage,
+        Self::BlockHash,
+    ];
+
+    /// Returns the method name.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Basic => "basic",
+            Self::CodeByHash => "code_by_hash",
+            Self::Storage => "storage",
+            Self::BlockHash => "block_hash",
+        }
+    }
+}
+
+impl fmt::Display for DatabaseMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Histogram of call latencies with power of two nanosecond buckets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Bucket `i` counts calls that took at most `2^i` nanoseconds and were not counted in a
+    /// lower bucket. The last bucket counts all slower calls.
+    pub buckets: [u64; 32],
+    /// Sum of all recorded latencies.
+    pub total: Duration,
+}
+
+impl LatencyHistogram {
+    /// Records a call latency.
+    pub fn record(&mut self, latency: Duration) {
+        let nanos = latency.as_nanos().max(1);
+        let bucket = (u128::BITS - (nanos - 1).leading_zeros()) as usize;
+        self.buckets[bucket.min(self.buckets.len() - 1)] += 1;
+        self.total += latency;
+    }
+
+    /// Returns the number of recorded calls.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the mean latency, zero if nothing was recorded.
+    pub fn mean(&self) -> Duration {
+        match self.count() {
+            0 => Duration::ZERO,
+            count => {
+                let nanos = self.total.as_nanos() / u128::from(count);
+                Duration::new(
+                    (nanos / NANOS_PER_SEC) as u64,
+                    (nanos % NANOS_PER_SEC) as u32,
+                )
+            }
+        }
+    }
+
+    /// Returns the upper bound of the bucket containing the `quantile` (`0.0..=1.0`) latency.
+    ///
+    /// Returns [Duration::MAX] if the latency is in the last bucket, which has no upper bound.
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        let target = (self.count() as f64 * quantile).ceil() as u64;
+        let mut seen = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                if i == self.buckets.len() - 1 {
+                    return Duration::MAX;
+                }
+                return Duration::from_nanos(1 << i);
+            }
+        }
+        Duration::ZERO
+    }
+}
+
+/// Statistics of a single [DatabaseMethod].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    /// Number of calls.
+    pub calls: u64,
+    /// Number of calls for a key that was not requested before.
+    ///
+    /// Repeated requests for the same key would be served by a cache placed above the
+    /// database, so this is the number of calls that miss such a cache. Only counted with
+    /// [`MetricsDB::with_key_tracking`].
+    pub misses: u64,
+    /// Number of calls that returned an error.
+    pub errors: u64,
+    /// Call latencies.
+    pub latency: LatencyHistogram,
+}
+
+/// Snapshot of statistics collected by [MetricsDB].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatabaseReport {
+    /// Statistics of [`DatabaseMethod::Basic`].
+    pub basic: MethodStats,
+    /// Statistics of [`DatabaseMethod::CodeByHash`].
+    pub code_by_hash: MethodStats,
+    /// Statistics of [`DatabaseMethod::Storage`].
+    pub storage: MethodStats,
+    /// Statistics of [`DatabaseMethod::BlockHash`].
+    pub block_hash: MethodStats,
+    /// Most accessed accounts with the number of `basic` and `storage` calls, most accessed first.
+    ///
+    /// Empty without [`MetricsDB::with_key_tracking`].
+    pub hottest_accounts: Vec<(Address, u64)>,
+    /// Most accessed storage slots with the number of calls, most accessed first.
+    ///
+    /// Empty without [`MetricsDB::with_key_tracking`].
+    pub hottest_slots: Vec<(Address, U256, u64)>,
+}
+
+impl DatabaseReport {
+    /// Returns statistics of the method.
+    pub fn method(&self, method: DatabaseMethod) -> &MethodStats {
+        match method {
+            DatabaseMethod::Basic => &self.basic,
+            DatabaseMethod::CodeByHash => &self.code_by_hash,
+            DatabaseMethod::Storage => &self.storage,
+            DatabaseMethod::BlockHash => &self.block_hash,
+        }
+    }
+}
+
+impl fmt::Display for DatabaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for method in DatabaseMethod::ALL {
+            let stats = self.method(method);
+            writeln!(
+                f,
+                "{method}: {} calls, {} misses, {} errors, mean {:?}, p99 <{:?}",
+                stats.calls,
+                stats.misses,
+                stats.errors,
+                stats.latency.mean(),
+                stats.latency.quantile(0.99),
+            )?;
+        }
+        for (address, count) in &self.hottest_accounts {
+            writeln!(f, "account {address}: {count}")?;
+        }
+        for (address, slot, count) in &self.hottest_slots {
+            writeln!(f, "slot {address} {slot}: {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Collected statistics.
+#[derive(Debug, Default)]
+struct Stats {
+    methods: [MethodStats; 4],
+    /// Track the keys below, see [`MetricsDB::with_key_tracking`].
+    track_keys: bool,
+    /// `basic` and `storage` calls per account.
+    accounts: HashMap<Address, u64>,
+    /// Accounts requested with `basic`.
+    basic_seen: HashSet<Address>,
+    slots: HashMap<(Address, U256), u64>,
+    codes_seen: HashSet<B256>,
+    block_hashes_seen: HashSet<u64>,
+}
+
+impl Stats {
+    fn record<T, E>(
+        &mut self,
+        method: DatabaseMethod,
+        is_miss: bool,
+        latency: Duration,
+        result: &Result<T, E>,
+    ) {
+        let stats = &mut self.methods[method as usize];
+        stats.calls += 1;
+        stats.misses += u64::from(is_miss);
+        stats.errors += u64::from(result.is_err());
+        stats.latency.record(latency);
+
+        #[cfg(feature = "metrics")]
+        {
+            let method = method.as_str();
+            metrics::counter!("revm_database_calls_total", "method" => method).increment(1);
+            if is_miss {
+                metrics::counter!("revm_database_misses_total", "method" => method).increment(1);
+            }
+            if result.is_err() {
+                metrics::counter!("revm_database_errors_total", "method" => method).increment(1);
+            }
+            metrics::histogram!("revm_database_call_duration_seconds", "method" => method)
+                .record(latency);
+        }
+    }
+
+    fn basic<T, E>(&mut self, address: Address, latency: Duration, result: &Result<T, E>) {
+        let mut is_miss = false;
+        if self.track_keys {
+            *self.accounts.entry(address).or_default() += 1;
+            is_miss = self.basic_seen.insert(address);
+        }
+        self.record(DatabaseMethod::Basic, is_miss, latency, result);
+    }
+
+    fn code_by_hash<T, E>(&mut self, code_hash: B256, latency: Duration, result: &Result<T, E>) {
+        let is_miss = self.track_keys && self.codes_seen.insert(code_hash);
+        self.record(DatabaseMethod::CodeByHash, is_miss, latency, result);
+    }
+
+    fn storage<T, E>(
+        &mut self,
+        address: Address,
+        index: U256,
+        latency: Duration,
+        result: &Result<T, E>,
+    ) {
+        let mut is_miss = false;
+        if self.track_keys {
+            *self.accounts.entry(address).or_default() += 1;
+            let count = self.slots.entry((address, index)).or_default();
+            *count += 1;
+            is_miss = *count == 1;
+        }
+        self.record(DatabaseMethod::Storage, is_miss, latency, result);
+    }
+
+    fn block_hash<T, E>(&mut self, number: u64, latency: Duration, result: &Result<T, E>) {
+        let is_miss = self.track_keys && self.block_hashes_seen.insert(number);
+        self.record(DatabaseMethod::BlockHash, is_miss, latency, result);
+    }
+
+    fn report(&self, top: usize) -> DatabaseReport {
+        let [basic, code_by_hash, storage, block_hash] = self.methods.clone();
+        let mut hottest_accounts: Vec<_> = self.accounts.iter().map(|(a, c)| (*a, *c)).collect();
+        hottest_accounts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hottest_accounts.truncate(top);
+        let mut hottest_slots: Vec<_> = self
+            .slots
+            .iter()
+            .map(|((address, slot), count)| (*address, *slot, *count))
+            .collect();
+        hottest_slots.sort_unstable_by(|a, b| b.2.cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+        hottest_slots.truncate(top);
+        DatabaseReport {
+            basic,
+            code_by_hash,
+            storage,
+            block_hash,
+            hottest_accounts,
+            hottest_slots,
+        }
+    }
+}
+
+/// A [Database] and [DatabaseRef] wrapper that measures calls to the wrapped database.
+///
+/// Counts calls and errors per [DatabaseMethod] and records latency histograms. Use
+/// [`MetricsDB::report`] to read them.
+///
+/// Misses and the most accessed accounts and storage slots are only collected with
+/// [`MetricsDB::with_key_tracking`], as they need every requested key to be kept.
+///
+/// With the `metrics` feature, calls are also reported to the global [`metrics`] recorder as
+/// `revm_database_calls_total`, `revm_database_misses_total`, `revm_database_errors_total`
+/// and `revm_database_call_duration_seconds`, labeled by `method`.
+#[derive(Debug, Default)]
+pub struct MetricsDB<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    stats: Mutex<Stats>,
+}
+
+impl<DB> MetricsDB<DB> {
+    /// Wraps the database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            stats: Mutex::default(),
+        }
+    }
+
+    /// Enables or disables tracking of the requested keys.
+    ///
+    /// Disabled by default. When enabled, every distinct account, storage slot, code hash and
+    /// block number that is requested is kept to count misses and find the most accessed
+    /// accounts and slots. Memory grows with the number of distinct keys, at least 64 bytes per
+    /// storage slot, until [`MetricsDB::reset`] is called.
+    pub fn with_key_tracking(mut self, enabled: bool) -> Self {
+        self.stats.get_mut().unwrap().track_keys = enabled;
+        self
+    }
+
+    /// Returns statistics collected so far with up to `top` hottest accounts and slots.
+    pub fn report(&self, top: usize) -> DatabaseReport {
+        self.stats.lock().unwrap().report(top)
+    }
+
+    /// Clears collected statistics and tracked keys.
+    pub fn reset(&mut self) {
+        let stats = self.stats.get_mut().unwrap();
+        *stats = Stats {
+            track_keys: stats.track_keys,
+            ..Default::default()
+        };
+    }
+
+    /// Consumes the wrapper and returns the wrapped database.
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+}
+
+impl<DB: Database> Database for MetricsDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.basic(address);
+        let stats = self.stats.get_mut().unwrap();
+        stats.basic(address, start.elapsed(), &result);
+        result
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.code_by_hash(code_hash);
+        let stats = self.stats.get_mut().unwrap();
+        stats.code_by_hash(code_hash, start.elapsed(), &result);
+        result
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.storage(address, index);
+        let stats = self.stats.get_mut().unwrap();
+        stats.storage(address, index, start.elapsed(), &result);
+        result
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.block_hash(number);
+        let stats = self.stats.get_mut().unwrap();
+        stats.block_hash(number, start.elapsed(), &result);
+        result
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for MetricsDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.basic_ref(address);
+        let latency = start.elapsed();
+        self.stats.lock().unwrap().basic(address, latency, &result);
+        result
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.code_by_hash_ref(code_hash);
+        let latency = start.elapsed();
+        self.stats
+            .lock()
+            .unwrap()
+            .code_by_hash(code_hash, latency, &result);
+        result
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.storage_ref(address, index);
+        let latency = start.elapsed();
+        self.stats
+            .lock()
+            .unwrap()
+            .storage(address, index, latency, &result);
+        result
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let start = Instant::now();
+        let result = self.db.block_hash_ref(number);
+        let latency = start.elapsed();
+        self.stats
+            .lock()
+            .unwrap()
+            .block_hash(number, latency, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+
+    #[test]
+    fn collects_statistics() {
+        let account = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+        let mut db = MetricsDB::new(InMemoryDB::default()).with_key_tracking(true);
+        for _ in 0..3 {
+            db.basic(account).unwrap();
+            db.storage(account, U256::from(1)).unwrap();
+        }
+        db.storage(account, U256::from(2)).unwrap();
+        db.basic_ref(other).unwrap();
+        db.block_hash(1).unwrap();
+
+        let report = db.report(1);
+        assert_eq!(report.basic.calls, 4);
+        assert_eq!(report.basic.misses, 2);
+        assert_eq!(report.storage.calls, 4);
+        assert_eq!(report.storage.misses, 2);
+        assert_eq!(report.storage.latency.count(), 4);
+        assert_eq!(report.block_hash.calls, 1);
+        assert_eq!(report.code_by_hash, MethodStats::default());
+        assert_eq!(report.hottest_accounts, vec![(account, 7)]);
+        assert_eq!(report.hottest_slots, vec![(account, U256::from(1), 3)]);
+
+        db.reset();
+        assert_eq!(db.report(1), DatabaseReport::default());
+        db.basic(account).unwrap();
+        assert_eq!(db.report(1).basic.misses, 1);
+    }
+
+    #[test]
+    fn keys_not_tracked_by_default() {
+        let account = Address::with_last_byte(1);
+        let mut db = MetricsDB::new(InMemoryDB::default());
+        db.basic(account).unwrap();
+        db.storage(account, U256::from(1)).unwrap();
+        db.block_hash(1).unwrap();
+
+        let report = db.report(1);
+        assert_eq!((report.basic.calls, report.basic.misses), (1, 0));
+        assert_eq!((report.storage.calls, report.storage.misses), (1, 0));
+        assert!(report.hottest_accounts.is_empty());
+        assert!(report.hottest_slots.is_empty());
+        let stats = db.stats.get_mut().unwrap();
+        assert!(stats.accounts.is_empty() && stats.slots.is_empty());
+        assert!(stats.basic_seen.is_empty() && stats.block_hashes_seen.is_empty());
+    }
+
+    #[test]
+    fn latency_histogram() {
+        let mut histogram = LatencyHistogram::default();
+        histogram.record(Duration::from_nanos(1));
+        histogram.record(Duration::from_nanos(3));
+        histogram.record(Duration::from_nanos(4));
+        histogram.record(Duration::from_nanos(1000));
+        assert_eq!(histogram.buckets[..3], [1, 0, 2]);
+        assert_eq!(histogram.buckets[10], 1);
+        assert_eq!(histogram.quantile(0.5), Duration::from_nanos(4));
+        assert_eq!(histogram.quantile(1.0), Duration::from_nanos(1024));
+        assert_eq!(histogram.mean(), Duration::from_nanos(252));
+
+        // Slower calls than the last bucket bound have no upper bound.
+        histogram.record(Duration::from_secs(10));
+        assert_eq!(histogram.quantile(1.0), Duration::MAX);
+        assert_eq!(histogram.quantile(0.5), Duration::from_nanos(4));
+
+        // Mean does not truncate the count.
+        let histogram = LatencyHistogram {
+            buckets: [1 << 32; 32],
+            total: Duration::from_secs(32 << 32),
+        };
+        assert_eq!(histogram.mean(), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,308 @@
+//! `eth_call` style state overrides.
+use bytecode::BytecodeDecodeError;
+use core::{error::Error, fmt};
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+
+/// Overrides of a single account, as accepted by `eth_call` and `debug_traceCall`.
+///
+/// `state` replaces the whole account storage, while `state_diff` only replaces the given slots.
+/// Setting both is an error.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AccountOverride {
+    /// Fake balance to set for the account.
+    pub balance: Option<U256>,
+    /// Fake nonce to set for the account.
+    pub nonce: Option<u64>,
+    /// Fake bytecode to set for the account.
+    pub code: Option<Bytes>,
+    /// Fake storage replacing the whole account storage.
+    pub state: Option<HashMap<U256, U256>>,
+    /// Fake storage slots patched into the account storage.
+    pub state_diff: Option<HashMap<U256, U256>>,
+}
+
+/// State overrides by account address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Error returned when an [AccountOverride] is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverrideError {
+    /// Both `state` and `state_diff` are set for the account.
+    StateAndStateDiff(Address),
+    /// Overridden code of the account could not be decoded.
+    Bytecode(Address, BytecodeDecodeError),
+}
+
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StateAndStateDiff(address) => {
+                write!(f, "account {address} has both 'state' and 'stateDiff'")
+            }
+            Self::Bytecode(address, e) => write!(f, "invalid code override of {address}: {e}"),
+        }
+    }
+}
+
+impl Error for OverrideError {}
+
+/// Overridden account, with code already hashed.
+#[derive(Clone, Debug, Default)]
+struct OverriddenAccount {
+    balance: Option<U256>,
+    nonce: Option<u64>,
+    code_hash: Option<B256>,
+    storage: HashMap<U256, U256>,
+    /// If true, slots not in `storage` are zero.
+    storage_replaced: bool,
+}
+
+/// A [DatabaseRef] wrapper that applies state overrides on top of the wrapped database.
+///
+/// The wrapped database is never modified. Overridden code is stored by its code hash, so it
+/// can be loaded with [`DatabaseRef::code_by_hash_ref`].
+#[derive(Clone, Debug, Default)]
+pub struct OverrideDB<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    accounts: HashMap<Address, OverriddenAccount>,
+    contracts: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl<DB> OverrideDB<DB> {
+    /// Wraps the database without any overrides.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: HashMap::default(),
+            contracts: HashMap::default(),
+            block_hashes: HashMap::default(),
+        }
+    }
+
+    /// Wraps the database and applies the state overrides.
+    pub fn with_state_override(db: DB, overrides: StateOverride) -> Result<Self, OverrideError> {
+        let mut db = Self::new(db);
+        db.apply_state_override(overrides)?;
+        Ok(db)
+    }
+
+    /// Applies the state overrides. See [`OverrideDB::apply_account_override`].
+    pub fn apply_state_override(&mut self, overrides: StateOverride) -> Result<(), OverrideError> {
+        for (address, account) in overrides {
+            self.apply_account_override(address, account)?;
+        }
+        Ok(())
+    }
+
+    /// Applies overrides of a single account on top of any previous overrides of it.
+    ///
+    /// Code hash is recalculated from the overridden code, empty code resets it to
+    /// [KECCAK_EMPTY].
+    pub fn apply_account_override(
+        &mut self,
+        address: Address,
+        account: AccountOverride,
+    ) -> Result<(), OverrideError> {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(OverrideError::StateAndStateDiff(address));
+        }
+        let code_hash = account
+            .code
+            .map(|code| {
+                if code.is_empty() {
+                    return Ok(KECCAK_EMPTY);
+                }
+                let code = Bytecode::new_raw_checked(code)
+                    .map_err(|e| OverrideError::Bytecode(address, e))?;
+                let code_hash = code.hash_slow();
+                self.contracts.entry(code_hash).or_insert(code);
+                Ok(code_hash)
+            })
+            .transpose()?;
+
+        let entry = self.accounts.entry(address).or_default();
+        entry.balance = account.balance.or(entry.balance);
+        entry.nonce = account.nonce.or(entry.nonce);
+        entry.code_hash = code_hash.or(entry.code_hash);
+        if let Some(state) = account.state {
+            entry.storage = state;
+            entry.storage_replaced = true;
+        }
+        if let Some(state_diff) = account.state_diff {
+            entry.storage.extend(state_diff);
+        }
+        Ok(())
+    }
+
+    /// Overrides the hash of the block.
+    pub fn override_block_hash(&mut self, number: u64, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        let Some(account) = self.accounts.get(&address) else {
+            return Ok(info);
+        };
+        // Overridden accounts always exist.
+        let mut info = info.unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code_hash) = account.code_hash {
+            info.code_hash = code_hash;
+            info.code = if code_hash == KECCAK_EMPTY {
+                Some(Bytecode::default())
+            } else {
+                self.contracts.get(&code_hash).cloned()
+            };
+        }
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.contracts.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.accounts.get(&address) {
+            Some(account) => match account.storage.get(&index) {
+                Some(value) => Ok(*value),
+                None if account.storage_replaced => Ok(U256::ZERO),
+                None => self.db.storage_ref(address, index),
+            },
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.db.block_hash_ref(number),
+        }
+    }
+}
+
+impl<DB: DatabaseRef> Database for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use primitives::{address, bytes, keccak256};
+
+    #[test]
+    fn applies_overrides() {
+        let account = address!("0x0000000000000000000000000000000000000001");
+        let new_account = address!("0x0000000000000000000000000000000000000002");
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            account,
+            AccountInfo::new(U256::from(1), 1, KECCAK_EMPTY, Bytecode::default()),
+        );
+        db.insert_account_storage(account, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(account, U256::from(2), U256::from(2))
+            .unwrap();
+
+        let code = bytes!("6000");
+        let overrides = StateOverride::from_iter([
+            (
+                account,
+                AccountOverride {
+                    nonce: Some(5),
+                    code: Some(code.clone()),
+                    state_diff: Some(HashMap::from_iter([(U256::from(2), U256::from(3))])),
+                    ..Default::default()
+                },
+            ),
+            (
+                new_account,
+                AccountOverride {
+                    state: Some(HashMap::from_iter([(U256::from(1), U256::from(4))])),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let mut db = OverrideDB::with_state_override(db, overrides).unwrap();
+        db.override_block_hash(1, B256::with_last_byte(1));
+
+        let info = db.basic(account).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(1));
+        assert_eq!(info.nonce, 5);
+        assert_eq!(info.code_hash, keccak256(&code));
+        assert_eq!(info.code, Some(Bytecode::new_raw(code)));
+        assert_eq!(db.code_by_hash(info.code_hash).unwrap(), info.code.unwrap());
+        assert_eq!(db.storage(account, U256::from(1)).unwrap(), U256::from(1));
+        assert_eq!(db.storage(account, U256::from(2)).unwrap(), U256::from(3));
+
+        assert_eq!(db.basic(new_account).unwrap(), Some(AccountInfo::default()));
+        assert_eq!(
+            db.storage(new_account, U256::from(1)).unwrap(),
+            U256::from(4)
+        );
+
+        // Full state replacement hides the wrapped storage.
+        db.apply_account_override(
+            account,
+            AccountOverride {
+                state: Some(HashMap::default()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(db.storage(account, U256::from(1)).unwrap(), U256::ZERO);
+        assert_eq!(db.basic(account).unwrap().unwrap().nonce, 5);
+
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(1));
+        assert_eq!(
+            db.apply_account_override(
+                account,
+                AccountOverride {
+                    state: Some(HashMap::default()),
+                    state_diff: Some(HashMap::default()),
+                    ..Default::default()
+                },
+            ),
+            Err(OverrideError::StateAndStateDiff(account))
+        );
+    }
+}
@@ -0,0 +1,741 @@
+use bytecode::{Bytecode, BytecodeDecodeError};
+use core::error::Error;
+use database_interface::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use primitives::{Address, HashMap, HashSet, B256, KECCAK_EMPTY, U256};
+use redb::{ReadableTable, Table, TableDefinition};
+use state::{Account, AccountInfo};
+use std::{boxed::Box, fmt, path::Path, vec::Vec};
+
+use crate::states::{PlainStateReverts, PlainStorageRevert, StateChangeset};
+
+/// Address -> encoded [`AccountInfo`] without code.
+const ACCOUNTS: TableDefinition<'_, &[u8; 20], &[u8; ACCOUNT_LEN]> =
+    TableDefinition::new("accounts");
+/// Code hash -> original bytecode bytes.
+const CONTRACTS: TableDefinition<'_, &[u8; 32], &[u8]> = TableDefinition::new("contracts");
+/// Address ++ slot -> value. Zero values are never stored.
+const STORAGE: TableDefinition<'_, &[u8; 52], &[u8; 32]> = TableDefinition::new("storage");
+/// Block number -> block hash.
+const BLOCK_HASHES: TableDefinition<'_, u64, &[u8; 32]> = TableDefinition::new("block_hashes");
+/// Block ++ address -> account info before the block. Empty value means account did not exist.
+const ACCOUNT_REVERTS: TableDefinition<'_, &[u8; 28], &[u8]> =
+    TableDefinition::new("account_reverts");
+/// Block ++ address ++ slot -> storage value before the block.
+const STORAGE_REVERTS: TableDefinition<'_, &[u8; 60], &[u8; 32]> =
+    TableDefinition::new("storage_reverts");
+/// Block ++ address of accounts whose storage was wiped in the block.
+const STORAGE_WIPE_REVERTS: TableDefinition<'_, &[u8; 28], ()> =
+    TableDefinition::new("storage_wipe_reverts");
+
+/// Length of encoded account: balance (32) + nonce (8) + code hash (32).
+const ACCOUNT_LEN: usize = 72;
+
+/// Error returned by [`PersistentDB`].
+#[derive(Debug)]
+pub enum PersistentDBError {
+    /// Error from the underlying storage engine.
+    Redb(Box<redb::Error>),
+    /// Stored bytecode could not be decoded.
+    Bytecode(BytecodeDecodeError),
+    /// Bytecode for the code hash is not present in the database.
+    MissingCode(B256),
+    /// Stored account revert of the block could not be decoded.
+    InvalidAccountRevert {
+        /// Block of the revert.
+        block: u64,
+        /// Address of the reverted account.
+        address: Address,
+    },
+}
+
+impl DBErrorMarker for PersistentDBError {}
+
+impl fmt::Display for PersistentDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Redb(e) => write!(f, "storage error: {e}"),
+            Self::Bytecode(e) => write!(f, "invalid stored bytecode: {e}"),
+            Self::MissingCode(hash) => write!(f, "missing bytecode for code hash {hash}"),
+            Self::InvalidAccountRevert { block, address } => {
+                write!(f, "invalid account revert of {address} in block {block}")
+            }
+        }
+    }
+}
+
+impl Error for PersistentDBError {}
+
+impl From<BytecodeDecodeError> for PersistentDBError {
+    fn from(e: BytecodeDecodeError) -> Self {
+        Self::Bytecode(e)
+    }
+}
+
+macro_rules! impl_from_redb {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for PersistentDBError {
+                fn from(e: $error) -> Self {
+                    Self::Redb(Box::new(e.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_redb!(
+    redb::Error,
+    redb::DatabaseError,
+    redb::TransactionError,
+    redb::TableError,
+    redb::StorageError,
+    redb::CommitError
+);
+
+/// A persistent on-disk [Database] implementation backed by [redb].
+///
+/// Plain state is kept in separate `accounts`/`storage`/`contracts` tables so that a
+/// [`StateChangeset`] can be applied directly. Every write method runs in a single write
+/// transaction, so a crash leaves the database either before or after the whole batch.
+///
+/// Reverts written with [`PersistentDB::write_plain_state`] are stored per block and can be
+/// applied with [`PersistentDB::unwind`].
+#[derive(Debug)]
+pub struct PersistentDB {
+    db: redb::Database,
+}
+
+impl PersistentDB {
+    /// Opens the database at `path`, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PersistentDBError> {
+        let db = redb::Database::create(path)?;
+        // Create all tables so that read transactions never see a missing table.
+        let tx = db.begin_write()?;
+        tx.open_table(ACCOUNTS)?;
+        tx.open_table(CONTRACTS)?;
+        tx.open_table(STORAGE)?;
+        tx.open_table(BLOCK_HASHES)?;
+        tx.open_table(ACCOUNT_REVERTS)?;
+        tx.open_table(STORAGE_REVERTS)?;
+        tx.open_table(STORAGE_WIPE_REVERTS)?;
+        tx.commit()?;
+        Ok(Self { db })
+    }
+
+    /// Inserts account info, storing its bytecode if present.
+    pub fn insert_account_info(
+        &mut self,
+        address: Address,
+        info: AccountInfo,
+    ) -> Result<(), PersistentDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut accounts = tx.open_table(ACCOUNTS)?;
+            let mut contracts = tx.open_table(CONTRACTS)?;
+            if let Some(code) = &info.code {
+                insert_contract(&mut contracts, info.code_hash, code)?;
+            }
+            accounts.insert(&address.into_array(), &encode_account(&info))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Inserts a storage slot. Zero values remove the slot.
+    pub fn insert_account_storage(
+        &mut self,
+        address: Address,
+        slot: U256,
+        value: U256,
+    ) -> Result<(), PersistentDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut storage = tx.open_table(STORAGE)?;
+            set_storage(&mut storage, address, slot, value)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Inserts a block hash.
+    pub fn insert_block_hash(&mut self, number: u64, hash: B256) -> Result<(), PersistentDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut block_hashes = tx.open_table(BLOCK_HASHES)?;
+            block_hashes.insert(number, &hash.0)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Commits changes from the EVM in a single transaction.
+    ///
+    /// Fallible version of [`DatabaseCommit::commit`].
+    pub fn try_commit(
+        &mut self,
+        changes: HashMap<Address, Account>,
+    ) -> Result<(), PersistentDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut accounts = tx.open_table(ACCOUNTS)?;
+            let mut contracts = tx.open_table(CONTRACTS)?;
+            let mut storage = tx.open_table(STORAGE)?;
+            for (address, account) in changes {
+                if !account.is_touched() {
+                    continue;
+                }
+                if account.is_selfdestructed() {
+                    accounts.remove(&address.into_array())?;
+                    wipe_storage(&mut storage, address)?;
+                    continue;
+                }
+                if account.is_created() {
+                    wipe_storage(&mut storage, address)?;
+                }
+                if let Some(code) = &account.info.code {
+                    insert_contract(&mut contracts, account.info.code_hash, code)?;
+                }
+                accounts.insert(&address.into_array(), &encode_account(&account.info))?;
+                for (slot, value) in account.storage {
+                    set_storage(&mut storage, address, slot, value.present_value())?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Writes plain state and its reverts in a single transaction.
+    ///
+    /// `reverts` are usually obtained together with `changeset` from
+    /// [`BundleState::to_plain_state_and_reverts`][crate::BundleState::to_plain_state_and_reverts],
+    /// where the revert at index `i` belongs to block `first_block + i`.
+    ///
+    /// For accounts with wiped storage, all storage currently in the database is added to
+    /// the revert so that [`PersistentDB::unwind`] can restore it.
+    pub fn write_plain_state(
+        &mut self,
+        changeset: StateChangeset,
+        reverts: PlainStateReverts,
+        first_block: u64,
+    ) -> Result<(), PersistentDBError> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut accounts = tx.open_table(ACCOUNTS)?;
+            let mut contracts = tx.open_table(CONTRACTS)?;
+            let mut storage = tx.open_table(STORAGE)?;
+            let mut account_reverts = tx.open_table(ACCOUNT_REVERTS)?;
+            let mut storage_reverts = tx.open_table(STORAGE_REVERTS)?;
+            let mut storage_wipe_reverts = tx.open_table(STORAGE_WIPE_REVERTS)?;
+
+            // Reverts need storage as it was before the changeset is applied.
+            let blocks = reverts.accounts.into_iter().zip(reverts.storage);
+            for (block, (block_accounts, block_storage)) in (first_block..).zip(blocks) {
+                for (address, info) in block_accounts {
+                    let value = info.as_ref().map(encode_account);
+                    account_reverts.insert(
+                        &block_address_key(block, address),
+                        value.as_ref().map_or(&[][..], |v| &v[..]),
+                    )?;
+                }
+                for PlainStorageRevert {
+                    address,
+                    wiped,
+                    storage_revert,
+                } in block_storage
+                {
+                    for (slot, revert) in &storage_revert {
+                        storage_reverts.insert(
+                            &block_storage_key(block, address, *slot),
+                            &revert.to_previous_value().to_be_bytes(),
+                        )?;
+                    }
+                    if !wiped {
+                        continue;
+                    }
+                    storage_wipe_reverts.insert(&block_address_key(block, address), ())?;
+                    let reverted: HashSet<U256> =
+                        storage_revert.iter().map(|(slot, _)| *slot).collect();
+                    for (slot, value) in read_storage(&storage, address)? {
+                        if !reverted.contains(&slot) {
+                            storage_reverts.insert(
+                                &block_storage_key(block, address, slot),
+                                &value.to_be_bytes(),
+                            )?;
+                        }
+                    }
+                }
+            }
+
+            for (hash, code) in &changeset.contracts {
+                insert_contract(&mut contracts, *hash, code)?;
+            }
+            for (address, info) in &changeset.accounts {
+                match info {
+                    Some(info) => {
+                        accounts.insert(&address.into_array(), &encode_account(info))?;
+                    }
+                    None => {
+                        accounts.remove(&address.into_array())?;
+                    }
+                }
+            }
+            for change in changeset.storage {
+                if change.wipe_storage {
+                    wipe_storage(&mut storage, change.address)?;
+                }
+                for (slot, value) in change.storage {
+                    set_storage(&mut storage, change.address, slot, value)?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Reverts plain state to the end of block `block` by applying all stored reverts of
+    /// later blocks, newest first. Applied reverts and the hashes of later blocks are removed.
+    pub fn unwind(&mut self, block: u64) -> Result<(), PersistentDBError> {
+        let Some(first_block) = block.checked_add(1) else {
+            return Ok(());
+        };
+        let tx = self.db.begin_write()?;
+        {
+            let mut accounts = tx.open_table(ACCOUNTS)?;
+            let mut storage = tx.open_table(STORAGE)?;
+            let mut account_reverts = tx.open_table(ACCOUNT_REVERTS)?;
+            let mut storage_reverts = tx.open_table(STORAGE_REVERTS)?;
+            let mut storage_wipe_reverts = tx.open_table(STORAGE_WIPE_REVERTS)?;
+            let mut block_hashes = tx.open_table(BLOCK_HASHES)?;
+
+            for entry in block_hashes.extract_from_if(first_block.., |_, _| true)? {
+                entry?;
+            }
+
+            let mut blocks = Vec::new();
+            let start = block_address_key(first_block, Address::ZERO);
+            for table_blocks in [
+                blocks_from(&account_reverts, &start)?,
+                blocks_from(&storage_wipe_reverts, &start)?,
+                blocks_from(
+                    &storage_reverts,
+                    &block_storage_key(first_block, Address::ZERO, U256::ZERO),
+                )?,
+            ] {
+                blocks.extend(table_blocks);
+            }
+            blocks.sort_unstable();
+            blocks.dedup();
+
+            for block in blocks.into_iter().rev() {
+                let block_start = block_address_key(block, Address::ZERO);
+                let block_end = block_address_key(block, Address::repeat_byte(0xff));
+
+                let wiped = storage_wipe_reverts
+                    .extract_from_if::<&[u8; 28], _>(&block_start..=&block_end, |_, _| true)?
+                    .map(|entry| entry.map(|(key, _)| address_from_key(&key.value()[8..])))
+                    .collect::<Result<Vec<_>, _>>()?;
+                for address in wiped {
+                    wipe_storage(&mut storage, address)?;
+                }
+
+                let slot_start = block_storage_key(block, Address::ZERO, U256::ZERO);
+                let slot_end = block_storage_key(block, Address::repeat_byte(0xff), U256::MAX);
+                let slots = storage_reverts
+                    .extract_from_if::<&[u8; 60], _>(&slot_start..=&slot_end, |_, _| true)?
+                    .map(|entry| {
+                        entry.map(|(key, value)| {
+                            let key = key.value();
+                            (
+                                address_from_key(&key[8..28]),
+                                U256::from_be_slice(&key[28..]),
+                                U256::from_be_bytes(*value.value()),
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (address, slot, value) in slots {
+                    set_storage(&mut storage, address, slot, value)?;
+                }
+
+                let infos = account_reverts
+                    .extract_from_if::<&[u8; 28], _>(&block_start..=&block_end, |_, _| true)?
+                    .map(|entry| {
+                        entry.map(|(key, value)| {
+                            (address_from_key(&key.value()[8..]), value.value().to_vec())
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                for (address, value) in infos {
+                    if value.is_empty() {
+                        accounts.remove(&address.into_array())?;
+                    } else {
+                        let value: [u8; ACCOUNT_LEN] = value.try_into().map_err(|_| {
+                            PersistentDBError::InvalidAccountRevert { block, address }
+                        })?;
+                        accounts.insert(&address.into_array(), &value)?;
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns all non-zero storage slots of the account.
+    pub fn account_storage(
+        &self,
+        address: Address,
+    ) -> Result<Vec<(U256, U256)>, PersistentDBError> {
+        let tx = self.db.begin_read()?;
+        let storage = tx.open_table(STORAGE)?;
+        read_storage(&storage, address)
+    }
+}
+
+impl DatabaseRef for PersistentDB {
+    type Error = PersistentDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let tx = self.db.begin_read()?;
+        let accounts = tx.open_table(ACCOUNTS)?;
+        Ok(accounts
+            .get(&address.into_array())?
+            .map(|value| decode_account(value.value())))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        let tx = self.db.begin_read()?;
+        let contracts = tx.open_table(CONTRACTS)?;
+        let Some(code) = contracts.get(&code_hash.0)? else {
+            return Err(PersistentDBError::MissingCode(code_hash));
+        };
+        Ok(Bytecode::new_raw_checked(code.value().to_vec().into())?)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let tx = self.db.begin_read()?;
+        let storage = tx.open_table(STORAGE)?;
+        Ok(storage
+            .get(&storage_key(address, index))?
+            .map(|value| U256::from_be_bytes(*value.value()))
+            .unwrap_or_default())
+    }
+
+    /// Returns zero hash for unknown block numbers.
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let tx = self.db.begin_read()?;
+        let block_hashes = tx.open_table(BLOCK_HASHES)?;
+        Ok(block_hashes
+            .get(number)?
+            .map(|value| B256::from(*value.value()))
+            .unwrap_or_default())
+    }
+}
+
+impl Database for PersistentDB {
+    type Error = PersistentDBError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+impl DatabaseCommit for PersistentDB {
+    /// # Panics
+    ///
+    /// Panics if the write fails. Use [`PersistentDB::try_commit`] to handle the error.
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.try_commit(changes)
+            .expect("failed to commit changes to persistent database")
+    }
+}
+
+fn encode_account(info: &AccountInfo) -> [u8; ACCOUNT_LEN] {
+    let mut out = [0u8; ACCOUNT_LEN];
+    out[..32].copy_from_slice(&info.balance.to_be_bytes::<32>());
+    out[32..40].copy_from_slice(&info.nonce.to_be_bytes());
+    out[40..].copy_from_slice(info.code_hash.as_slice());
+    out
+}
+
+fn decode_account(bytes: &[u8; ACCOUNT_LEN]) -> AccountInfo {
+    AccountInfo {
+        balance: U256::from_be_slice(&bytes[..32]),
+        nonce: u64::from_be_bytes(bytes[32..40].try_into().unwrap()),
+        code_hash: B256::from_slice(&bytes[40..]),
+        code: None,
+    }
+}
+
+fn address_from_key(bytes: &[u8]) -> Address {
+    Address::from_slice(&bytes[..20])
+}
+
+fn storage_key(address: Address, slot: U256) -> [u8; 52] {
+    let mut key = [0u8; 52];
+    key[..20].copy_from_slice(address.as_slice());
+    key[20..].copy_from_slice(&slot.to_be_bytes::<32>());
+    key
+}
+
+fn block_address_key(block: u64, address: Address) -> [u8; 28] {
+    let mut key = [0u8; 28];
+    key[..8].copy_from_slice(&block.to_be_bytes());
+    key[8..].copy_from_slice(address.as_slice());
+    key
+}
+
+fn block_storage_key(block: u64, address: Address, slot: U256) -> [u8; 60] {
+    let mut key = [0u8; 60];
+    key[..8].copy_from_slice(&block.to_be_bytes());
+    key[8..].copy_from_slice(&storage_key(address, slot));
+    key
+}
+
+fn insert_contract(
+    contracts: &mut Table<'_, &[u8; 32], &[u8]>,
+    code_hash: B256,
+    code: &Bytecode,
+) -> Result<(), PersistentDBError> {
+    if code_hash != KECCAK_EMPTY && !code.is_empty() {
+        contracts.insert(&code_hash.0, code.original_byte_slice())?;
+    }
+    Ok(())
+}
+
+fn set_storage(
+    storage: &mut Table<'_, &[u8; 52], &[u8; 32]>,
+    address: Address,
+    slot: U256,
+    value: U256,
+) -> Result<(), PersistentDBError> {
+    let key = storage_key(address, slot);
+    if value.is_zero() {
+        storage.remove(&key)?;
+    } else {
+        storage.insert(&key, &value.to_be_bytes())?;
+    }
+    Ok(())
+}
+
+fn wipe_storage(
+    storage: &mut Table<'_, &[u8; 52], &[u8; 32]>,
+    address: Address,
+) -> Result<(), PersistentDBError> {
+    let start = storage_key(address, U256::ZERO);
+    let end = storage_key(address, U256::MAX);
+    storage.retain_in::<&[u8; 52], _>(&start..=&end, |_, _| false)?;
+    Ok(())
+}
+
+fn read_storage(
+    storage: &impl ReadableTable<&'static [u8; 52], &'static [u8; 32]>,
+    address: Address,
+) -> Result<Vec<(U256, U256)>, PersistentDBError> {
+    let start = storage_key(address, U256::ZERO);
+    let end = storage_key(address, U256::MAX);
+    storage
+        .range::<&[u8; 52]>(&start..=&end)?
+        .map(|entry| {
+            let (key, value) = entry?;
+            Ok((
+                U256::from_be_slice(&key.value()[20..]),
+                U256::from_be_bytes(*value.value()),
+            ))
+        })
+        .collect()
+}
+
+/// Returns block numbers of all entries with key at or after `start`.
+fn blocks_from<const N: usize, V: redb::Value + 'static>(
+    table: &impl ReadableTable<&'static [u8; N], V>,
+    start: &[u8; N],
+) -> Result<Vec<u64>, PersistentDBError> {
+    let mut blocks: Vec<u64> = Vec::new();
+    for entry in table.range::<&[u8; N]>(start..)? {
+        let (key, _) = entry?;
+        let block = u64::from_be_bytes(key.value()[..8].try_into().unwrap());
+        if blocks.last() != Some(&block) {
+            blocks.push(block);
+        }
+    }
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{states::bundle_state::BundleRetention, State};
+    use primitives::{address, Bytes};
+    use state::AccountStatus;
+
+    const ADDRESS: Address = address!("0x0000000000000000000000000000000000000001");
+
+    fn contract_account(balance: u64, nonce: u64) -> AccountInfo {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x00]));
+        AccountInfo::new(U256::from(balance), nonce, code.hash_slow(), code)
+    }
+
+    #[test]
+    fn commit_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.redb");
+        let info = contract_account(100, 1);
+        {
+            let mut db = PersistentDB::open(&path).unwrap();
+            let mut account = Account::from(info.clone());
+            account.status = AccountStatus::Touched;
+            account.storage.insert(
+                U256::from(1),
+                state::EvmStorageSlot::new_changed(U256::ZERO, U256::from(5)),
+            );
+            db.commit(HashMap::from_iter([(ADDRESS, account)]));
+            db.insert_block_hash(1, B256::repeat_byte(1)).unwrap();
+        }
+
+        let mut db = PersistentDB::open(&path).unwrap();
+        assert_eq!(db.basic(ADDRESS).unwrap(), Some(info.copy_without_code()));
+        assert_eq!(db.storage(ADDRESS, U256::from(1)).unwrap(), U256::from(5));
+        assert_eq!(db.code_by_hash(info.code_hash).unwrap(), info.code.unwrap());
+        assert_eq!(db.block_hash(1).unwrap(), B256::repeat_byte(1));
+        assert_eq!(db.block_hash(2).unwrap(), B256::ZERO);
+    }
+
+    #[test]
+    fn selfdestruct_wipes_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = PersistentDB::open(dir.path().join("state.redb")).unwrap();
+        db.insert_account_info(ADDRESS, contract_account(1, 1))
+            .unwrap();
+        db.insert_account_storage(ADDRESS, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let account = Account {
+            status: AccountStatus::Touched | AccountStatus::SelfDestructed,
+            ..Default::default()
+        };
+        db.commit(HashMap::from_iter([(ADDRESS, account)]));
+
+        assert_eq!(db.basic(ADDRESS).unwrap(), None);
+        assert!(db.account_storage(ADDRESS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_plain_state_and_unwind() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = PersistentDB::open(dir.path().join("state.redb")).unwrap();
+        let original = contract_account(10, 0);
+        db.insert_account_info(ADDRESS, original.clone()).unwrap();
+        db.insert_account_storage(ADDRESS, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(ADDRESS, U256::from(2), U256::from(2))
+            .unwrap();
+
+        // Block 1 changes slot 1, block 2 changes balance and slot 2.
+        let mut state = State::builder()
+            .with_database_ref(&db)
+            .with_bundle_update()
+            .build();
+        for (block, slot, balance) in [(1u64, 1u64, 10u64), (2, 2, 20)] {
+            let mut account = Account::from(state.basic(ADDRESS).unwrap().unwrap());
+            state.storage(ADDRESS, U256::from(slot)).unwrap();
+            account.status = AccountStatus::Touched;
+            account.info.balance = U256::from(balance);
+            account.storage.insert(
+                U256::from(slot),
+                state::EvmStorageSlot::new_changed(U256::from(slot), U256::from(100 + block)),
+            );
+            state.commit(HashMap::from_iter([(ADDRESS, account)]));
+            state.merge_transitions(BundleRetention::Reverts);
+        }
+        let (changeset, reverts) = state
+            .take_bundle()
+            .to_plain_state_and_reverts(crate::OriginalValuesKnown::Yes);
+        drop(state);
+
+        db.write_plain_state(changeset, reverts, 1).unwrap();
+        for number in 0..=2 {
+            db.insert_block_hash(number, B256::with_last_byte(number as u8 + 1))
+                .unwrap();
+        }
+        assert_eq!(db.basic(ADDRESS).unwrap().unwrap().balance, U256::from(20));
+        assert_eq!(
+            db.account_storage(ADDRESS).unwrap(),
+            vec![
+                (U256::from(1), U256::from(101)),
+                (U256::from(2), U256::from(102))
+            ]
+        );
+
+        db.unwind(1).unwrap();
+        assert_eq!(db.basic(ADDRESS).unwrap().unwrap().balance, U256::from(10));
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(2));
+        assert_eq!(db.block_hash(2).unwrap(), B256::ZERO);
+        assert_eq!(
+            db.account_storage(ADDRESS).unwrap(),
+            vec![
+                (U256::from(1), U256::from(101)),
+                (U256::from(2), U256::from(2))
+            ]
+        );
+
+        db.unwind(0).unwrap();
+        assert_eq!(
+            db.basic(ADDRESS).unwrap(),
+            Some(original.copy_without_code())
+        );
+        assert_eq!(db.block_hash(0).unwrap(), B256::with_last_byte(1));
+        assert_eq!(db.block_hash(1).unwrap(), B256::ZERO);
+        assert_eq!(
+            db.account_storage(ADDRESS).unwrap(),
+            vec![
+                (U256::from(1), U256::from(1)),
+                (U256::from(2), U256::from(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn unwind_invalid_account_revert() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = PersistentDB::open(dir.path().join("state.redb")).unwrap();
+        let info = contract_account(10, 0);
+        db.insert_account_info(ADDRESS, info.clone()).unwrap();
+        let tx = db.db.begin_write().unwrap();
+        tx.open_table(ACCOUNT_REVERTS)
+            .unwrap()
+            .insert(&block_address_key(1, ADDRESS), &[1u8; 3][..])
+            .unwrap();
+        tx.commit().unwrap();
+
+        assert!(matches!(
+            db.unwind(0),
+            Err(PersistentDBError::InvalidAccountRevert {
+                block: 1,
+                address: ADDRESS
+            })
+        ));
+        // Failed unwind does not change the state.
+        assert_eq!(db.basic(ADDRESS).unwrap(), Some(info.copy_without_code()));
+    }
+}
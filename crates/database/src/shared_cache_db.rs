@@ -0,0 +1,233 @@
+//! Cache that can be shared between threads.
+use core::hash::Hash;
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+use std::sync::RwLock;
+
+/// Number of shards per map. Must be a power of two.
+const SHARDS: usize = 16;
+
+/// Map split into [SHARDS] independently locked shards.
+#[derive(Debug)]
+struct ShardedMap<K, V> {
+    shards: [RwLock<HashMap<K, V>>; SHARDS],
+}
+
+impl<K, V> Default for ShardedMap<K, V> {
+    fn default() -> Self {
+        Self {
+            shards: core::array::from_fn(|_| RwLock::new(HashMap::default())),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> ShardedMap<K, V> {
+    fn shard(&self, shard: u8) -> &RwLock<HashMap<K, V>> {
+        &self.shards[shard as usize & (SHARDS - 1)]
+    }
+
+    fn get(&self, shard: u8, key: &K) -> Option<V> {
+        self.shard(shard).read().unwrap().get(key).cloned()
+    }
+
+    /// Inserts the value if the key is vacant and returns the cached value.
+    ///
+    /// If another thread inserted the key in the meantime its value is kept, so all threads
+    /// observe the same value.
+    fn get_or_insert(&self, shard: u8, key: K, value: V) -> V {
+        self.shard(shard)
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert(value)
+            .clone()
+    }
+
+    fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap().len())
+            .sum()
+    }
+}
+
+/// A read-through cache over a [DatabaseRef] that is [Sync] and can be shared between threads.
+///
+/// Unlike [`CacheDB`][crate::CacheDB], it implements [DatabaseRef] and fills its cache through a
+/// shared reference, so many threads can execute against one warm cache, each wrapping
+/// `&SharedCacheDB` in its own [`CacheDB`][crate::CacheDB] or
+/// [`WrapDatabaseRef`][database_interface::WrapDatabaseRef] to hold its local changes.
+///
+/// Maps are split into shards with separate locks to reduce contention. Values are never
+/// modified once cached.
+#[derive(Debug)]
+pub struct SharedCacheDB<ExtDB> {
+    accounts: ShardedMap<Address, Option<AccountInfo>>,
+    storage: ShardedMap<(Address, U256), U256>,
+    contracts: ShardedMap<B256, Bytecode>,
+    block_hashes: ShardedMap<u64, B256>,
+    /// The underlying database ([DatabaseRef]) that is used to load data.
+    pub db: ExtDB,
+}
+
+impl<ExtDB: Default> Default for SharedCacheDB<ExtDB> {
+    fn default() -> Self {
+        Self::new(ExtDB::default())
+    }
+}
+
+impl<ExtDB> SharedCacheDB<ExtDB> {
+    /// Creates a new cache with the given external database.
+    pub fn new(db: ExtDB) -> Self {
+        Self {
+            accounts: ShardedMap::default(),
+            storage: ShardedMap::default(),
+            contracts: ShardedMap::default(),
+            block_hashes: ShardedMap::default(),
+            db,
+        }
+    }
+
+    /// Returns the number of cached accounts.
+    pub fn cached_accounts(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns the number of cached storage slots.
+    pub fn cached_slots(&self) -> usize {
+        self.storage.len()
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for SharedCacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let shard = address[19];
+        if let Some(info) = self.accounts.get(shard, &address) {
+            return Ok(info);
+        }
+        let info = self.db.basic_ref(address)?;
+        if let Some(info) = &info {
+            if let Some(code) = &info.code {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.contracts
+                        .get_or_insert(info.code_hash[31], info.code_hash, code.clone());
+                }
+            }
+        }
+        Ok(self.accounts.get_or_insert(shard, address, info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let shard = code_hash[31];
+        if let Some(code) = self.contracts.get(shard, &code_hash) {
+            return Ok(code);
+        }
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        Ok(self.contracts.get_or_insert(shard, code_hash, code))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        // Spread slots of a single hot contract over all shards.
+        let shard = address[19] ^ index.byte(0);
+        if let Some(value) = self.storage.get(shard, &(address, index)) {
+            return Ok(value);
+        }
+        let value = self.db.storage_ref(address, index)?;
+        Ok(self.storage.get_or_insert(shard, (address, index), value))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let shard = number as u8;
+        if let Some(hash) = self.block_hashes.get(shard, &number) {
+            return Ok(hash);
+        }
+        let hash = self.db.block_hash_ref(number)?;
+        Ok(self.block_hashes.get_or_insert(shard, number, hash))
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for SharedCacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Database counting storage reads.
+    #[derive(Default)]
+    struct CountingDB {
+        reads: AtomicUsize,
+    }
+
+    impl DatabaseRef for CountingDB {
+        type Error = Infallible;
+
+        fn basic_ref(&self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            Ok(Some(AccountInfo::default()))
+        }
+
+        fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            Ok(Bytecode::default())
+        }
+
+        fn storage_ref(&self, _address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            Ok(index + U256::from(1))
+        }
+
+        fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            Ok(B256::with_last_byte(number as u8))
+        }
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let db = SharedCacheDB::new(CountingDB::default());
+        let address = Address::with_last_byte(1);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for slot in 0..64u64 {
+                        let slot = U256::from(slot);
+                        assert_eq!(db.storage_ref(address, slot), Ok(slot + U256::from(1)));
+                    }
+                    assert!(db.basic_ref(address).unwrap().is_some());
+                });
+            }
+        });
+        assert_eq!(db.cached_accounts(), 1);
+        assert_eq!(db.cached_slots(), 64);
+
+        // Warm cache does not reach the database.
+        let reads = db.db.reads.load(Ordering::Relaxed);
+        assert!(reads >= 64);
+        db.storage_ref(address, U256::from(1)).unwrap();
+        assert_eq!(db.db.reads.load(Ordering::Relaxed), reads);
+    }
+}
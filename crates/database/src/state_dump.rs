@@ -0,0 +1,443 @@
+//! State dumps in the JSON format of anvil's `anvil_dumpState` and `--dump-state`.
+//!
+//! Accounts are written and read one at a time, so dumps of any size can be streamed from and
+//! to files without buffering them in memory.
+use crate::{AccountState, CacheDB, State};
+use bytecode::BytecodeDecodeError;
+use context::{block::BlobExcessGasAndPrice, BlockEnv};
+use core::{cell::Cell, fmt};
+use database_interface::Database;
+use primitives::{Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use state::{AccountInfo, Bytecode};
+use std::{
+    collections::BTreeMap,
+    io::{BufReader, BufWriter, Read, Write},
+    string::String,
+    vec::Vec,
+};
+
+/// Account of a state dump.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpAccount {
+    /// Nonce of the account.
+    pub nonce: u64,
+    /// Balance of the account.
+    pub balance: U256,
+    /// Code of the account, empty if it has none.
+    pub code: Bytes,
+    /// Non-zero storage slots of the account.
+    pub storage: BTreeMap<B256, B256>,
+}
+
+impl DumpAccount {
+    /// Creates the dump account from its info and storage.
+    ///
+    /// Code is taken from the info, or from `contracts` if the info does not contain it.
+    pub fn new<'a>(
+        info: &AccountInfo,
+        storage: impl IntoIterator<Item = (&'a U256, &'a U256)>,
+        contracts: &HashMap<B256, Bytecode>,
+    ) -> Self {
+        let code = match &info.code {
+            Some(code) => code.original_bytes(),
+            None => contracts
+                .get(&info.code_hash)
+                .map(Bytecode::original_bytes)
+                .unwrap_or_default(),
+        };
+        Self {
+            nonce: info.nonce,
+            balance: info.balance,
+            code,
+            storage: storage
+                .into_iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(slot, value)| (B256::from(*slot), B256::from(*value)))
+                .collect(),
+        }
+    }
+
+    /// Returns the account info, with code decoded and hashed, and the account storage.
+    pub fn into_parts(self) -> Result<(AccountInfo, HashMap<U256, U256>), BytecodeDecodeError> {
+        let (code_hash, code) = if self.code.is_empty() {
+            (KECCAK_EMPTY, Bytecode::default())
+        } else {
+            let code = Bytecode::new_raw_checked(self.code)?;
+            (code.hash_slow(), code)
+        };
+        let storage = self
+            .storage
+            .into_iter()
+            .map(|(slot, value)| (slot.into(), value.into()))
+            .collect();
+        Ok((
+            AccountInfo::new(self.balance, self.nonce, code_hash, code),
+            storage,
+        ))
+    }
+}
+
+/// Block of a state dump, in the shape written by anvil.
+///
+/// Anvil names the beneficiary `coinbase` and writes number, timestamp, gas limit and base fee
+/// as hex quantities.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpBlock {
+    /// Number of the block.
+    pub number: U256,
+    /// Beneficiary of the block.
+    pub coinbase: Address,
+    /// Timestamp of the block.
+    pub timestamp: U256,
+    /// Gas limit of the block.
+    pub gas_limit: U256,
+    /// Base fee per gas of the block.
+    pub basefee: U256,
+    /// Difficulty of the block.
+    pub difficulty: U256,
+    /// Output of the randomness beacon.
+    #[serde(default)]
+    pub prevrandao: Option<B256>,
+    /// Excess blob gas and blob gas price.
+    #[serde(default)]
+    pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+}
+
+impl From<&BlockEnv> for DumpBlock {
+    fn from(block: &BlockEnv) -> Self {
+        Self {
+            number: U256::from(block.number),
+            coinbase: block.beneficiary,
+            timestamp: U256::from(block.timestamp),
+            gas_limit: U256::from(block.gas_limit),
+            basefee: U256::from(block.basefee),
+            difficulty: block.difficulty,
+            prevrandao: block.prevrandao,
+            blob_excess_gas_and_price: block.blob_excess_gas_and_price,
+        }
+    }
+}
+
+impl From<DumpBlock> for BlockEnv {
+    /// Quantities that do not fit into `u64` are saturated.
+    fn from(block: DumpBlock) -> Self {
+        Self {
+            number: block.number.saturating_to(),
+            beneficiary: block.coinbase,
+            timestamp: block.timestamp.saturating_to(),
+            gas_limit: block.gas_limit.saturating_to(),
+            basefee: block.basefee.saturating_to(),
+            difficulty: block.difficulty,
+            prevrandao: block.prevrandao,
+            blob_excess_gas_and_price: block.blob_excess_gas_and_price,
+        }
+    }
+}
+
+/// Writes a state dump of the accounts to the writer.
+///
+/// Accounts are written in the order of the iterator, which is advanced while writing. Anvil
+/// writes accounts sorted by address.
+pub fn dump_state(
+    writer: impl Write,
+    block: Option<&BlockEnv>,
+    accounts: impl IntoIterator<Item = (Address, DumpAccount)>,
+) -> serde_json::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    serde_json::to_writer(
+        &mut writer,
+        &SerializeDump {
+            block: block.map(DumpBlock::from),
+            accounts: Cell::new(Some(accounts.into_iter())),
+        },
+    )?;
+    writer.flush().map_err(serde_json::Error::io)
+}
+
+/// Reads a state dump, calling `insert` for every account, and returns its block env.
+///
+/// Fields of the dump other than `block` and `accounts`, such as anvil's blocks and
+/// transactions, are ignored.
+pub fn load_state(
+    reader: impl Read,
+    insert: impl FnMut(Address, AccountInfo, HashMap<U256, U256>),
+) -> serde_json::Result<Option<BlockEnv>> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let block = DumpSeed(insert).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(block.map(BlockEnv::from))
+}
+
+impl<ExtDB> CacheDB<ExtDB> {
+    /// Writes the cached accounts as a state dump.
+    ///
+    /// Only existing accounts and cached storage slots are written. Accounts loaded from the
+    /// underlying database may have storage that was never read and is missing from the dump.
+    pub fn dump_state(
+        &self,
+        writer: impl Write,
+        block: Option<&BlockEnv>,
+    ) -> serde_json::Result<()> {
+        let mut addresses: Vec<_> = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.account_state != AccountState::NotExisting)
+            .map(|(address, _)| *address)
+            .collect();
+        addresses.sort_unstable();
+        let accounts = addresses.into_iter().map(|address| {
+            let account = &self.accounts[&address];
+            let account = DumpAccount::new(&account.info, &account.storage, &self.contracts);
+            (address, account)
+        });
+        dump_state(writer, block, accounts)
+    }
+
+    /// Loads accounts from a state dump, replacing their storage, and returns its block env.
+    pub fn load_state(&mut self, reader: impl Read) -> serde_json::Result<Option<BlockEnv>> {
+        load_state(reader, |address, info, storage| {
+            self.insert_account_with_storage(address, info, storage)
+        })
+    }
+}
+
+impl<DB: Database> State<DB> {
+    /// Writes the accounts of the cache as a state dump.
+    ///
+    /// Only existing accounts are written, with their storage slots present in the cache.
+    pub fn dump_state(
+        &self,
+        writer: impl Write,
+        block: Option<&BlockEnv>,
+    ) -> serde_json::Result<()> {
+        let mut addresses: Vec<_> = self
+            .cache
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.account.is_some())
+            .map(|(address, _)| *address)
+            .collect();
+        addresses.sort_unstable();
+        let accounts = addresses.into_iter().filter_map(|address| {
+            let account = self.cache.accounts[&address].account.as_ref()?;
+            let account = DumpAccount::new(&account.info, &account.storage, &self.cache.contracts);
+            Some((address, account))
+        });
+        dump_state(writer, block, accounts)
+    }
+
+    /// Loads accounts from a state dump into the cache and returns its block env.
+    pub fn load_state(&mut self, reader: impl Read) -> serde_json::Result<Option<BlockEnv>> {
+        load_state(reader, |address, info, storage| {
+            if let Some(code) = &info.code {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.cache.contracts.insert(info.code_hash, code.clone());
+                }
+            }
+            self.insert_account_with_storage(address, info, storage)
+        })
+    }
+}
+
+/// Serializes the dump, taking accounts from the iterator as they are written.
+struct SerializeDump<I> {
+    block: Option<DumpBlock>,
+    accounts: Cell<Option<I>>,
+}
+
+impl<I: Iterator<Item = (Address, DumpAccount)>> Serialize for SerializeDump<I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut dump = serializer.serialize_struct("SerializableState", 2)?;
+        dump.serialize_field("block", &self.block)?;
+        dump.serialize_field("accounts", &SerializeAccounts(&self.accounts))?;
+        dump.end()
+    }
+}
+
+struct SerializeAccounts<'a, I>(&'a Cell<Option<I>>);
+
+impl<I: Iterator<Item = (Address, DumpAccount)>> Serialize for SerializeAccounts<'_, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.take().into_iter().flatten())
+    }
+}
+
+/// Deserializes the dump, passing accounts to the closure as they are read.
+struct DumpSeed<F>(F);
+
+impl<'de, F: FnMut(Address, AccountInfo, HashMap<U256, U256>)> DeserializeSeed<'de>
+    for DumpSeed<F>
+{
+    type Value = Option<DumpBlock>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(Address, AccountInfo, HashMap<U256, U256>)> Visitor<'de> for DumpSeed<F> {
+    type Value = Option<DumpBlock>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a state dump")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut block = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "block" => block = map.next_value()?,
+                "accounts" => map.next_value_seed(AccountsSeed(&mut self.0))?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(block)
+    }
+}
+
+struct AccountsSeed<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(Address, AccountInfo, HashMap<U256, U256>)> DeserializeSeed<'de>
+    for AccountsSeed<'_, F>
+{
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(Address, AccountInfo, HashMap<U256, U256>)> Visitor<'de>
+    for AccountsSeed<'_, F>
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of accounts")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        while let Some(address) = map.next_key::<Address>()? {
+            let (info, storage) = map
+                .next_value::<DumpAccount>()?
+                .into_parts()
+                .map_err(|e| de::Error::custom(format_args!("invalid code of {address}: {e}")))?;
+            (self.0)(address, info, storage);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyDB, StateBuilder};
+    use database_interface::DatabaseRef;
+    use primitives::{address, bytes};
+
+    #[test]
+    fn dump_and_load() {
+        let funded = address!("0x0000000000000000000000000000000000000001");
+        let contract = address!("0x0000000000000000000000000000000000000002");
+        let code = Bytecode::new_raw(bytes!("6000"));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(funded, AccountInfo::from_balance(U256::from(10)));
+        db.insert_account_with_storage(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code.clone()),
+            HashMap::from_iter([(U256::from(1), U256::from(2)), (U256::from(2), U256::ZERO)]),
+        );
+        let block = BlockEnv {
+            number: 10,
+            beneficiary: funded,
+            ..Default::default()
+        };
+
+        let mut dump = Vec::new();
+        db.dump_state(&mut dump, Some(&block)).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&dump).unwrap();
+        assert_eq!(json["block"]["number"], "0xa");
+        assert_eq!(json["block"]["coinbase"], funded.to_string());
+        assert_eq!(json["accounts"][contract.to_string()]["code"], "0x6000");
+        assert_eq!(
+            json["accounts"][contract.to_string()]["storage"]
+                .as_object()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let mut loaded = CacheDB::new(EmptyDB::default());
+        assert_eq!(
+            loaded.load_state(dump.as_slice()).unwrap(),
+            Some(block.clone())
+        );
+        assert_eq!(
+            loaded.basic_ref(funded).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        let info = loaded.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(
+            loaded.storage_ref(contract, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+
+        let mut state = StateBuilder::new().build();
+        state.load_state(dump.as_slice()).unwrap();
+        assert_eq!(
+            state.storage(contract, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+        let mut state_dump = Vec::new();
+        state.dump_state(&mut state_dump, Some(&block)).unwrap();
+        assert_eq!(state_dump, dump);
+
+        // Unknown anvil fields are ignored.
+        let anvil = r#"{"block":null,"accounts":{},"best_block_number":"0x0","blocks":[]}"#;
+        assert_eq!(loaded.load_state(anvil.as_bytes()).unwrap(), None);
+    }
+
+    #[test]
+    fn load_anvil_dump() {
+        let dump = include_str!("../testdata/anvil_state_dump.json");
+        let mut db = CacheDB::new(EmptyDB::default());
+        let block = db.load_state(dump.as_bytes()).unwrap().unwrap();
+        assert_eq!(block.number, 2);
+        assert_eq!(block.beneficiary, Address::ZERO);
+        assert_eq!(block.timestamp, 0x66cdc823);
+        assert_eq!(block.gas_limit, 30_000_000);
+        assert_eq!(block.basefee, 0x342a1c58);
+        assert_eq!(block.blob_excess_gas_and_price.unwrap().blob_gasprice, 1);
+
+        let dev = address!("0xf39Fd6e51aad88F6F4ce6aB8827279cfFFb92266");
+        let info = db.basic_ref(dev).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        let counter = address!("0x5FbDB2315678afecb367f032d93F642f64180aa3");
+        let info = db.basic_ref(counter).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        assert_eq!(
+            db.code_by_hash_ref(info.code_hash)
+                .unwrap()
+                .original_bytes(),
+            bytes!("60005460010160005500")
+        );
+        assert_eq!(db.storage_ref(counter, U256::ZERO).unwrap(), U256::from(1));
+
+        // Block is written back in the same shape.
+        let mut written = Vec::new();
+        db.dump_state(&mut written, Some(&block)).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&written).unwrap();
+        let dump: serde_json::Value = serde_json::from_str(dump).unwrap();
+        assert_eq!(written["block"], dump["block"]);
+        let counter = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+        assert_eq!(written["accounts"][counter], dump["accounts"][counter]);
+    }
+}
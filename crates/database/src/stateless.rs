@@ -0,0 +1,443 @@
+//! Stateless database backed by an execution witness.
+mod trie;
+
+use crate::{states::BundleState, witness::ExecutionWitness};
+use alloy_rlp::{Decodable, Encodable, Header};
+use core::{error::Error, fmt};
+use database_interface::{DBErrorMarker, Database, DatabaseRef};
+use primitives::{keccak256, Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+use std::vec::Vec;
+use trie::{nibbles, TrieNode};
+
+/// Error returned by [`StatelessDB`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatelessDBError {
+    /// Trie node with the hash is needed but not present in the witness.
+    MissingTrieNode(B256),
+    /// Bytecode for the code hash is not present in the witness.
+    MissingCode(B256),
+    /// Block hash for the block number is not present in the witness.
+    MissingBlockHash(u64),
+    /// Trie node or leaf value could not be decoded.
+    Rlp(alloy_rlp::Error),
+}
+
+impl DBErrorMarker for StatelessDBError {}
+
+impl fmt::Display for StatelessDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTrieNode(hash) => write!(f, "missing witness for trie node {hash}"),
+            Self::MissingCode(hash) => write!(f, "missing witness for code hash {hash}"),
+            Self::MissingBlockHash(number) => {
+                write!(f, "missing witness for hash of block {number}")
+            }
+            Self::Rlp(e) => write!(f, "invalid witness trie node: {e}"),
+        }
+    }
+}
+
+impl Error for StatelessDBError {}
+
+impl From<alloy_rlp::Error> for StatelessDBError {
+    fn from(e: alloy_rlp::Error) -> Self {
+        Self::Rlp(e)
+    }
+}
+
+/// Account as stored in the state trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: B256,
+    code_hash: B256,
+}
+
+impl TrieAccount {
+    fn encode(&self) -> Vec<u8> {
+        let payload_length = self.nonce.length()
+            + self.balance.length()
+            + self.storage_root.length()
+            + self.code_hash.length();
+        let mut out = Vec::with_capacity(payload_length + 2);
+        Header {
+            list: true,
+            payload_length,
+        }
+        .encode(&mut out);
+        self.nonce.encode(&mut out);
+        self.balance.encode(&mut out);
+        self.storage_root.encode(&mut out);
+        self.code_hash.encode(&mut out);
+        out
+    }
+
+    fn decode(mut buf: &[u8]) -> Result<Self, alloy_rlp::Error> {
+        let mut payload = Header::decode_bytes(&mut buf, true)?;
+        let account = Self {
+            nonce: u64::decode(&mut payload)?,
+            balance: U256::decode(&mut payload)?,
+            storage_root: B256::decode(&mut payload)?,
+            code_hash: B256::decode(&mut payload)?,
+        };
+        if !payload.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength);
+        }
+        Ok(account)
+    }
+}
+
+/// A [Database] that executes against a pre-state root using only the trie nodes and bytecodes
+/// of an [ExecutionWitness].
+///
+/// Accounts and storage are resolved by walking the state trie from the root, so every value
+/// returned is verified against it. Reading anything not covered by the witness fails with a
+/// `Missing*` [StatelessDBError] instead of returning a default.
+///
+/// After execution, [`StatelessDB::apply_bundle`] updates the tries with the resulting
+/// [BundleState] and returns the post-state root.
+#[derive(Clone, Debug)]
+pub struct StatelessDB {
+    /// Trie nodes by their keccak256 hash.
+    nodes: HashMap<B256, Bytes>,
+    /// Bytecodes by their code hash.
+    codes: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    block_hashes: HashMap<u64, B256>,
+    /// State trie.
+    state: TrieNode,
+    /// Storage tries modified by [`StatelessDB::apply_bundle`].
+    storage_tries: HashMap<Address, TrieNode>,
+}
+
+impl StatelessDB {
+    /// Creates a database from the pre-state root, trie nodes and bytecodes.
+    ///
+    /// Nodes and bytecodes are keyed by their own hash, so nodes not linked from the root are
+    /// never used.
+    pub fn new(
+        state_root: B256,
+        nodes: impl IntoIterator<Item = Bytes>,
+        codes: impl IntoIterator<Item = Bytecode>,
+    ) -> Self {
+        Self {
+            nodes: nodes
+                .into_iter()
+                .map(|node| (keccak256(&node), node))
+                .collect(),
+            codes: codes
+                .into_iter()
+                .map(|code| (code.hash_slow(), code))
+                .collect(),
+            block_hashes: HashMap::default(),
+            state: TrieNode::from_root(state_root),
+            storage_tries: HashMap::default(),
+        }
+    }
+
+    /// Creates a database from the pre-state root and a witness with trie nodes.
+    ///
+    /// Plain account and storage values of the witness are not trusted and are ignored, block
+    /// hashes are taken as is.
+    pub fn from_witness(state_root: B256, witness: ExecutionWitness) -> Self {
+        let mut db = Self::new(
+            state_root,
+            witness.state.into_values(),
+            witness.codes.into_values(),
+        );
+        db.block_hashes = witness.block_hashes;
+        db
+    }
+
+    /// Inserts block hash.
+    pub fn insert_block_hash(&mut self, number: u64, hash: B256) {
+        self.block_hashes.insert(number, hash);
+    }
+
+    /// Returns the current state root.
+    pub fn state_root(&self) -> B256 {
+        self.state.root()
+    }
+
+    /// Applies the changes of the bundle to the tries and returns the new state root.
+    ///
+    /// Storage of destroyed accounts is wiped before the bundle storage is applied.
+    pub fn apply_bundle(&mut self, bundle: &BundleState) -> Result<B256, StatelessDBError> {
+        for (hash, code) in &bundle.contracts {
+            self.codes.insert(*hash, code.clone());
+        }
+
+        for (address, account) in &bundle.state {
+            let key = nibbles(keccak256(address).as_slice());
+            let Some(info) = &account.info else {
+                self.state.remove(&key, &self.nodes)?;
+                self.storage_tries.remove(address);
+                continue;
+            };
+
+            let mut storage = if account.was_destroyed() {
+                TrieNode::Empty
+            } else {
+                self.storage_trie(address)?
+            };
+            for (slot, value) in &account.storage {
+                if !account.was_destroyed() && !value.is_changed() {
+                    continue;
+                }
+                let slot_key = nibbles(keccak256(slot.to_be_bytes::<32>()).as_slice());
+                let value = value.present_value();
+                if value.is_zero() {
+                    storage.remove(&slot_key, &self.nodes)?;
+                } else {
+                    storage.insert(&slot_key, alloy_rlp::encode(value), &self.nodes)?;
+                }
+            }
+
+            let account = TrieAccount {
+                nonce: info.nonce,
+                balance: info.balance,
+                storage_root: storage.root(),
+                code_hash: info.code_hash,
+            };
+            self.state.insert(&key, account.encode(), &self.nodes)?;
+            self.storage_tries.insert(*address, storage);
+        }
+        Ok(self.state.root())
+    }
+
+    fn trie_account(&self, address: &Address) -> Result<Option<TrieAccount>, StatelessDBError> {
+        let key = nibbles(keccak256(address).as_slice());
+        self.state
+            .get(&key, &self.nodes)?
+            .map(|value| TrieAccount::decode(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn storage_trie(&self, address: &Address) -> Result<TrieNode, StatelessDBError> {
+        if let Some(trie) = self.storage_tries.get(address) {
+            return Ok(trie.clone());
+        }
+        Ok(self
+            .trie_account(address)?
+            .map(|account| TrieNode::from_root(account.storage_root))
+            .unwrap_or_default())
+    }
+}
+
+impl DatabaseRef for StatelessDB {
+    type Error = StatelessDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let Some(account) = self.trie_account(&address)? else {
+            return Ok(None);
+        };
+        let code = if account.code_hash == KECCAK_EMPTY {
+            Some(Bytecode::default())
+        } else {
+            self.codes.get(&account.code_hash).cloned()
+        };
+        Ok(Some(AccountInfo {
+            balance: account.balance,
+            nonce: account.nonce,
+            code_hash: account.code_hash,
+            code,
+        }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        self.codes
+            .get(&code_hash)
+            .cloned()
+            .ok_or(StatelessDBError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let key = nibbles(keccak256(index.to_be_bytes::<32>()).as_slice());
+        let value = match self.storage_tries.get(&address) {
+            Some(trie) => trie.get(&key, &self.nodes)?,
+            None => match self.trie_account(&address)? {
+                Some(account) => {
+                    TrieNode::from_root(account.storage_root).get(&key, &self.nodes)?
+                }
+                None => None,
+            },
+        };
+        value
+            .map(|value| U256::decode(&mut value.as_slice()))
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(Into::into)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(StatelessDBError::MissingBlockHash(number))
+    }
+}
+
+impl Database for StatelessDB {
+    type Error = StatelessDBError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::{BundleAccount, StorageSlot};
+    use crate::AccountStatus;
+    use primitives::{address, bytes};
+    use trie::tests::collect_nodes;
+
+    type TestAccount<'a> = (Address, AccountInfo, &'a [(U256, U256)]);
+
+    /// Builds a fully resolved state trie with the accounts and their storage.
+    fn build_state(accounts: &[TestAccount<'_>]) -> (TrieNode, HashMap<B256, Bytes>) {
+        let nodes = HashMap::default();
+        let mut witness = HashMap::default();
+        let mut state = TrieNode::Empty;
+        for (address, info, storage) in accounts {
+            let mut trie = TrieNode::Empty;
+            for (slot, value) in *storage {
+                let key = nibbles(keccak256(slot.to_be_bytes::<32>()).as_slice());
+                trie.insert(&key, alloy_rlp::encode(value), &nodes).unwrap();
+            }
+            collect_nodes(&trie, &mut witness);
+            let account = TrieAccount {
+                nonce: info.nonce,
+                balance: info.balance,
+                storage_root: trie.root(),
+                code_hash: info.code_hash,
+            };
+            let key = nibbles(keccak256(address).as_slice());
+            state.insert(&key, account.encode(), &nodes).unwrap();
+        }
+        collect_nodes(&state, &mut witness);
+        (state, witness)
+    }
+
+    #[test]
+    fn reads_and_applies_bundle() {
+        let contract = address!("0x0000000000000000000000000000000000000001");
+        let eoa = address!("0x0000000000000000000000000000000000000002");
+        let missing = address!("0x0000000000000000000000000000000000000003");
+        let code = Bytecode::new_raw(bytes!("6000"));
+        let contract_info = AccountInfo::new(U256::from(1), 1, code.hash_slow(), code.clone());
+        let eoa_info = AccountInfo::from_balance(U256::from(10));
+        let storage = [
+            (U256::from(1), U256::from(2)),
+            (U256::from(3), U256::from(4)),
+        ];
+
+        let (pre, nodes) = build_state(&[
+            (contract, contract_info.clone(), &storage),
+            (eoa, eoa_info.clone(), &[]),
+        ]);
+        let mut db = StatelessDB::new(pre.root(), nodes.values().cloned(), [code.clone()]);
+        db.insert_block_hash(1, B256::with_last_byte(1));
+
+        assert_eq!(db.basic(contract).unwrap(), Some(contract_info.clone()));
+        assert_eq!(db.basic(eoa).unwrap(), Some(eoa_info.clone()));
+        assert_eq!(db.basic(missing).unwrap(), None);
+        assert_eq!(db.storage(contract, U256::from(3)).unwrap(), U256::from(4));
+        assert_eq!(db.storage(contract, U256::from(5)).unwrap(), U256::ZERO);
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(1));
+        assert_eq!(db.block_hash(2), Err(StatelessDBError::MissingBlockHash(2)));
+        assert_eq!(
+            db.code_by_hash(B256::with_last_byte(1)),
+            Err(StatelessDBError::MissingCode(B256::with_last_byte(1)))
+        );
+
+        let empty = StatelessDB::new(pre.root(), [], []);
+        assert_eq!(
+            empty.basic_ref(eoa),
+            Err(StatelessDBError::MissingTrieNode(pre.root()))
+        );
+
+        // Change a slot, clear a slot, remove an account and create a new one.
+        let new_info = AccountInfo::from_balance(U256::from(5));
+        let new_contract_info = AccountInfo {
+            nonce: 2,
+            ..contract_info.clone()
+        };
+        let mut bundle = BundleState::default();
+        bundle.state.insert(
+            contract,
+            BundleAccount::new(
+                Some(contract_info.clone()),
+                Some(new_contract_info.clone()),
+                HashMap::from_iter([
+                    (
+                        U256::from(1),
+                        StorageSlot::new_changed(U256::from(2), U256::ZERO),
+                    ),
+                    (
+                        U256::from(3),
+                        StorageSlot::new_changed(U256::from(4), U256::from(6)),
+                    ),
+                ]),
+                AccountStatus::Changed,
+            ),
+        );
+        bundle.state.insert(
+            eoa,
+            BundleAccount::new(
+                Some(eoa_info),
+                None,
+                HashMap::default(),
+                AccountStatus::Destroyed,
+            ),
+        );
+        bundle.state.insert(
+            missing,
+            BundleAccount::new(
+                None,
+                Some(new_info.clone()),
+                HashMap::default(),
+                AccountStatus::InMemoryChange,
+            ),
+        );
+        let root = db.apply_bundle(&bundle).unwrap();
+
+        let (post, _) = build_state(&[
+            (
+                contract,
+                new_contract_info.clone(),
+                &[(U256::from(3), U256::from(6))],
+            ),
+            (missing, new_info.clone(), &[]),
+        ]);
+        assert_eq!(root, post.root());
+        assert_eq!(db.state_root(), root);
+        assert_eq!(db.basic(eoa).unwrap(), None);
+        assert_eq!(db.basic(missing).unwrap(), Some(new_info));
+        assert_eq!(db.storage(contract, U256::from(1)).unwrap(), U256::ZERO);
+        assert_eq!(db.storage(contract, U256::from(3)).unwrap(), U256::from(6));
+    }
+}
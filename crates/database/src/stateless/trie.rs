@@ -0,0 +1,454 @@
+//! Sparse Merkle-Patricia trie over nodes from an execution witness.
+use super::StatelessDBError;
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use core::mem;
+use primitives::{keccak256, Bytes, HashMap, B256};
+use std::{boxed::Box, vec, vec::Vec};
+
+/// Root hash of an empty trie.
+pub(crate) const EMPTY_ROOT_HASH: B256 =
+    primitives::b256!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Trie node. Nodes that were not needed yet are kept as [`TrieNode::Hash`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TrieNode {
+    #[default]
+    Empty,
+    /// Unresolved node, resolved from witness nodes when it is accessed.
+    Hash(B256),
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        key: Vec<u8>,
+        child: Box<TrieNode>,
+    },
+    Branch {
+        children: Box<[TrieNode; 16]>,
+    },
+}
+
+impl TrieNode {
+    /// Creates a trie from its root hash.
+    pub(crate) fn from_root(root: B256) -> Self {
+        if root == EMPTY_ROOT_HASH {
+            Self::Empty
+        } else {
+            Self::Hash(root)
+        }
+    }
+
+    /// Returns the value stored under the nibble `path`.
+    pub(crate) fn get(
+        &self,
+        path: &[u8],
+        nodes: &HashMap<B256, Bytes>,
+    ) -> Result<Option<Vec<u8>>, StatelessDBError> {
+        match self {
+            Self::Empty => Ok(None),
+            Self::Hash(hash) => resolve(*hash, nodes)?.get(path, nodes),
+            Self::Leaf { key, value } => Ok((key == path).then(|| value.clone())),
+            Self::Extension { key, child } => match path.strip_prefix(key.as_slice()) {
+                Some(rest) => child.get(rest, nodes),
+                None => Ok(None),
+            },
+            Self::Branch { children } => match path.split_first() {
+                Some((nibble, rest)) => children[*nibble as usize].get(rest, nodes),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Inserts `value` under the nibble `path`.
+    pub(crate) fn insert(
+        &mut self,
+        path: &[u8],
+        value: Vec<u8>,
+        nodes: &HashMap<B256, Bytes>,
+    ) -> Result<(), StatelessDBError> {
+        if let Self::Hash(hash) = self {
+            *self = resolve(*hash, nodes)?;
+        }
+        match self {
+            Self::Empty => {
+                *self = Self::Leaf {
+                    key: path.to_vec(),
+                    value,
+                };
+            }
+            Self::Hash(_) => unreachable!("node is resolved"),
+            Self::Leaf { key, value: old } => {
+                if key == path {
+                    *old = value;
+                    return Ok(());
+                }
+                let common = common_prefix(key, path);
+                let mut children = empty_children();
+                children[key[common] as usize] = Self::Leaf {
+                    key: key[common + 1..].to_vec(),
+                    value: mem::take(old),
+                };
+                children[path[common] as usize] = Self::Leaf {
+                    key: path[common + 1..].to_vec(),
+                    value,
+                };
+                *self = with_prefix(&path[..common], Self::Branch { children });
+            }
+            Self::Extension { key, child } => {
+                let common = common_prefix(key, path);
+                if common == key.len() {
+                    return child.insert(&path[common..], value, nodes);
+                }
+                let mut children = empty_children();
+                children[key[common] as usize] =
+                    with_prefix(&key[common + 1..], mem::take(child.as_mut()));
+                children[path[common] as usize] = Self::Leaf {
+                    key: path[common + 1..].to_vec(),
+                    value,
+                };
+                *self = with_prefix(&path[..common], Self::Branch { children });
+            }
+            Self::Branch { children } => {
+                let (nibble, rest) = path.split_first().expect("keys have equal length");
+                children[*nibble as usize].insert(rest, value, nodes)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the value stored under the nibble `path`.
+    ///
+    /// Removing from a branch with two children needs the remaining child to merge it
+    /// into its parent, so that child has to be present in the witness.
+    pub(crate) fn remove(
+        &mut self,
+        path: &[u8],
+        nodes: &HashMap<B256, Bytes>,
+    ) -> Result<(), StatelessDBError> {
+        if let Self::Hash(hash) = self {
+            *self = resolve(*hash, nodes)?;
+        }
+        match self {
+            Self::Empty => {}
+            Self::Hash(_) => unreachable!("node is resolved"),
+            Self::Leaf { key, .. } => {
+                if key == path {
+                    *self = Self::Empty;
+                }
+            }
+            Self::Extension { key, child } => {
+                let Some(rest) = path.strip_prefix(key.as_slice()) else {
+                    return Ok(());
+                };
+                child.remove(rest, nodes)?;
+                let key = mem::take(key);
+                *self = with_prefix(&key, mem::take(child.as_mut()));
+            }
+            Self::Branch { children } => {
+                let (nibble, rest) = path.split_first().expect("keys have equal length");
+                children[*nibble as usize].remove(rest, nodes)?;
+
+                let mut remaining = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| **child != Self::Empty);
+                let (Some((index, _)), None) = (remaining.next(), remaining.next()) else {
+                    return Ok(());
+                };
+                let mut child = mem::take(&mut children[index]);
+                if let Self::Hash(hash) = child {
+                    child = resolve(hash, nodes)?;
+                }
+                *self = with_prefix(&[index as u8], child);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the root hash of the trie.
+    pub(crate) fn root(&self) -> B256 {
+        match self {
+            Self::Empty => EMPTY_ROOT_HASH,
+            Self::Hash(hash) => *hash,
+            node => keccak256(node.encode()),
+        }
+    }
+
+    /// RLP encoding of the node.
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self {
+            Self::Empty => return vec![EMPTY_STRING_CODE],
+            Self::Hash(hash) => return alloy_rlp::encode(hash),
+            Self::Leaf { key, value } => {
+                hex_prefix(key, true).as_slice().encode(&mut payload);
+                value.as_slice().encode(&mut payload);
+            }
+            Self::Extension { key, child } => {
+                hex_prefix(key, false).as_slice().encode(&mut payload);
+                child.encode_reference(&mut payload);
+            }
+            Self::Branch { children } => {
+                for child in children.iter() {
+                    child.encode_reference(&mut payload);
+                }
+                payload.push(EMPTY_STRING_CODE);
+            }
+        }
+        let mut out = Vec::with_capacity(payload.len() + 3);
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    /// Encodes the node as a child of another node: inline if shorter than 32 bytes, hash otherwise.
+    fn encode_reference(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Empty => out.push(EMPTY_STRING_CODE),
+            Self::Hash(hash) => hash.encode(out),
+            node => {
+                let encoded = node.encode();
+                if encoded.len() < 32 {
+                    out.extend_from_slice(&encoded);
+                } else {
+                    keccak256(&encoded).encode(out);
+                }
+            }
+        }
+    }
+
+    /// Decodes RLP encoded node.
+    fn decode(mut buf: &[u8]) -> Result<Self, alloy_rlp::Error> {
+        let mut payload = Header::decode_bytes(&mut buf, true)?;
+        let mut items = Vec::with_capacity(17);
+        while !payload.is_empty() {
+            let item = payload;
+            let header = Header::decode(&mut payload)?;
+            payload = &payload[header.payload_length..];
+            items.push(&item[..item.len() - payload.len()]);
+        }
+        match items.len() {
+            2 => {
+                let mut path = items[0];
+                let (key, is_leaf) = decode_hex_prefix(Header::decode_bytes(&mut path, false)?)?;
+                if is_leaf {
+                    let mut value = items[1];
+                    Ok(Self::Leaf {
+                        key,
+                        value: Header::decode_bytes(&mut value, false)?.to_vec(),
+                    })
+                } else {
+                    Ok(Self::Extension {
+                        key,
+                        child: Box::new(Self::decode_reference(items[1])?),
+                    })
+                }
+            }
+            17 => {
+                let mut children = empty_children();
+                for (child, item) in children.iter_mut().zip(&items) {
+                    *child = Self::decode_reference(item)?;
+                }
+                if items[16] != [EMPTY_STRING_CODE] {
+                    return Err(alloy_rlp::Error::Custom("branch node with value"));
+                }
+                Ok(Self::Branch { children })
+            }
+            _ => Err(alloy_rlp::Error::Custom("invalid trie node")),
+        }
+    }
+
+    /// Decodes child reference, either inline node or node hash.
+    fn decode_reference(mut item: &[u8]) -> Result<Self, alloy_rlp::Error> {
+        if item
+            .first()
+            .is_some_and(|b| *b >= alloy_rlp::EMPTY_LIST_CODE)
+        {
+            return Self::decode(item);
+        }
+        let bytes = Header::decode_bytes(&mut item, false)?;
+        match bytes.len() {
+            0 => Ok(Self::Empty),
+            32 => Ok(Self::Hash(B256::from_slice(bytes))),
+            _ => Err(alloy_rlp::Error::Custom("invalid child reference")),
+        }
+    }
+}
+
+/// Converts bytes into nibbles.
+pub(crate) fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+fn resolve(hash: B256, nodes: &HashMap<B256, Bytes>) -> Result<TrieNode, StatelessDBError> {
+    let node = nodes
+        .get(&hash)
+        .ok_or(StatelessDBError::MissingTrieNode(hash))?;
+    TrieNode::decode(node).map_err(StatelessDBError::Rlp)
+}
+
+fn empty_children() -> Box<[TrieNode; 16]> {
+    Box::new(core::array::from_fn(|_| TrieNode::Empty))
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Prepends `prefix` to the path of `node`, merging it into leaf and extension keys.
+fn with_prefix(prefix: &[u8], node: TrieNode) -> TrieNode {
+    if prefix.is_empty() {
+        return node;
+    }
+    match node {
+        TrieNode::Empty => TrieNode::Empty,
+        TrieNode::Leaf { key, value } => TrieNode::Leaf {
+            key: [prefix, &key].concat(),
+            value,
+        },
+        TrieNode::Extension { key, child } => TrieNode::Extension {
+            key: [prefix, &key].concat(),
+            child,
+        },
+        node => TrieNode::Extension {
+            key: prefix.to_vec(),
+            child: Box::new(node),
+        },
+    }
+}
+
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let odd = nibbles.len() % 2 == 1;
+    let flag = (u8::from(is_leaf) << 1) | u8::from(odd);
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if odd {
+        out.push((flag << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag << 4);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+fn decode_hex_prefix(bytes: &[u8]) -> Result<(Vec<u8>, bool), alloy_rlp::Error> {
+    let Some(first) = bytes.first() else {
+        return Err(alloy_rlp::Error::Custom("empty hex prefix path"));
+    };
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(alloy_rlp::Error::Custom("invalid hex prefix flag"));
+    }
+    let mut key = Vec::with_capacity(bytes.len() * 2);
+    if flag & 1 == 1 {
+        key.push(first & 0x0f);
+    }
+    key.extend(nibbles(&bytes[1..]));
+    Ok((key, flag & 2 == 2))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use hash_db::Hasher;
+    use plain_hasher::PlainHasher;
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+    struct KeccakHasher;
+
+    impl Hasher for KeccakHasher {
+        type Out = B256;
+        type StdHasher = PlainHasher;
+        const LENGTH: usize = 32;
+
+        fn hash(x: &[u8]) -> Self::Out {
+            keccak256(x)
+        }
+    }
+
+    /// Collects all hashed nodes of a fully resolved trie.
+    pub(crate) fn collect_nodes(node: &TrieNode, out: &mut HashMap<B256, Bytes>) {
+        match node {
+            TrieNode::Empty | TrieNode::Hash(_) => return,
+            TrieNode::Leaf { .. } => {}
+            TrieNode::Extension { child, .. } => collect_nodes(child, out),
+            TrieNode::Branch { children } => children.iter().for_each(|c| collect_nodes(c, out)),
+        }
+        let encoded = node.encode();
+        out.insert(keccak256(&encoded), encoded.into());
+    }
+
+    #[test]
+    fn hex_prefix_roundtrip() {
+        for (nibbles, is_leaf) in [
+            (vec![], false),
+            (vec![1], true),
+            (vec![1, 2], false),
+            (vec![0xf, 1, 0xa], true),
+        ] {
+            let encoded = hex_prefix(&nibbles, is_leaf);
+            assert_eq!(decode_hex_prefix(&encoded).unwrap(), (nibbles, is_leaf));
+        }
+    }
+
+    #[test]
+    fn insert_remove_matches_reference() {
+        // Keys sharing prefixes to exercise extension and branch splitting.
+        let keys: Vec<Vec<u8>> = [[0x12u8, 0x34], [0x12, 0x35], [0x13, 0x00], [0xab, 0xcd]]
+            .iter()
+            .map(|k| nibbles(k))
+            .collect();
+        let nodes = HashMap::default();
+
+        let mut trie = TrieNode::Empty;
+        for (i, key) in keys.iter().enumerate() {
+            trie.insert(key, vec![i as u8 + 1; 40], &nodes).unwrap();
+        }
+        let full = trie.clone();
+        let reference = triehash::trie_root::<KeccakHasher, _, _, _>(
+            [[0x12u8, 0x34], [0x12, 0x35], [0x13, 0x00], [0xab, 0xcd]]
+                .iter()
+                .enumerate()
+                .map(|(i, key)| (key.to_vec(), vec![i as u8 + 1; 40])),
+        );
+        assert_eq!(full.root(), reference);
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(trie.get(key, &nodes).unwrap(), Some(vec![i as u8 + 1; 40]));
+        }
+
+        let mut expected = TrieNode::Empty;
+        for (i, key) in keys.iter().enumerate().skip(1) {
+            expected.insert(key, vec![i as u8 + 1; 40], &nodes).unwrap();
+        }
+        trie.remove(&keys[0], &nodes).unwrap();
+        assert_eq!(trie.root(), expected.root());
+
+        for key in &keys[1..] {
+            trie.remove(key, &nodes).unwrap();
+        }
+        assert_eq!(trie, TrieNode::Empty);
+
+        // Walk the same trie from its root hash and witness nodes.
+        let mut witness = HashMap::default();
+        collect_nodes(&full, &mut witness);
+        let from_root = TrieNode::from_root(full.root());
+        assert_eq!(
+            from_root.get(&keys[3], &witness).unwrap(),
+            Some(vec![4; 40])
+        );
+        assert_eq!(
+            from_root.get(&nibbles(&[0x12, 0x36]), &witness).unwrap(),
+            None
+        );
+        assert!(matches!(
+            from_root.get(&keys[0], &HashMap::default()),
+            Err(StatelessDBError::MissingTrieNode(_))
+        ));
+    }
+}
@@ -1,9 +1,13 @@
 pub mod account_status;
 pub mod bundle_account;
 pub mod bundle_state;
+pub mod bundle_view;
 pub mod cache;
 pub mod cache_account;
 pub mod changes;
+pub mod codec;
+#[cfg(feature = "std")]
+mod merge_worker;
 pub mod plain_account;
 pub mod reverts;
 pub mod state;
@@ -14,13 +18,18 @@ pub mod transition_state;
 /// Account status for Block and Bundle states.
 pub use account_status::AccountStatus;
 pub use bundle_account::BundleAccount;
-pub use bundle_state::{BundleBuilder, BundleState, OriginalValuesKnown};
+pub use bundle_state::{BundleBuilder, BundleState, BundleStateIndex, OriginalValuesKnown};
+pub use bundle_view::BundleView;
 pub use cache::CacheState;
 pub use cache_account::CacheAccount;
-pub use changes::{PlainStateReverts, PlainStorageChangeset, PlainStorageRevert, StateChangeset};
+pub use changes::{
+    PlainAccountChangeset, PlainAccountRevert, PlainStateReverts, PlainStorageChangeset,
+    PlainStorageRevert, StateChangeset,
+};
+pub use codec::{CodecError, CODEC_VERSION};
 pub use plain_account::{PlainAccount, StorageSlot, StorageWithOriginalValues};
 pub use reverts::{AccountRevert, RevertToSlot};
-pub use state::{DBBox, State, StateDBBox};
+pub use state::{DBBox, State, StateDBBox, StateIndex};
 pub use state_builder::StateBuilder;
 pub use transition_account::TransitionAccount;
 pub use transition_state::TransitionState;
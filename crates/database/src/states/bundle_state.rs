@@ -1,11 +1,17 @@
 use super::{
-    changes::{PlainStorageChangeset, StateChangeset},
+    changes::{PlainAccountChangeset, PlainStorageChangeset, StateChangeset},
     reverts::{AccountInfoRevert, Reverts},
-    AccountRevert, AccountStatus, BundleAccount, PlainStateReverts, RevertToSlot, StorageSlot,
-    TransitionState,
+    AccountRevert, AccountStatus, BundleAccount, BundleView, PlainStateReverts, RevertToSlot,
+    StorageSlot, TransitionState,
 };
 use bytecode::Bytecode;
-use core::{mem, ops::RangeInclusive};
+use core::{
+    cell::{OnceCell, RefCell},
+    convert::Infallible,
+    mem,
+    ops::RangeInclusive,
+};
+use database_interface::{overlay_range, DatabaseIter, SortedOverlay};
 use primitives::{
     hash_map::{self, Entry},
     Address, HashMap, HashSet, B256, KECCAK_EMPTY, U256,
@@ -532,6 +538,16 @@ impl BundleState {
         self.contracts.get(hash).cloned()
     }
 
+    /// Returns a read-only view of the state after the first `block` blocks of the bundle.
+    ///
+    /// Reverts of later blocks are applied lazily on each query and values that are not in the
+    /// bundle are read from `db`. Returns `None` if the bundle contains less than `block` blocks.
+    ///
+    /// See [`BundleView`] for more details.
+    pub fn view_at<DB>(&self, block: usize, db: DB) -> Option<BundleView<'_, DB>> {
+        BundleView::new(self, block, db)
+    }
+
     /// Consumes [`TransitionState`] by applying the changes and creating the
     /// reverts.
     ///
@@ -594,62 +610,84 @@ impl BundleState {
     pub fn to_plain_state(&self, is_value_known: OriginalValuesKnown) -> StateChangeset {
         // Pessimistically pre-allocate assuming _all_ accounts changed.
         let state_len = self.state.len();
-        let mut accounts = Vec::with_capacity(state_len);
-        let mut storage = Vec::with_capacity(state_len);
-
+        let mut changeset = StateChangeset {
+            accounts: Vec::with_capacity(state_len),
+            storage: Vec::with_capacity(state_len),
+            contracts: Vec::new(),
+        };
         for (address, account) in self.state.iter() {
-            // Append account info if it is changed.
-            let was_destroyed = account.was_destroyed();
-            if is_value_known.is_not_known() || account.is_info_changed() {
-                let info = account.info.as_ref().map(AccountInfo::copy_without_code);
-                accounts.push((*address, info));
-            }
-
-            // Append storage changes
-
-            // Note: Assumption is that revert is going to remove whole plain storage from
-            // database so we can check if plain state was wiped or not.
-            let mut account_storage_changed = Vec::with_capacity(account.storage.len());
-
-            for (key, slot) in account.storage.iter().map(|(k, v)| (*k, *v)) {
-                // If storage was destroyed that means that storage was wiped.
-                // In that case we need to check if present storage value is different then ZERO.
-                let destroyed_and_not_zero = was_destroyed && !slot.present_value.is_zero();
-
-                // If account is not destroyed check if original values was changed,
-                // so we can update it.
-                let not_destroyed_and_changed = !was_destroyed && slot.is_changed();
-
-                if is_value_known.is_not_known()
-                    || destroyed_and_not_zero
-                    || not_destroyed_and_changed
-                {
-                    account_storage_changed.push((key, slot.present_value));
-                }
+            if let Some(change) = plain_account_changeset(*address, account, is_value_known, false)
+            {
+                changeset.push(change);
             }
+        }
+        changeset.contracts = self.plain_contracts().collect();
+        changeset
+    }
 
-            if !account_storage_changed.is_empty() || was_destroyed {
-                // Append storage changes to account.
-                storage.push(PlainStorageChangeset {
-                    address: *address,
-                    wipe_storage: was_destroyed,
-                    storage: account_storage_changed,
-                });
-            }
+    /// Generate a sorted [`StateChangeset`] from the bundle state without consuming it.
+    ///
+    /// Accounts, storage and contracts are sorted by address, slot key and bytecode hash, and
+    /// every one of them is present only once. Sorted data can be inserted directly into
+    /// databases that store it in a B-tree.
+    ///
+    /// See [`Self::sorted_plain_state_iter`] for the variant that does not collect the changes.
+    pub fn to_sorted_plain_state(&self, is_value_known: OriginalValuesKnown) -> StateChangeset {
+        let state_len = self.state.len();
+        let mut changeset = StateChangeset {
+            accounts: Vec::with_capacity(state_len),
+            storage: Vec::with_capacity(state_len),
+            contracts: Vec::new(),
+        };
+        for change in self.sorted_plain_state_iter(is_value_known) {
+            changeset.push(change);
         }
+        changeset.contracts = self.sorted_plain_contracts_iter().collect();
+        changeset
+    }
+
+    /// Returns an iterator over the account changes sorted by address.
+    ///
+    /// Storage changes of every account are sorted by slot key. Accounts without changes to be
+    /// written are skipped. Only the addresses are collected up front, the changes of each
+    /// account are created when the iterator reaches it.
+    ///
+    /// Contracts are returned separately by [`Self::sorted_plain_contracts_iter`].
+    pub fn sorted_plain_state_iter(
+        &self,
+        is_value_known: OriginalValuesKnown,
+    ) -> impl Iterator<Item = PlainAccountChangeset> + '_ {
+        let mut addresses: Vec<_> = self.state.keys().copied().collect();
+        addresses.sort_unstable();
+        addresses.into_iter().filter_map(move |address| {
+            plain_account_changeset(address, &self.state[&address], is_value_known, true)
+        })
+    }
 
-        let contracts = self
+    /// Returns an iterator over the contracts sorted by bytecode hash.
+    ///
+    /// Empty bytecode is skipped.
+    pub fn sorted_plain_contracts_iter(&self) -> impl Iterator<Item = (B256, Bytecode)> + '_ {
+        let mut hashes: Vec<_> = self
             .contracts
+            .keys()
+            // Remove empty bytecodes
+            .filter(|hash| **hash != KECCAK_EMPTY)
+            .copied()
+            .collect();
+        hashes.sort_unstable();
+        hashes
+            .into_iter()
+            .map(move |hash| (hash, self.contracts[&hash].clone()))
+    }
+
+    /// Returns an iterator over the non empty contracts.
+    fn plain_contracts(&self) -> impl Iterator<Item = (B256, Bytecode)> + '_ {
+        self.contracts
             .iter()
             // Remove empty bytecodes
             .filter(|(b, _)| **b != KECCAK_EMPTY)
             .map(|(b, code)| (*b, code.clone()))
-            .collect::<Vec<_>>();
-        StateChangeset {
-            accounts,
-            storage,
-            contracts,
-        }
     }
 
     /// Converts the bundle state into a [`StateChangeset`].
@@ -670,6 +708,20 @@ impl BundleState {
         )
     }
 
+    /// Generates a sorted [`StateChangeset`] and sorted [`PlainStateReverts`] from the bundle
+    /// state.
+    ///
+    /// See [`Self::to_sorted_plain_state`] and [`Reverts::to_sorted_plain_state_reverts`].
+    pub fn to_sorted_plain_state_and_reverts(
+        &self,
+        is_value_known: OriginalValuesKnown,
+    ) -> (StateChangeset, PlainStateReverts) {
+        (
+            self.to_sorted_plain_state(is_value_known),
+            self.reverts.to_sorted_plain_state_reverts(),
+        )
+    }
+
     /// Consumes the bundle state and split it into a [`StateChangeset`] and a
     /// [`PlainStateReverts`].
     #[deprecated = "Use `to_plain_state_and_reverts` instead"]
@@ -861,6 +913,186 @@ impl BundleState {
     }
 }
 
+/// Pages through a fresh [BundleStateIndex], use [BundleState::iter_index] to sort the bundle
+/// only once when reading multiple pages.
+impl DatabaseIter for BundleState {
+    type Error = Infallible;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        self.iter_index().accounts_range(start, limit)
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        self.iter_index().storage_range(address, start, limit)
+    }
+}
+
+impl BundleState {
+    /// Returns an index that iterates present accounts and storage of the bundle.
+    ///
+    /// Accounts and storage are sorted on first use, after that a page costs `O(limit + log N)`.
+    pub fn iter_index(&self) -> BundleStateIndex<'_> {
+        BundleStateIndex {
+            bundle: self,
+            accounts: OnceCell::new(),
+            storage: RefCell::new(HashMap::default()),
+        }
+    }
+}
+
+/// Sorted index of a borrowed [BundleState], see [BundleState::iter_index].
+#[derive(Debug)]
+pub struct BundleStateIndex<'a> {
+    bundle: &'a BundleState,
+    accounts: OnceCell<SortedOverlay<Address, AccountInfo>>,
+    storage: RefCell<HashMap<Address, SortedOverlay<U256, U256>>>,
+}
+
+impl BundleStateIndex<'_> {
+    /// Returns the page of accounts of the bundle on top of the `inner` range.
+    pub(crate) fn accounts_range_over<E>(
+        &self,
+        start: Address,
+        limit: usize,
+        inner: impl FnOnce(usize) -> Result<Vec<(Address, AccountInfo)>, E>,
+    ) -> Result<Vec<(Address, AccountInfo)>, E> {
+        let accounts = self.accounts.get_or_init(|| {
+            SortedOverlay::new(
+                self.bundle
+                    .state
+                    .iter()
+                    .map(|(address, account)| (*address, account.info.clone())),
+            )
+        });
+        overlay_range(
+            start,
+            limit,
+            accounts
+                .range(start)
+                .map(|(address, info)| (address, info.cloned())),
+            inner,
+        )
+    }
+
+    /// Returns the page of the account storage of the bundle on top of the `inner` range.
+    ///
+    /// The `inner` range is skipped if the account storage was destroyed in the bundle.
+    pub(crate) fn storage_range_over<E>(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+        inner: impl FnOnce(usize) -> Result<Vec<(U256, U256)>, E>,
+    ) -> Result<Vec<(U256, U256)>, E> {
+        let Some(account) = self.bundle.state.get(&address) else {
+            return inner(limit);
+        };
+        let mut storage = self.storage.borrow_mut();
+        let overlay = storage.entry(address).or_insert_with(|| {
+            SortedOverlay::new(account.storage.iter().map(|(slot, value)| {
+                let value = value.present_value();
+                (*slot, (!value.is_zero()).then_some(value))
+            }))
+        });
+        overlay_range(
+            start,
+            limit,
+            overlay
+                .range(start)
+                .map(|(slot, value)| (slot, value.copied())),
+            |limit| {
+                if account.was_destroyed() {
+                    Ok(Vec::new())
+                } else {
+                    inner(limit)
+                }
+            },
+        )
+    }
+}
+
+impl DatabaseIter for BundleStateIndex<'_> {
+    type Error = Infallible;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        self.accounts_range_over(start, limit, |_| Ok(Vec::new()))
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        self.storage_range_over(address, start, limit, |_| Ok(Vec::new()))
+    }
+}
+
+/// Creates the changes of the account that need to be written to the database.
+///
+/// Returns `None` if there is nothing to write.
+fn plain_account_changeset(
+    address: Address,
+    account: &BundleAccount,
+    is_value_known: OriginalValuesKnown,
+    sort_storage: bool,
+) -> Option<PlainAccountChangeset> {
+    // Append account info if it is changed.
+    let was_destroyed = account.was_destroyed();
+    let info = (is_value_known.is_not_known() || account.is_info_changed())
+        .then(|| account.info.as_ref().map(AccountInfo::copy_without_code));
+
+    // Append storage changes
+
+    // Note: Assumption is that revert is going to remove whole plain storage from
+    // database so we can check if plain state was wiped or not.
+    let mut account_storage_changed = Vec::with_capacity(account.storage.len());
+
+    for (key, slot) in account.storage.iter().map(|(k, v)| (*k, *v)) {
+        // If storage was destroyed that means that storage was wiped.
+        // In that case we need to check if present storage value is different then ZERO.
+        let destroyed_and_not_zero = was_destroyed && !slot.present_value.is_zero();
+
+        // If account is not destroyed check if original values was changed,
+        // so we can update it.
+        let not_destroyed_and_changed = !was_destroyed && slot.is_changed();
+
+        if is_value_known.is_not_known() || destroyed_and_not_zero || not_destroyed_and_changed {
+            account_storage_changed.push((key, slot.present_value));
+        }
+    }
+    if sort_storage {
+        account_storage_changed.sort_unstable_by_key(|(key, _)| *key);
+    }
+
+    // Append storage changes to account.
+    let storage =
+        (!account_storage_changed.is_empty() || was_destroyed).then_some(PlainStorageChangeset {
+            address,
+            wipe_storage: was_destroyed,
+            storage: account_storage_changed,
+        });
+
+    (info.is_some() || storage.is_some()).then_some(PlainAccountChangeset {
+        address,
+        info,
+        storage,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1333,4 +1565,93 @@ mod tests {
             .insert(B256::default(), Bytecode::default());
         assert!(builder.get_contracts_mut().contains_key(&B256::default()));
     }
+
+    #[test]
+    fn sorted_plain_state() {
+        let mut bundle = test_bundle1();
+        bundle.extend(test_bundle2());
+        for address in 2..20 {
+            let address = Address::with_last_byte(address);
+            bundle.state.insert(
+                address,
+                BundleAccount::new(
+                    None,
+                    Some(AccountInfo::default()),
+                    (0..10u64)
+                        .rev()
+                        .map(|slot| {
+                            (
+                                U256::from(slot),
+                                StorageSlot::new_changed(U256::ZERO, U256::from(slot + 1)),
+                            )
+                        })
+                        .collect(),
+                    AccountStatus::Changed,
+                ),
+            );
+        }
+
+        let sorted = bundle.to_sorted_plain_state(OriginalValuesKnown::Yes);
+        assert!(sorted.accounts.is_sorted_by_key(|(address, _)| *address));
+        assert!(sorted.storage.is_sorted_by_key(|storage| storage.address));
+        assert!(sorted
+            .storage
+            .iter()
+            .all(|storage| storage.storage.is_sorted_by_key(|(key, _)| *key)));
+
+        // Same content as the unsorted changeset.
+        let mut plain = bundle.to_plain_state(OriginalValuesKnown::Yes);
+        plain.accounts.sort_by_key(|(address, _)| *address);
+        plain.storage.sort_by_key(|storage| storage.address);
+        for storage in &mut plain.storage {
+            storage.storage.sort_by_key(|(key, _)| *key);
+        }
+        assert_eq!(sorted.accounts, plain.accounts);
+        assert_eq!(sorted.storage, plain.storage);
+
+        // Iterator yields the same changes.
+        let mut changeset = StateChangeset::default();
+        for change in bundle.sorted_plain_state_iter(OriginalValuesKnown::Yes) {
+            changeset.push(change);
+        }
+        assert_eq!(changeset.accounts, sorted.accounts);
+        assert_eq!(changeset.storage, sorted.storage);
+
+        let reverts = bundle.reverts.to_sorted_plain_state_reverts();
+        for (accounts, storage) in reverts.accounts.iter().zip(&reverts.storage) {
+            assert!(accounts.is_sorted_by_key(|(address, _)| *address));
+            assert!(storage.is_sorted_by_key(|revert| revert.address));
+            assert!(storage
+                .iter()
+                .all(|revert| revert.storage_revert.is_sorted_by_key(|(key, _)| *key)));
+        }
+
+        // Same content as the unsorted reverts.
+        let mut plain = bundle.reverts.to_plain_state_reverts();
+        for accounts in &mut plain.accounts {
+            accounts.sort_by_key(|(address, _)| *address);
+        }
+        for storage in &mut plain.storage {
+            storage.sort_by_key(|revert| revert.address);
+            for revert in storage {
+                revert.storage_revert.sort_by_key(|(key, _)| *key);
+            }
+        }
+        assert_eq!(reverts.accounts, plain.accounts);
+        assert_eq!(reverts.storage, plain.storage);
+
+        // Iterator yields the same reverts per transition.
+        let transitions: Vec<Vec<_>> = bundle
+            .reverts
+            .sorted_plain_state_reverts_iter()
+            .map(Iterator::collect)
+            .collect();
+        assert_eq!(transitions.len(), reverts.accounts.len());
+        let mut from_iter = PlainStateReverts::default();
+        for transition in transitions {
+            from_iter.push_transition(transition);
+        }
+        assert_eq!(from_iter.accounts, reverts.accounts);
+        assert_eq!(from_iter.storage, reverts.storage);
+    }
 }
@@ -0,0 +1,256 @@
+use super::{reverts::AccountInfoRevert, AccountRevert, BundleState};
+use bytecode::Bytecode;
+use database_interface::DatabaseRef;
+use primitives::{Address, HashMap, B256, U256};
+use state::AccountInfo;
+use std::vec::Vec;
+
+/// Read-only view of a [`BundleState`] at an earlier block of the bundle.
+///
+/// Values are looked up in the bundle and the reverts of the blocks after the viewed one are
+/// applied lazily on each query, the bundle is never modified. Accounts and storage that the
+/// bundle does not know about are read from the underlying database, which is expected to hold
+/// the state before the first block of the bundle.
+///
+/// Created with [`BundleState::view_at`].
+#[derive(Debug)]
+pub struct BundleView<'a, DB> {
+    bundle: &'a BundleState,
+    /// Number of bundle blocks applied, reverts from this index onward are reverted.
+    block: usize,
+    /// Reverts of every block per account, ordered by block index.
+    reverts: HashMap<Address, Vec<(usize, &'a AccountRevert)>>,
+    db: DB,
+}
+
+impl<'a, DB> BundleView<'a, DB> {
+    /// Creates a view of the state after the first `block` blocks of the bundle.
+    ///
+    /// Returns `None` if the bundle contains less than `block` blocks.
+    pub fn new(bundle: &'a BundleState, block: usize, db: DB) -> Option<Self> {
+        if block > bundle.reverts.len() {
+            return None;
+        }
+        let mut reverts: HashMap<Address, Vec<_>> = HashMap::default();
+        for (index, block_reverts) in bundle.reverts.iter().enumerate() {
+            for (address, revert) in block_reverts {
+                reverts.entry(*address).or_default().push((index, revert));
+            }
+        }
+        Some(Self {
+            bundle,
+            block,
+            reverts,
+            db,
+        })
+    }
+
+    /// Returns the number of bundle blocks applied in this view.
+    pub fn block(&self) -> usize {
+        self.block
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Consumes the view and returns the underlying database.
+    pub fn into_db(self) -> DB {
+        self.db
+    }
+
+    /// Returns the reverts of the account for blocks after the viewed one.
+    fn reverts_after(&self, address: &Address) -> &[(usize, &'a AccountRevert)] {
+        let Some(reverts) = self.reverts.get(address) else {
+            return &[];
+        };
+        let start = reverts.partition_point(|(index, _)| *index < self.block);
+        &reverts[start..]
+    }
+
+    /// Returns true if the account storage was wiped before the given bundle block.
+    fn wiped_before(&self, address: &Address, block: usize) -> bool {
+        self.reverts.get(address).is_some_and(|reverts| {
+            reverts
+                .iter()
+                .take_while(|(index, _)| *index < block)
+                .any(|(_, revert)| revert.wipe_storage)
+        })
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for BundleView<'_, DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        // Oldest revert after the viewed block holds the account as it was at that block.
+        for (_, revert) in self.reverts_after(&address) {
+            match &revert.account {
+                AccountInfoRevert::DoNothing => continue,
+                AccountInfoRevert::DeleteIt => return Ok(None),
+                AccountInfoRevert::RevertTo(info) => return Ok(Some(info.clone())),
+            }
+        }
+        match self.bundle.account(&address) {
+            Some(account) => Ok(account.info.clone()),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.bundle.bytecode(&code_hash) {
+            Some(code) => Ok(code),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        for (block, revert) in self.reverts_after(&address) {
+            if let Some(slot) = revert.storage.get(&index) {
+                return Ok(slot.to_previous_value());
+            }
+            if revert.wipe_storage {
+                // Slot was not changed in the bundle before the wipe, so it holds the value
+                // from before the bundle, unless the storage was already wiped earlier.
+                if self.wiped_before(&address, *block) {
+                    return Ok(U256::ZERO);
+                }
+                return self.db.storage_ref(address, index);
+            }
+        }
+        match self
+            .bundle
+            .account(&address)
+            .and_then(|account| account.storage_slot(index))
+        {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{states::RevertToSlot, AccountStatus, EmptyDB};
+    use primitives::KECCAK_EMPTY;
+    use std::vec;
+
+    fn account() -> Address {
+        Address::with_last_byte(1)
+    }
+
+    fn info(nonce: u64, balance: u64) -> AccountInfo {
+        AccountInfo {
+            nonce,
+            balance: U256::from(balance),
+            code_hash: KECCAK_EMPTY,
+            code: None,
+        }
+    }
+
+    fn block1() -> BundleState {
+        BundleState::new(
+            vec![(
+                account(),
+                None,
+                Some(info(1, 10)),
+                HashMap::from_iter([
+                    (U256::from(1), (U256::ZERO, U256::from(10))),
+                    (U256::from(2), (U256::ZERO, U256::from(15))),
+                ]),
+            )],
+            vec![vec![(
+                account(),
+                Some(None),
+                vec![(U256::from(1), U256::ZERO), (U256::from(2), U256::ZERO)],
+            )]],
+            vec![],
+        )
+    }
+
+    fn block2() -> BundleState {
+        BundleState::new(
+            vec![(
+                account(),
+                Some(info(1, 10)),
+                Some(info(3, 20)),
+                HashMap::from_iter([(U256::from(1), (U256::from(10), U256::from(25)))]),
+            )],
+            vec![vec![(
+                account(),
+                Some(Some(info(1, 10))),
+                vec![(U256::from(1), U256::from(10))],
+            )]],
+            vec![],
+        )
+    }
+
+    /// Checks that the view at every block matches the bundle reverted to that block.
+    fn assert_views(bundle: &BundleState) {
+        let blocks = bundle.reverts.len();
+        for block in 0..=blocks {
+            let view = bundle.view_at(block, EmptyDB::default()).unwrap();
+            let mut reverted = bundle.clone();
+            reverted.revert(blocks - block);
+            let expected = reverted.account(&account());
+
+            assert_eq!(
+                view.basic_ref(account()).unwrap(),
+                expected.and_then(|account| account.info.clone()),
+                "account at block {block}"
+            );
+            for slot in 1..=3 {
+                let slot = U256::from(slot);
+                assert_eq!(
+                    view.storage_ref(account(), slot).unwrap(),
+                    expected
+                        .and_then(|account| account.storage_slot(slot))
+                        .unwrap_or_default(),
+                    "slot {slot} at block {block}"
+                );
+            }
+        }
+        assert!(bundle.view_at(blocks + 1, EmptyDB::default()).is_none());
+    }
+
+    #[test]
+    fn view_at_changed() {
+        let mut bundle = block1();
+        bundle.extend(block2());
+        assert_views(&bundle);
+
+        let view = bundle.view_at(1, EmptyDB::default()).unwrap();
+        assert_eq!(view.basic_ref(account()).unwrap(), Some(info(1, 10)));
+        assert_eq!(
+            view.storage_ref(account(), U256::from(1)).unwrap(),
+            U256::from(10)
+        );
+    }
+
+    #[test]
+    fn view_at_destroyed() {
+        let mut second = block2();
+        second.state.get_mut(&account()).unwrap().status = AccountStatus::Destroyed;
+        second.reverts[0][0].1.wipe_storage = true;
+
+        let mut bundle = block1();
+        bundle.extend(second);
+        assert_eq!(
+            bundle.reverts[1][0].1.storage.get(&U256::from(2)),
+            Some(&RevertToSlot::Some(U256::from(15)))
+        );
+        assert_views(&bundle);
+
+        let view = bundle.view_at(2, EmptyDB::default()).unwrap();
+        assert_eq!(
+            view.storage_ref(account(), U256::from(2)).unwrap(),
+            U256::ZERO
+        );
+    }
+}
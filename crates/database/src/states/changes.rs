@@ -10,7 +10,9 @@ use std::vec::Vec;
 /// that mostly have separate tables to store `accounts`/`storages`/`contracts` data.
 ///
 /// **Note**: That data is **not** sorted. Some database benefit of faster inclusion
-/// and smaller footprint if data is inserted in sorted order.
+/// and smaller footprint if data is inserted in sorted order, use
+/// [`BundleState::to_sorted_plain_state`][crate::BundleState::to_sorted_plain_state]
+/// to get sorted data.
 #[derive(Clone, Debug, Default)]
 pub struct StateChangeset {
     /// Vector of **not** sorted accounts information.
@@ -21,6 +23,33 @@ pub struct StateChangeset {
     pub contracts: Vec<(B256, Bytecode)>,
 }
 
+impl StateChangeset {
+    /// Appends the account changes.
+    pub fn push(&mut self, change: PlainAccountChangeset) {
+        if let Some(info) = change.info {
+            self.accounts.push((change.address, info));
+        }
+        if let Some(storage) = change.storage {
+            self.storage.push(storage);
+        }
+    }
+}
+
+/// Changes of a single account for inclusion into database.
+///
+/// Returned by [`BundleState::sorted_plain_state_iter`][crate::BundleState::sorted_plain_state_iter].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlainAccountChangeset {
+    /// Address of account
+    pub address: Address,
+    /// Changed account information.
+    ///
+    /// **Note**: `Some(None)` means that account needs to be removed.
+    pub info: Option<Option<AccountInfo>>,
+    /// Storage changes, `None` if storage is not changed.
+    pub storage: Option<PlainStorageChangeset>,
+}
+
 /// Plain storage changeset.
 ///
 /// Used to apply storage changes of plain state to the database.
@@ -54,7 +83,8 @@ pub struct PlainStorageRevert {
 
 /// Plain state reverts are used to easily store reverts into database.
 ///
-/// Note that accounts are assumed **not** sorted.
+/// Note that accounts are assumed **not** sorted, unless created with
+/// [`Reverts::to_sorted_plain_state_reverts`][crate::states::reverts::Reverts::to_sorted_plain_state_reverts].
 #[derive(Clone, Debug, Default)]
 pub struct PlainStateReverts {
     /// Vector of account with removed contracts bytecode.
@@ -73,6 +103,37 @@ impl PlainStateReverts {
             storage: Vec::with_capacity(capacity),
         }
     }
+
+    /// Appends the account reverts of a transition.
+    pub fn push_transition(&mut self, reverts: impl IntoIterator<Item = PlainAccountRevert>) {
+        let mut accounts = Vec::new();
+        let mut storage = Vec::new();
+        for revert in reverts {
+            if let Some(info) = revert.info {
+                accounts.push((revert.address, info));
+            }
+            if let Some(revert) = revert.storage {
+                storage.push(revert);
+            }
+        }
+        self.accounts.push(accounts);
+        self.storage.push(storage);
+    }
+}
+
+/// Reverts of a single account in a transition for inclusion into database.
+///
+/// Returned by [`Reverts::sorted_plain_state_reverts_iter`][crate::states::reverts::Reverts::sorted_plain_state_reverts_iter].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlainAccountRevert {
+    /// Address of account
+    pub address: Address,
+    /// Account information to revert to.
+    ///
+    /// **Note**: `Some(None)` means that account needs to be removed.
+    pub info: Option<Option<AccountInfo>>,
+    /// Storage reverts, `None` if storage is not reverted.
+    pub storage: Option<PlainStorageRevert>,
 }
 
 /// Storage reverts
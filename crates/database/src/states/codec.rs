@@ -0,0 +1,730 @@
+//! Compact binary encoding of [BundleState] and [Reverts].
+//!
+//! The encoding is versioned and deterministic: accounts are sorted by address, slots by key,
+//! bytecode is stored once per code hash and referenced by index, and integers are encoded as
+//! varints. All encodings start with [CODEC_VERSION].
+use super::{
+    reverts::{AccountInfoRevert, Reverts},
+    AccountRevert, AccountStatus, BundleAccount, BundleState, RevertToSlot, StorageSlot,
+};
+use bytecode::{Bytecode, BytecodeDecodeError};
+use core::{error::Error, fmt};
+use primitives::{Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use state::AccountInfo;
+use std::{vec, vec::Vec};
+
+/// Version of the encoding written by [`BundleState::encode_compact`] and
+/// [`Reverts::encode_compact`].
+pub const CODEC_VERSION: u8 = 1;
+
+/// Error returned when decoding a compact encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// Encoding was written by an unsupported version of the codec.
+    UnsupportedVersion(u8),
+    /// Input ended in the middle of a value.
+    UnexpectedEnd,
+    /// Input has bytes left after the encoded value.
+    TrailingBytes,
+    /// Varint does not fit into 64 bits or number does not fit into 256 bits.
+    Overflow,
+    /// Unknown tag of an enum value.
+    InvalidTag(u8),
+    /// Reference to a bytecode that is not in the bytecode table.
+    MissingBytecode(u64),
+    /// Bytecode could not be decoded.
+    Bytecode(BytecodeDecodeError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(f, "unsupported codec version {version}"),
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::TrailingBytes => f.write_str("trailing bytes after encoded value"),
+            Self::Overflow => f.write_str("number overflow"),
+            Self::InvalidTag(tag) => write!(f, "invalid tag {tag}"),
+            Self::MissingBytecode(index) => write!(f, "missing bytecode {index}"),
+            Self::Bytecode(e) => write!(f, "invalid bytecode: {e}"),
+        }
+    }
+}
+
+impl Error for CodecError {}
+
+impl From<BytecodeDecodeError> for CodecError {
+    fn from(e: BytecodeDecodeError) -> Self {
+        Self::Bytecode(e)
+    }
+}
+
+impl BundleState {
+    /// Encodes the bundle state in the compact binary encoding.
+    ///
+    /// Contracts and the code of account infos are stored in a single bytecode table.
+    pub fn encode_compact(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(
+            self.contracts
+                .iter()
+                .chain(self.state.values().flat_map(|account| {
+                    [&account.info, &account.original_info]
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|info| Some((&info.code_hash, info.code.as_ref()?)))
+                }))
+                .chain(reverts_bytecodes(&self.reverts)),
+        );
+
+        let mut contracts: Vec<_> = self.contracts.keys().collect();
+        contracts.sort_unstable();
+        encoder.varint(contracts.len() as u64);
+        for code_hash in contracts {
+            encoder.varint(encoder.bytecodes[code_hash] as u64);
+        }
+
+        let mut state: Vec<_> = self.state.iter().collect();
+        state.sort_unstable_by_key(|(address, _)| *address);
+        encoder.varint(state.len() as u64);
+        for (address, account) in state {
+            encoder.bytes(address.as_slice());
+            encoder.status(account.status);
+            encoder.info(account.info.as_ref());
+            encoder.info(account.original_info.as_ref());
+            let mut storage: Vec<_> = account.storage.iter().collect();
+            storage.sort_unstable_by_key(|(slot, _)| *slot);
+            encoder.varint(storage.len() as u64);
+            for (slot, value) in storage {
+                encoder.u256(*slot);
+                encoder.u256(value.previous_or_original_value);
+                encoder.u256(value.present_value);
+            }
+        }
+
+        encoder.reverts(&self.reverts);
+        encoder.varint(self.state_size as u64);
+        encoder.varint(self.reverts_size as u64);
+        encoder.out
+    }
+
+    /// Decodes the bundle state from the compact binary encoding.
+    pub fn decode_compact(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut decoder = Decoder::new(bytes)?;
+
+        let contracts = (0..decoder.len()?)
+            .map(|_| {
+                let (code_hash, code) = decoder.bytecode()?;
+                Ok((*code_hash, code.clone()))
+            })
+            .collect::<Result<_, CodecError>>()?;
+
+        let state = (0..decoder.len()?)
+            .map(|_| {
+                let address = Address::from_slice(decoder.bytes(20)?);
+                let status = decoder.status()?;
+                let info = decoder.info()?;
+                let original_info = decoder.info()?;
+                let storage = (0..decoder.len()?)
+                    .map(|_| {
+                        let slot = decoder.u256()?;
+                        let value = StorageSlot::new_changed(decoder.u256()?, decoder.u256()?);
+                        Ok((slot, value))
+                    })
+                    .collect::<Result<_, CodecError>>()?;
+                let account = BundleAccount {
+                    info,
+                    original_info,
+                    storage,
+                    status,
+                };
+                Ok((address, account))
+            })
+            .collect::<Result<_, CodecError>>()?;
+
+        let reverts = decoder.reverts()?;
+        let state_size = decoder.varint()? as usize;
+        let reverts_size = decoder.varint()? as usize;
+        decoder.finish()?;
+        Ok(Self {
+            state,
+            contracts,
+            reverts,
+            state_size,
+            reverts_size,
+        })
+    }
+}
+
+impl Reverts {
+    /// Encodes the reverts in the compact binary encoding.
+    ///
+    /// Accounts of each transition are written sorted by address.
+    pub fn encode_compact(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new(reverts_bytecodes(self));
+        encoder.reverts(self);
+        encoder.out
+    }
+
+    /// Decodes the reverts from the compact binary encoding.
+    pub fn decode_compact(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut decoder = Decoder::new(bytes)?;
+        let reverts = decoder.reverts()?;
+        decoder.finish()?;
+        Ok(reverts)
+    }
+}
+
+/// Returns the code of all account infos in the reverts.
+fn reverts_bytecodes(reverts: &Reverts) -> impl Iterator<Item = (&B256, &Bytecode)> {
+    reverts
+        .iter()
+        .flatten()
+        .filter_map(|(_, revert)| match &revert.account {
+            AccountInfoRevert::RevertTo(info) => Some((&info.code_hash, info.code.as_ref()?)),
+            _ => None,
+        })
+}
+
+/// Tags of [Bytecode] variants.
+const BYTECODE_LEGACY: u8 = 0;
+const BYTECODE_EOF: u8 = 1;
+const BYTECODE_EIP7702: u8 = 2;
+
+/// Flags of an encoded [AccountInfo].
+const INFO_PRESENT: u8 = 1;
+const INFO_CODE: u8 = 1 << 1;
+const INFO_EMPTY_CODE_HASH: u8 = 1 << 2;
+
+/// Tags of [AccountInfoRevert] variants.
+const REVERT_DO_NOTHING: u8 = 0;
+const REVERT_DELETE_IT: u8 = 1;
+const REVERT_TO: u8 = 2;
+
+/// Tags of [RevertToSlot] variants.
+const SLOT_DESTROYED: u8 = 0;
+const SLOT_SOME: u8 = 1;
+
+struct Encoder {
+    out: Vec<u8>,
+    /// Index of bytecode in the bytecode table by code hash.
+    bytecodes: HashMap<B256, usize>,
+}
+
+impl Encoder {
+    /// Creates the encoder and writes the version and the bytecode table, sorted by code hash.
+    fn new<'a>(bytecodes: impl Iterator<Item = (&'a B256, &'a Bytecode)>) -> Self {
+        let mut table: Vec<_> = bytecodes.collect();
+        table.sort_unstable_by_key(|(code_hash, _)| *code_hash);
+        table.dedup_by_key(|(code_hash, _)| *code_hash);
+
+        let mut encoder = Self {
+            out: vec![CODEC_VERSION],
+            bytecodes: HashMap::default(),
+        };
+        encoder.varint(table.len() as u64);
+        for (index, (code_hash, code)) in table.into_iter().enumerate() {
+            encoder.bytecodes.insert(*code_hash, index);
+            encoder.bytes(code_hash.as_slice());
+            let (tag, raw) = match code {
+                Bytecode::LegacyAnalyzed(legacy) => (BYTECODE_LEGACY, legacy.original_bytes()),
+                Bytecode::Eof(eof) => (BYTECODE_EOF, eof.raw().clone()),
+                Bytecode::Eip7702(eip7702) => (BYTECODE_EIP7702, eip7702.raw().clone()),
+            };
+            encoder.out.push(tag);
+            encoder.varint(raw.len() as u64);
+            encoder.bytes(&raw);
+        }
+        encoder
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.out.extend_from_slice(bytes);
+    }
+
+    /// Writes LEB128 varint.
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.out.push(value as u8);
+    }
+
+    /// Writes number as its length followed by its big-endian bytes without leading zeros.
+    fn u256(&mut self, value: U256) {
+        let bytes = value.to_be_bytes::<32>();
+        let len = value.byte_len();
+        self.out.push(len as u8);
+        self.bytes(&bytes[32 - len..]);
+    }
+
+    fn status(&mut self, status: AccountStatus) {
+        self.out.push(match status {
+            AccountStatus::LoadedNotExisting => 0,
+            AccountStatus::Loaded => 1,
+            AccountStatus::LoadedEmptyEIP161 => 2,
+            AccountStatus::InMemoryChange => 3,
+            AccountStatus::Changed => 4,
+            AccountStatus::Destroyed => 5,
+            AccountStatus::DestroyedChanged => 6,
+            AccountStatus::DestroyedAgain => 7,
+        });
+    }
+
+    fn info(&mut self, info: Option<&AccountInfo>) {
+        let Some(info) = info else {
+            self.out.push(0);
+            return;
+        };
+        let mut flags = INFO_PRESENT;
+        if info.code.is_some() {
+            flags |= INFO_CODE;
+        } else if info.code_hash == KECCAK_EMPTY {
+            flags |= INFO_EMPTY_CODE_HASH;
+        }
+        self.out.push(flags);
+        self.u256(info.balance);
+        self.varint(info.nonce);
+        if info.code.is_some() {
+            self.varint(self.bytecodes[&info.code_hash] as u64);
+        } else if info.code_hash != KECCAK_EMPTY {
+            self.bytes(info.code_hash.as_slice());
+        }
+    }
+
+    fn reverts(&mut self, reverts: &Reverts) {
+        self.varint(reverts.len() as u64);
+        for transition in reverts.iter() {
+            let mut transition: Vec<_> = transition.iter().collect();
+            transition.sort_unstable_by_key(|(address, _)| *address);
+            self.varint(transition.len() as u64);
+            for (address, revert) in transition {
+                self.bytes(address.as_slice());
+                match &revert.account {
+                    AccountInfoRevert::DoNothing => self.out.push(REVERT_DO_NOTHING),
+                    AccountInfoRevert::DeleteIt => self.out.push(REVERT_DELETE_IT),
+                    AccountInfoRevert::RevertTo(info) => {
+                        self.out.push(REVERT_TO);
+                        self.info(Some(info));
+                    }
+                }
+                self.status(revert.previous_status);
+                self.out.push(revert.wipe_storage as u8);
+                let mut storage: Vec<_> = revert.storage.iter().collect();
+                storage.sort_unstable_by_key(|(slot, _)| *slot);
+                self.varint(storage.len() as u64);
+                for (slot, value) in storage {
+                    self.u256(*slot);
+                    match value {
+                        RevertToSlot::Destroyed => self.out.push(SLOT_DESTROYED),
+                        RevertToSlot::Some(value) => {
+                            self.out.push(SLOT_SOME);
+                            self.u256(*value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct Decoder<'a> {
+    input: &'a [u8],
+    bytecodes: Vec<(B256, Bytecode)>,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates the decoder, checking the version and reading the bytecode table.
+    fn new(input: &'a [u8]) -> Result<Self, CodecError> {
+        let mut decoder = Self {
+            input,
+            bytecodes: Vec::new(),
+        };
+        let version = decoder.u8()?;
+        if version != CODEC_VERSION {
+            return Err(CodecError::UnsupportedVersion(version));
+        }
+        for _ in 0..decoder.len()? {
+            let code_hash = B256::from_slice(decoder.bytes(32)?);
+            let tag = decoder.u8()?;
+            let len = decoder.len()?;
+            let raw = Bytes::copy_from_slice(decoder.bytes(len)?);
+            let code = match tag {
+                BYTECODE_LEGACY => Bytecode::new_legacy(raw),
+                BYTECODE_EOF | BYTECODE_EIP7702 => Bytecode::new_raw_checked(raw)?,
+                tag => return Err(CodecError::InvalidTag(tag)),
+            };
+            decoder.bytecodes.push((code_hash, code));
+        }
+        Ok(decoder)
+    }
+
+    fn finish(self) -> Result<(), CodecError> {
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(CodecError::TrailingBytes)
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if self.input.len() < len {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            let bits = (byte & 0x7f) as u64;
+            if bits << shift >> shift != bits {
+                return Err(CodecError::Overflow);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::Overflow)
+    }
+
+    /// Reads a length, which can not be larger than the remaining input.
+    fn len(&mut self) -> Result<usize, CodecError> {
+        let len = self.varint()?;
+        if len > self.input.len() as u64 {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        Ok(len as usize)
+    }
+
+    fn u256(&mut self) -> Result<U256, CodecError> {
+        let len = self.u8()? as usize;
+        if len > 32 {
+            return Err(CodecError::Overflow);
+        }
+        Ok(U256::from_be_slice(self.bytes(len)?))
+    }
+
+    fn bytecode(&mut self) -> Result<&(B256, Bytecode), CodecError> {
+        let index = self.varint()?;
+        self.bytecodes
+            .get(index as usize)
+            .ok_or(CodecError::MissingBytecode(index))
+    }
+
+    fn status(&mut self) -> Result<AccountStatus, CodecError> {
+        Ok(match self.u8()? {
+            0 => AccountStatus::LoadedNotExisting,
+            1 => AccountStatus::Loaded,
+            2 => AccountStatus::LoadedEmptyEIP161,
+            3 => AccountStatus::InMemoryChange,
+            4 => AccountStatus::Changed,
+            5 => AccountStatus::Destroyed,
+            6 => AccountStatus::DestroyedChanged,
+            7 => AccountStatus::DestroyedAgain,
+            tag => return Err(CodecError::InvalidTag(tag)),
+        })
+    }
+
+    fn info(&mut self) -> Result<Option<AccountInfo>, CodecError> {
+        let flags = self.u8()?;
+        if flags & !(INFO_PRESENT | INFO_CODE | INFO_EMPTY_CODE_HASH) != 0 {
+            return Err(CodecError::InvalidTag(flags));
+        }
+        if flags & INFO_PRESENT == 0 {
+            return Ok(None);
+        }
+        let balance = self.u256()?;
+        let nonce = self.varint()?;
+        let (code_hash, code) = if flags & INFO_CODE != 0 {
+            let (code_hash, code) = self.bytecode()?;
+            (*code_hash, Some(code.clone()))
+        } else if flags & INFO_EMPTY_CODE_HASH != 0 {
+            (KECCAK_EMPTY, None)
+        } else {
+            (B256::from_slice(self.bytes(32)?), None)
+        };
+        Ok(Some(AccountInfo {
+            balance,
+            nonce,
+            code_hash,
+            code,
+        }))
+    }
+
+    fn reverts(&mut self) -> Result<Reverts, CodecError> {
+        let reverts = (0..self.len()?)
+            .map(|_| {
+                (0..self.len()?)
+                    .map(|_| {
+                        let address = Address::from_slice(self.bytes(20)?);
+                        let account = match self.u8()? {
+                            REVERT_DO_NOTHING => AccountInfoRevert::DoNothing,
+                            REVERT_DELETE_IT => AccountInfoRevert::DeleteIt,
+                            REVERT_TO => AccountInfoRevert::RevertTo(
+                                self.info()?.ok_or(CodecError::InvalidTag(0))?,
+                            ),
+                            tag => return Err(CodecError::InvalidTag(tag)),
+                        };
+                        let previous_status = self.status()?;
+                        let wipe_storage = match self.u8()? {
+                            0 => false,
+                            1 => true,
+                            tag => return Err(CodecError::InvalidTag(tag)),
+                        };
+                        let storage = (0..self.len()?)
+                            .map(|_| {
+                                let slot = self.u256()?;
+                                let value = match self.u8()? {
+                                    SLOT_DESTROYED => RevertToSlot::Destroyed,
+                                    SLOT_SOME => RevertToSlot::Some(self.u256()?),
+                                    tag => return Err(CodecError::InvalidTag(tag)),
+                                };
+                                Ok((slot, value))
+                            })
+                            .collect::<Result<_, CodecError>>()?;
+                        let revert = AccountRevert {
+                            account,
+                            storage,
+                            previous_status,
+                            wipe_storage,
+                        };
+                        Ok((address, revert))
+                    })
+                    .collect::<Result<Vec<_>, CodecError>>()
+            })
+            .collect::<Result<Vec<_>, CodecError>>()?;
+        Ok(Reverts::new(reverts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection, prelude::*};
+
+    fn info() -> impl Strategy<Value = AccountInfo> {
+        (
+            any::<u128>(),
+            any::<u64>(),
+            prop_oneof![
+                Just(None),
+                Just(Some(Bytes::new())),
+                collection::vec(any::<u8>(), 1..8).prop_map(|code| Some(Bytes::from(code))),
+                Just(Some(Bytes::from_static(&[0xef, 0x01, 0x00, 0x12]))),
+            ],
+            any::<[u8; 32]>(),
+        )
+            .prop_map(|(balance, nonce, code, code_hash)| {
+                let balance = U256::from(balance);
+                match code {
+                    // Code is not loaded.
+                    None => {
+                        AccountInfo::new(balance, nonce, B256::from(code_hash), Bytecode::new())
+                            .without_code()
+                    }
+                    Some(code) => {
+                        let code = Bytecode::new_legacy(code);
+                        AccountInfo::new(balance, nonce, code.hash_slow(), code)
+                    }
+                }
+            })
+    }
+
+    fn status() -> impl Strategy<Value = AccountStatus> {
+        prop_oneof![
+            Just(AccountStatus::LoadedNotExisting),
+            Just(AccountStatus::Loaded),
+            Just(AccountStatus::LoadedEmptyEIP161),
+            Just(AccountStatus::InMemoryChange),
+            Just(AccountStatus::Changed),
+            Just(AccountStatus::Destroyed),
+            Just(AccountStatus::DestroyedChanged),
+            Just(AccountStatus::DestroyedAgain),
+        ]
+    }
+
+    fn u256() -> impl Strategy<Value = U256> {
+        prop_oneof![
+            Just(U256::ZERO),
+            any::<u64>().prop_map(U256::from),
+            any::<[u8; 32]>().prop_map(U256::from_be_bytes),
+        ]
+    }
+
+    fn reverts() -> impl Strategy<Value = Reverts> {
+        let revert = (
+            prop_oneof![
+                Just(AccountInfoRevert::DoNothing),
+                Just(AccountInfoRevert::DeleteIt),
+                info().prop_map(AccountInfoRevert::RevertTo),
+            ],
+            collection::hash_map(
+                u256(),
+                prop_oneof![
+                    Just(RevertToSlot::Destroyed),
+                    u256().prop_map(RevertToSlot::Some)
+                ],
+                0..4,
+            ),
+            status(),
+            any::<bool>(),
+        )
+            .prop_map(
+                |(account, storage, previous_status, wipe_storage)| AccountRevert {
+                    account,
+                    storage: storage.into_iter().collect(),
+                    previous_status,
+                    wipe_storage,
+                },
+            );
+        collection::vec(
+            collection::hash_map(any::<[u8; 20]>().prop_map(Address::from), revert, 0..4),
+            0..3,
+        )
+        .prop_map(|reverts| {
+            Reverts::new(
+                reverts
+                    .into_iter()
+                    .map(|r| r.into_iter().collect())
+                    .collect(),
+            )
+        })
+    }
+
+    fn bundle_state() -> impl Strategy<Value = BundleState> {
+        let account = (
+            proptest::option::of(info()),
+            proptest::option::of(info()),
+            collection::hash_map(u256(), (u256(), u256()), 0..4),
+            status(),
+        )
+            .prop_map(|(info, original_info, storage, status)| BundleAccount {
+                info,
+                original_info,
+                storage: storage
+                    .into_iter()
+                    .map(|(slot, (original, present))| {
+                        (slot, StorageSlot::new_changed(original, present))
+                    })
+                    .collect(),
+                status,
+            });
+        (
+            collection::hash_map(any::<[u8; 20]>().prop_map(Address::from), account, 0..4),
+            collection::vec(collection::vec(any::<u8>(), 0..8), 0..3),
+            reverts(),
+            any::<u32>(),
+            any::<u32>(),
+        )
+            .prop_map(|(state, contracts, reverts, state_size, reverts_size)| {
+                BundleState {
+                    state: state.into_iter().collect(),
+                    contracts: contracts
+                        .into_iter()
+                        .map(|code| {
+                            let code = Bytecode::new_legacy(code.into());
+                            (code.hash_slow(), code)
+                        })
+                        .collect(),
+                    reverts,
+                    state_size: state_size as usize,
+                    reverts_size: reverts_size as usize,
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn bundle_state_roundtrip(bundle in bundle_state()) {
+            let encoded = bundle.encode_compact();
+            let decoded = BundleState::decode_compact(&encoded).unwrap();
+            prop_assert_eq!(&decoded, &bundle);
+            // Code presence is not compared by `AccountInfo` equality.
+            for (address, account) in &bundle.state {
+                let decoded = &decoded.state[address];
+                prop_assert_eq!(
+                    decoded.info.as_ref().map(|info| &info.code),
+                    account.info.as_ref().map(|info| &info.code)
+                );
+            }
+            prop_assert_eq!(decoded.encode_compact(), encoded);
+        }
+
+        #[test]
+        fn reverts_roundtrip(reverts in reverts()) {
+            let encoded = reverts.encode_compact();
+            let decoded = Reverts::decode_compact(&encoded).unwrap();
+            prop_assert_eq!(&decoded, &reverts);
+            prop_assert_eq!(decoded.encode_compact(), encoded);
+        }
+
+        #[test]
+        fn truncated_input_is_rejected(bundle in bundle_state()) {
+            let encoded = bundle.encode_compact();
+            for len in 0..encoded.len() {
+                prop_assert!(BundleState::decode_compact(&encoded[..len]).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut encoded = BundleState::default().encode_compact();
+        assert_eq!(encoded, [CODEC_VERSION, 0, 0, 0, 0, 0, 0]);
+        encoded.push(0);
+        assert_eq!(
+            BundleState::decode_compact(&encoded),
+            Err(CodecError::TrailingBytes)
+        );
+        assert_eq!(
+            BundleState::decode_compact(&[2]),
+            Err(CodecError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Reverts::decode_compact(&[CODEC_VERSION, 0, 1, 1, 0xff]),
+            Err(CodecError::UnexpectedEnd)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn smaller_than_bincode() {
+        let code = Bytecode::new_legacy(Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]));
+        let mut builder = BundleState::builder(0..=0).contract(code.hash_slow(), code.clone());
+        for i in 0..100u64 {
+            let address = Address::with_last_byte(i as u8);
+            let info = AccountInfo::new(U256::from(i * 1_000), i, code.hash_slow(), code.clone());
+            builder = builder
+                .state_present_account_info(address, info.clone())
+                .state_original_account_info(address, info)
+                .state_storage(
+                    address,
+                    (0..10)
+                        .map(|slot| (U256::from(slot), (U256::ZERO, U256::from(i + slot))))
+                        .collect(),
+                )
+                .revert_account_info(0, address, Some(None))
+                .revert_storage(
+                    0,
+                    address,
+                    (0..10).map(|slot| (U256::from(slot), U256::ZERO)).collect(),
+                );
+        }
+        let bundle = builder.build();
+
+        let compact = bundle.encode_compact().len();
+        let bincode = bincode::serialize(&bundle).unwrap().len();
+        assert!(
+            compact * 4 < bincode,
+            "compact {compact} bytes, bincode {bincode} bytes"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Worker thread merging transitions into a bundle.
+use super::{bundle_state::BundleRetention, BundleState, TransitionState};
+use std::{
+    sync::mpsc,
+    thread::{self, JoinHandle},
+};
+
+/// Owns a [BundleState] on a worker thread and merges transitions into it in the order they
+/// are sent, so the result is identical to merging them on the calling thread.
+#[derive(Debug)]
+pub(crate) struct MergeWorker {
+    sender: mpsc::Sender<(TransitionState, BundleRetention)>,
+    handle: JoinHandle<BundleState>,
+}
+
+impl MergeWorker {
+    /// Spawns the worker thread that takes ownership of the bundle.
+    pub(crate) fn spawn(mut bundle: BundleState) -> Self {
+        let (sender, receiver) = mpsc::channel::<(TransitionState, BundleRetention)>();
+        let handle = thread::Builder::new()
+            .name("revm-transition-merge".into())
+            .spawn(move || {
+                for (transitions, retention) in receiver {
+                    bundle.apply_transitions_and_create_reverts(transitions, retention);
+                }
+                bundle
+            })
+            .expect("failed to spawn transition merge thread");
+        Self { sender, handle }
+    }
+
+    /// Queues transitions to be merged.
+    pub(crate) fn merge(&self, transitions: TransitionState, retention: BundleRetention) {
+        self.sender
+            .send((transitions, retention))
+            .expect("transition merge thread panicked");
+    }
+
+    /// Waits for all queued merges and returns the bundle.
+    pub(crate) fn join(self) -> BundleState {
+        drop(self.sender);
+        match self.handle.join() {
+            Ok(bundle) => bundle,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    changes::PlainStorageRevert, AccountStatus, BundleAccount, PlainStateReverts,
-    StorageWithOriginalValues,
+    changes::{PlainAccountRevert, PlainStorageRevert},
+    AccountStatus, BundleAccount, PlainStateReverts, StorageWithOriginalValues,
 };
 use core::{
     cmp::Ordering,
@@ -84,6 +84,53 @@ impl Reverts {
         state_reverts
     }
 
+    /// Generates a sorted [`PlainStateReverts`].
+    ///
+    /// Accounts and storage of every transition are sorted by address and storage reverts of
+    /// every account by slot key.
+    pub fn to_sorted_plain_state_reverts(&self) -> PlainStateReverts {
+        let mut state_reverts = PlainStateReverts::with_capacity(self.0.len());
+        for transition in self.sorted_plain_state_reverts_iter() {
+            state_reverts.push_transition(transition);
+        }
+        state_reverts
+    }
+
+    /// Returns an iterator over transitions, each yielding its account reverts sorted by
+    /// address with storage reverts sorted by slot key.
+    ///
+    /// A transition is sorted only when it is reached.
+    pub fn sorted_plain_state_reverts_iter(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = PlainAccountRevert> + '_> + '_ {
+        self.0.iter().map(|transition| {
+            let mut reverts: Vec<_> = transition.iter().collect();
+            reverts.sort_unstable_by_key(|(address, _)| *address);
+            reverts.into_iter().map(|(address, revert)| {
+                let info = match &revert.account {
+                    AccountInfoRevert::RevertTo(info) => Some(Some(info.clone())),
+                    AccountInfoRevert::DeleteIt => Some(None),
+                    AccountInfoRevert::DoNothing => None,
+                };
+                let storage = (revert.wipe_storage || !revert.storage.is_empty()).then(|| {
+                    let mut storage_revert: Vec<_> =
+                        revert.storage.iter().map(|(k, v)| (*k, *v)).collect();
+                    storage_revert.sort_unstable_by_key(|(key, _)| *key);
+                    PlainStorageRevert {
+                        address: *address,
+                        wiped: revert.wipe_storage,
+                        storage_revert,
+                    }
+                });
+                PlainAccountRevert {
+                    address: *address,
+                    info,
+                    storage,
+                }
+            })
+        })
+    }
+
     /// Compare two Reverts instances, ignoring the order of elements
     pub fn content_eq(&self, other: &Self) -> bool {
         if self.0.len() != other.0.len() {
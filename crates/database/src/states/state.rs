@@ -1,9 +1,15 @@
 use super::{
-    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    bundle_state::{BundleRetention, BundleStateIndex},
+    cache::CacheState,
+    plain_account::PlainStorage,
+    AccountStatus, BundleState, CacheAccount, PlainAccount, StateBuilder, StorageSlot,
+    TransitionAccount, TransitionState,
 };
 use bytecode::Bytecode;
-use database_interface::{Database, DatabaseCommit, EmptyDB};
+use core::cell::{OnceCell, RefCell};
+use database_interface::{
+    overlay_range, Database, DatabaseCommit, DatabaseIter, EmptyDB, SortedOverlay,
+};
 use primitives::{hash_map, Address, HashMap, B256, BLOCK_HASH_HISTORY, U256};
 use state::{Account, AccountInfo};
 use std::{
@@ -49,6 +55,8 @@ pub struct State<DB> {
     /// Bundle is used to update database and create changesets.
     ///
     /// Bundle state can be set on initialization if we want to use preloaded bundle.
+    ///
+    /// Empty while background merges are pending, see [State::background_transition_merge].
     pub bundle_state: BundleState,
     /// Addition layer that is going to be used to fetched values before fetching values
     /// from database
@@ -63,6 +71,116 @@ pub struct State<DB> {
     ///
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// If true, [State::merge_transitions] merges on a worker thread.
+    ///
+    /// While merges are pending the worker owns the bundle and [State::bundle_state] is empty.
+    /// Call [State::wait_for_background_merge] or [State::take_bundle] to get it back, and
+    /// [State::is_merging_in_background] to check whether merges are pending.
+    ///
+    /// Ignored when the preloaded bundle is used, as it is read during execution.
+    pub background_transition_merge: bool,
+    /// Worker thread owning the bundle while background merges are pending.
+    #[cfg(feature = "std")]
+    pub(crate) merge_worker: Option<super::merge_worker::MergeWorker>,
+    /// Undo log of changes made since the oldest snapshot, see [State::snapshot].
+    pub(crate) snapshots: StateSnapshots,
+}
+
+/// Undo log backing [State::snapshot] and [State::revert_to].
+#[derive(Debug, Default)]
+pub(crate) struct StateSnapshots {
+    /// Id and undo log length of each snapshot, ordered by id.
+    checkpoints: Vec<(usize, usize)>,
+    /// Changes made since the oldest snapshot, with values before the change.
+    journal: Vec<StateUndo>,
+    /// Id of the next snapshot.
+    next_id: usize,
+}
+
+impl StateSnapshots {
+    /// Returns the index of the snapshot `id` in the checkpoints.
+    fn position(&self, id: usize) -> Option<usize> {
+        self.checkpoints
+            .binary_search_by_key(&id, |&(id, _)| id)
+            .ok()
+    }
+}
+
+/// Change recorded in the [StateSnapshots] undo log.
+#[derive(Debug)]
+enum StateUndo {
+    /// Cache account changed. `None` if the account was not cached.
+    Account(Address, Option<CacheAccountUndo>),
+    /// Account transition changed. `None` if the account had no transition.
+    Transition(Address, Option<TransitionUndo>),
+}
+
+/// Cache account before a change, without the storage slots that the change kept.
+#[derive(Debug)]
+struct CacheAccountUndo {
+    status: AccountStatus,
+    /// `None` if the account did not exist.
+    info: Option<AccountInfo>,
+    storage: StorageUndo<U256>,
+}
+
+/// Account transition before a change, without the storage slots that the change kept.
+#[derive(Debug)]
+struct TransitionUndo {
+    info: Option<AccountInfo>,
+    status: AccountStatus,
+    previous_info: Option<AccountInfo>,
+    previous_status: AccountStatus,
+    storage_was_destroyed: bool,
+    storage: StorageUndo<StorageSlot>,
+}
+
+/// Storage before a change.
+#[derive(Debug)]
+enum StorageUndo<T> {
+    /// Values of the changed slots, `None` if the slot was not set.
+    Slots(Vec<(U256, Option<T>)>),
+    /// Whole storage, for changes that replace it.
+    Whole(HashMap<U256, T>),
+}
+
+impl<T: Clone> StorageUndo<T> {
+    /// Records the slots of the storage before they are changed.
+    fn new(
+        storage: Option<&HashMap<U256, T>>,
+        slots: impl IntoIterator<Item = U256>,
+        replaced: bool,
+    ) -> Self {
+        if replaced {
+            return Self::Whole(storage.cloned().unwrap_or_default());
+        }
+        Self::Slots(
+            slots
+                .into_iter()
+                .map(|slot| {
+                    (
+                        slot,
+                        storage.and_then(|storage| storage.get(&slot).cloned()),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Restores the recorded slots.
+    fn revert(self, storage: &mut HashMap<U256, T>) {
+        match self {
+            Self::Slots(slots) => {
+                for (slot, previous) in slots {
+                    match previous {
+                        Some(value) => storage.insert(slot, value),
+                        None => storage.remove(&slot),
+                    };
+                }
+            }
+            Self::Whole(previous) => *storage = previous,
+        }
+    }
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -76,6 +194,9 @@ impl State<EmptyDB> {
 impl<DB: Database> State<DB> {
     /// Returns the size hint for the inner bundle state.
     ///
+    /// Does not include transitions that are still being merged in the background, call
+    /// [State::wait_for_background_merge] first to include them.
+    ///
     /// See [BundleState::size_hint] for more info.
     pub fn bundle_size_hint(&self) -> usize {
         self.bundle_state.size_hint()
@@ -101,6 +222,7 @@ impl<DB: Database> State<DB> {
             if balance == 0 {
                 continue;
             }
+            self.journal_account(address, [], false);
             let original_account = self.load_cache_account(address)?;
             transitions.push((
                 address,
@@ -110,9 +232,7 @@ impl<DB: Database> State<DB> {
             ))
         }
         // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(())
     }
 
@@ -127,15 +247,14 @@ impl<DB: Database> State<DB> {
         let mut transitions = Vec::new();
         let mut balances = Vec::new();
         for address in addresses {
+            self.journal_account(address, [], false);
             let original_account = self.load_cache_account(address)?;
             let (balance, transition) = original_account.drain_balance();
             balances.push(balance);
             transitions.push((address, transition))
         }
         // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(balances)
     }
 
@@ -145,10 +264,12 @@ impl<DB: Database> State<DB> {
     }
 
     pub fn insert_not_existing(&mut self, address: Address) {
+        self.journal_account(address, [], true);
         self.cache.insert_not_existing(address)
     }
 
     pub fn insert_account(&mut self, address: Address, info: AccountInfo) {
+        self.journal_account(address, [], true);
         self.cache.insert_account(address, info)
     }
 
@@ -158,12 +279,18 @@ impl<DB: Database> State<DB> {
         info: AccountInfo,
         storage: PlainStorage,
     ) {
+        self.journal_account(address, [], true);
         self.cache
             .insert_account_with_storage(address, info, storage)
     }
 
     /// Applies evm transitions to transition state.
     pub fn apply_transition(&mut self, transitions: Vec<(Address, TransitionAccount)>) {
+        if !self.snapshots.checkpoints.is_empty() {
+            for (address, transition) in &transitions {
+                self.journal_transition(*address, transition);
+            }
+        }
         // Add transition to transition state.
         if let Some(s) = self.transition_state.as_mut() {
             s.add_transitions(transitions)
@@ -175,11 +302,52 @@ impl<DB: Database> State<DB> {
     /// This action will create final post state and all reverts so that
     /// we at any time revert state of bundle to the state before transition
     /// is applied.
-    pub fn merge_transitions(&mut self, retention: BundleRetention) {
-        if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
-            self.bundle_state
-                .apply_transitions_and_create_reverts(transition_state, retention);
+    ///
+    /// Bundle state changes are not recorded, so this removes all snapshots. Returns the number
+    /// of removed snapshots, release or revert them first to avoid losing them unnoticed.
+    pub fn merge_transitions(&mut self, retention: BundleRetention) -> usize {
+        let dropped = self.snapshots.checkpoints.len();
+        self.snapshots.checkpoints.clear();
+        self.snapshots.journal = Vec::new();
+        let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take)
+        else {
+            return dropped;
+        };
+        #[cfg(feature = "std")]
+        if self.background_transition_merge && !self.use_preloaded_bundle {
+            let bundle_state = &mut self.bundle_state;
+            self.merge_worker
+                .get_or_insert_with(|| {
+                    super::merge_worker::MergeWorker::spawn(core::mem::take(bundle_state))
+                })
+                .merge(transition_state, retention);
+            return dropped;
         }
+        // Merges queued before background merging was disabled go first.
+        self.wait_for_background_merge();
+        self.bundle_state
+            .apply_transitions_and_create_reverts(transition_state, retention);
+        dropped
+    }
+
+    /// Waits for transitions merged in the background and puts the bundle back into
+    /// [State::bundle_state].
+    ///
+    /// Does nothing if there are no pending background merges.
+    pub fn wait_for_background_merge(&mut self) {
+        #[cfg(feature = "std")]
+        if let Some(worker) = self.merge_worker.take() {
+            self.bundle_state = worker.join();
+        }
+    }
+
+    /// Returns `true` if transitions are being merged in the background and
+    /// [State::bundle_state] does not contain them yet.
+    pub fn is_merging_in_background(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.merge_worker.is_some();
+        #[cfg(not(feature = "std"))]
+        false
     }
 
     /// Get a mutable reference to the [`CacheAccount`] for the given address.
@@ -212,6 +380,150 @@ impl<DB: Database> State<DB> {
         }
     }
 
+    /// Takes a snapshot of the cache and transition state and returns its id.
+    ///
+    /// Ids increase with every snapshot and are never reused, also after the snapshot is reverted
+    /// or released.
+    ///
+    /// Snapshots are cheap: the account info and storage slots changed after the oldest snapshot
+    /// are recorded in an undo log instead of copying the state. Only changes made through
+    /// [State] methods and [DatabaseCommit::commit] are recorded. Snapshots are removed by
+    /// [State::release_snapshot] and [State::merge_transitions].
+    pub fn snapshot(&mut self) -> usize {
+        let id = self.snapshots.next_id;
+        self.snapshots.next_id += 1;
+        self.snapshots
+            .checkpoints
+            .push((id, self.snapshots.journal.len()));
+        id
+    }
+
+    /// Reverts all changes made since the snapshot was taken.
+    ///
+    /// The snapshot and all snapshots taken after it are removed. Returns `false` if the snapshot
+    /// does not exist.
+    pub fn revert_to(&mut self, id: usize) -> bool {
+        let Some(index) = self.snapshots.position(id) else {
+            return false;
+        };
+        let (_, journal_len) = self.snapshots.checkpoints[index];
+        self.snapshots.checkpoints.truncate(index);
+        for undo in self.snapshots.journal.drain(journal_len..).rev() {
+            match undo {
+                StateUndo::Account(address, Some(previous)) => {
+                    let Some(account) = self.cache.accounts.get_mut(&address) else {
+                        continue;
+                    };
+                    account.status = previous.status;
+                    let Some(info) = previous.info else {
+                        account.account = None;
+                        continue;
+                    };
+                    let account = account.account.get_or_insert_with(|| {
+                        PlainAccount::new_empty_with_storage(HashMap::default())
+                    });
+                    account.info = info;
+                    previous.storage.revert(&mut account.storage);
+                }
+                StateUndo::Account(address, None) => {
+                    self.cache.accounts.remove(&address);
+                }
+                StateUndo::Transition(address, previous) => {
+                    let Some(transition_state) = self.transition_state.as_mut() else {
+                        continue;
+                    };
+                    let Some(previous) = previous else {
+                        transition_state.transitions.remove(&address);
+                        continue;
+                    };
+                    let Some(transition) = transition_state.transitions.get_mut(&address) else {
+                        continue;
+                    };
+                    transition.info = previous.info;
+                    transition.status = previous.status;
+                    transition.previous_info = previous.previous_info;
+                    transition.previous_status = previous.previous_status;
+                    transition.storage_was_destroyed = previous.storage_was_destroyed;
+                    previous.storage.revert(&mut transition.storage);
+                }
+            }
+        }
+        true
+    }
+
+    /// Removes the snapshot and all snapshots taken after it, keeping the changes made since.
+    ///
+    /// The undo log is dropped once no snapshot is left, so release snapshots that are no longer
+    /// needed to keep the log from growing. Returns `false` if the snapshot does not exist.
+    pub fn release_snapshot(&mut self, id: usize) -> bool {
+        let Some(index) = self.snapshots.position(id) else {
+            return false;
+        };
+        self.snapshots.checkpoints.truncate(index);
+        if self.snapshots.checkpoints.is_empty() {
+            self.snapshots.journal = Vec::new();
+        }
+        true
+    }
+
+    /// Records the cache account in the undo log if there is a snapshot.
+    ///
+    /// Only the account info and the given `slots` are recorded, unless the change replaces the
+    /// whole storage.
+    fn journal_account(
+        &mut self,
+        address: Address,
+        slots: impl IntoIterator<Item = U256>,
+        replaces_storage: bool,
+    ) {
+        if self.snapshots.checkpoints.is_empty() {
+            return;
+        }
+        let previous = self
+            .cache
+            .accounts
+            .get(&address)
+            .map(|account| CacheAccountUndo {
+                status: account.status,
+                info: account.account.as_ref().map(|account| account.info.clone()),
+                storage: StorageUndo::new(
+                    account.account.as_ref().map(|account| &account.storage),
+                    slots,
+                    replaces_storage,
+                ),
+            });
+        self.snapshots
+            .journal
+            .push(StateUndo::Account(address, previous));
+    }
+
+    /// Records the account transition that is updated by `transition` in the undo log.
+    fn journal_transition(&mut self, address: Address, transition: &TransitionAccount) {
+        let previous = self
+            .transition_state
+            .as_ref()
+            .and_then(|state| state.transitions.get(&address))
+            .map(|previous| TransitionUndo {
+                info: previous.info.clone(),
+                status: previous.status,
+                previous_info: previous.previous_info.clone(),
+                previous_status: previous.previous_status,
+                storage_was_destroyed: previous.storage_was_destroyed,
+                storage: StorageUndo::new(
+                    Some(&previous.storage),
+                    transition.storage.keys().copied(),
+                    // Same as `TransitionAccount::update`.
+                    matches!(
+                        transition.status,
+                        AccountStatus::Destroyed | AccountStatus::DestroyedAgain
+                    ),
+                ),
+            });
+        self.snapshots
+            .journal
+            .push(StateUndo::Transition(address, previous));
+    }
+
     // TODO : Make cache aware of transitions dropping by having global transition counter.
     /// Takess the [`BundleState`] changeset from the [`State`], replacing it
     /// with an empty one.
@@ -220,10 +532,13 @@ impl<DB: Database> State<DB> {
     ///
     /// It is recommended to call [`State::merge_transitions`] before taking the bundle.
     ///
+    /// Waits for transitions that are being merged in the background.
+    ///
     /// If the `State` has been built with the
     /// [`StateBuilder::with_bundle_prestate`] option, the pre-state will be
     /// taken along with any changes made by [`State::merge_transitions`].
     pub fn take_bundle(&mut self) -> BundleState {
+        self.wait_for_background_merge();
         core::mem::take(&mut self.bundle_state)
     }
 }
@@ -306,8 +621,151 @@ impl<DB: Database> Database for State<DB> {
     }
 }
 
+/// Pages through a fresh [StateIndex], use [State::iter_index] to sort the cache only once when
+/// reading multiple pages.
+impl<DB: DatabaseIter> DatabaseIter for State<DB> {
+    type Error = DB::Error;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        self.iter_index().accounts_range(start, limit)
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        self.iter_index().storage_range(address, start, limit)
+    }
+}
+
+impl<DB> State<DB> {
+    /// Returns an index that iterates the cache on top of the preloaded bundle (if used) on top
+    /// of the database.
+    ///
+    /// Cached accounts and storage are sorted on first use, after that a page costs
+    /// `O(limit + log N)`.
+    pub fn iter_index(&self) -> StateIndex<'_, DB> {
+        StateIndex {
+            state: self,
+            // Bundle is merged in the background only if it is not preloaded.
+            bundle: self
+                .use_preloaded_bundle
+                .then(|| self.bundle_state.iter_index()),
+            accounts: OnceCell::new(),
+            storage: RefCell::new(HashMap::default()),
+        }
+    }
+}
+
+/// Sorted index of a borrowed [State], see [State::iter_index].
+#[derive(Debug)]
+pub struct StateIndex<'a, DB> {
+    state: &'a State<DB>,
+    bundle: Option<BundleStateIndex<'a>>,
+    accounts: OnceCell<SortedOverlay<Address, AccountInfo>>,
+    storage: RefCell<HashMap<Address, SortedOverlay<U256, U256>>>,
+}
+
+impl<DB: DatabaseIter> DatabaseIter for StateIndex<'_, DB> {
+    type Error = DB::Error;
+
+    fn accounts_range(
+        &self,
+        start: Address,
+        limit: usize,
+    ) -> Result<Vec<(Address, AccountInfo)>, Self::Error> {
+        let accounts = self.accounts.get_or_init(|| {
+            SortedOverlay::new(
+                self.state
+                    .cache
+                    .accounts
+                    .iter()
+                    .map(|(address, account)| (*address, account.account_info())),
+            )
+        });
+        let database = &self.state.database;
+        overlay_range(
+            start,
+            limit,
+            accounts
+                .range(start)
+                .map(|(address, info)| (address, info.cloned())),
+            |limit| match &self.bundle {
+                Some(bundle) => bundle.accounts_range_over(start, limit, |limit| {
+                    database.accounts_range(start, limit)
+                }),
+                None => database.accounts_range(start, limit),
+            },
+        )
+    }
+
+    fn storage_range(
+        &self,
+        address: Address,
+        start: U256,
+        limit: usize,
+    ) -> Result<Vec<(U256, U256)>, Self::Error> {
+        let database = &self.state.database;
+        let inner = |limit| match &self.bundle {
+            Some(bundle) => bundle.storage_range_over(address, start, limit, |limit| {
+                database.storage_range(address, start, limit)
+            }),
+            None => database.storage_range(address, start, limit),
+        };
+        let Some(account) = self.state.cache.accounts.get(&address) else {
+            return inner(limit);
+        };
+        let mut storage = self.storage.borrow_mut();
+        let overlay = storage.entry(address).or_insert_with(|| {
+            SortedOverlay::new(
+                account
+                    .account
+                    .iter()
+                    .flat_map(|account| account.storage.iter())
+                    .map(|(slot, value)| (*slot, (!value.is_zero()).then_some(*value))),
+            )
+        });
+        overlay_range(
+            start,
+            limit,
+            overlay
+                .range(start)
+                .map(|(slot, value)| (slot, value.copied())),
+            |limit| {
+                if account.status.is_storage_known() {
+                    Ok(Vec::new())
+                } else {
+                    inner(limit)
+                }
+            },
+        )
+    }
+}
+
 impl<DB: Database> DatabaseCommit for State<DB> {
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
+        if !self.snapshots.checkpoints.is_empty() {
+            for (address, account) in &evm_state {
+                if !account.is_touched() {
+                    continue;
+                }
+                // Selfdestructed, created and touched empty accounts get new storage.
+                let replaces_storage =
+                    account.is_selfdestructed() || account.is_created() || account.is_empty();
+                let slots = account
+                    .storage
+                    .iter()
+                    .filter(|(_, slot)| slot.is_changed())
+                    .map(|(slot, _)| *slot);
+                self.journal_account(*address, slots, replaces_storage);
+            }
+        }
         let transitions = self.cache.apply_evm_state(evm_state);
         self.apply_transition(transitions);
     }
@@ -805,4 +1263,223 @@ mod tests {
             )])])
         )
     }
+
+    #[test]
+    fn snapshot_revert() {
+        let mut state = State::builder().with_bundle_update().build();
+        let address = Address::with_last_byte(1);
+        let slot = U256::from(1);
+        state.insert_account_with_storage(
+            address,
+            AccountInfo::from_balance(U256::from(1)),
+            HashMap::from_iter([(slot, U256::from(2))]),
+        );
+
+        let first = state.snapshot();
+        let mut account = Account::from(state.basic(address).unwrap().unwrap());
+        account.info.nonce = 1;
+        account.mark_touch();
+        account.storage.insert(
+            slot,
+            state::EvmStorageSlot::new_changed(U256::from(2), U256::from(3)),
+        );
+        state.commit(HashMap::from_iter([(address, account)]));
+
+        let second = state.snapshot();
+        state
+            .increment_balances([(address, 1), (Address::with_last_byte(2), 1)])
+            .unwrap();
+        assert_eq!(
+            state.basic(address).unwrap().unwrap().balance,
+            U256::from(2)
+        );
+
+        assert!(state.revert_to(second));
+        let info = state.basic(address).unwrap().unwrap();
+        assert_eq!((info.nonce, info.balance), (1, U256::from(1)));
+        assert_eq!(state.storage(address, slot).unwrap(), U256::from(3));
+        assert!(!state
+            .cache
+            .accounts
+            .contains_key(&Address::with_last_byte(2)));
+
+        assert!(state.revert_to(first));
+        assert_eq!(state.basic(address).unwrap().unwrap().nonce, 0);
+        assert_eq!(state.storage(address, slot).unwrap(), U256::from(2));
+        assert!(state.transition_state.unwrap().transitions.is_empty());
+    }
+
+    #[test]
+    fn snapshot_journal_changes() {
+        let mut state = State::builder().with_bundle_update().build();
+        let address = Address::with_last_byte(1);
+        let storage = (0..100u64).map(|slot| (U256::from(slot), U256::from(1)));
+        state.insert_account_with_storage(
+            address,
+            AccountInfo::from_balance(U256::from(1)),
+            storage.collect(),
+        );
+
+        let first = state.snapshot();
+        let mut account = Account::from(state.basic(address).unwrap().unwrap());
+        account.mark_touch();
+        account.storage.insert(
+            U256::ZERO,
+            state::EvmStorageSlot::new_changed(U256::from(1), U256::from(2)),
+        );
+        state.commit(HashMap::from_iter([(address, account)]));
+
+        // Only the changed slot is recorded, and the transition only once.
+        assert_eq!(state.snapshots.journal.len(), 2);
+        let StateUndo::Account(_, Some(undo)) = &state.snapshots.journal[0] else {
+            panic!("expected account undo");
+        };
+        assert!(matches!(&undo.storage, StorageUndo::Slots(slots) if slots.len() == 1));
+
+        let second = state.snapshot();
+        state.increment_balances([(address, 1)]).unwrap();
+        assert!(state.release_snapshot(second));
+        assert_eq!(state.snapshots.journal.len(), 4);
+        assert!(!state.revert_to(second));
+
+        assert!(state.release_snapshot(first));
+        assert!(state.snapshots.journal.is_empty());
+        assert!(!state.revert_to(first));
+        assert_eq!(state.storage(address, U256::ZERO).unwrap(), U256::from(2));
+        assert_eq!(
+            state.basic(address).unwrap().unwrap().balance,
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn snapshot_ids_and_merge() {
+        let mut state = State::builder().with_bundle_update().build();
+        let address = Address::with_last_byte(1);
+        assert_eq!(state.merge_transitions(BundleRetention::Reverts), 0);
+
+        let first = state.snapshot();
+        let second = state.snapshot();
+        assert!(state.revert_to(second));
+        // Ids of reverted snapshots are not reused.
+        let third = state.snapshot();
+        assert!(third > second);
+        assert!(!state.revert_to(second));
+
+        state.increment_balances([(address, 1)]).unwrap();
+        assert_eq!(state.merge_transitions(BundleRetention::Reverts), 2);
+        assert!(!state.revert_to(first));
+        assert!(!state.revert_to(third));
+        assert!(state.snapshots.journal.is_empty());
+        assert_eq!(
+            state
+                .bundle_state
+                .account(&address)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .balance,
+            U256::from(1)
+        );
+        assert!(state.snapshot() > third);
+    }
+
+    #[test]
+    fn iter_over_database() {
+        let address = Address::with_last_byte(1);
+        let mut db = crate::InMemoryDB::default();
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(address, U256::from(2), U256::from(2))
+            .unwrap();
+        let mut state = State::builder().with_database(db).build();
+
+        let mut account = Account::from(state.basic(address).unwrap().unwrap());
+        account.mark_touch();
+        account.storage.insert(
+            U256::from(1),
+            state::EvmStorageSlot::new_changed(U256::from(1), U256::ZERO),
+        );
+        account.storage.insert(
+            U256::from(3),
+            state::EvmStorageSlot::new_changed(U256::ZERO, U256::from(3)),
+        );
+        state.commit(HashMap::from_iter([(address, account)]));
+        state.insert_account(Address::with_last_byte(2), AccountInfo::default());
+
+        assert_eq!(
+            state.storage_range(address, U256::ZERO, 10),
+            Ok(vec![
+                (U256::from(2), U256::from(2)),
+                (U256::from(3), U256::from(3))
+            ])
+        );
+        let accounts = state.accounts_range(Address::ZERO, 10).unwrap();
+        assert_eq!(
+            accounts.iter().map(|(a, _)| *a).collect::<Vec<_>>(),
+            [address, Address::with_last_byte(2)]
+        );
+    }
+
+    #[test]
+    fn background_transition_merge() {
+        let build = |background: bool| {
+            let builder = State::builder().with_bundle_update();
+            let mut state = if background {
+                builder.with_background_transition_merge().build()
+            } else {
+                builder.build()
+            };
+            for i in 1..=3u8 {
+                let address = Address::with_last_byte(i);
+                let mut account = Account::from(state.basic(address).unwrap().unwrap_or_default());
+                account.info.nonce = i as u64;
+                account.mark_touch();
+                account.storage.insert(
+                    U256::from(i),
+                    state::EvmStorageSlot::new_changed(U256::ZERO, U256::from(i)),
+                );
+                state.commit(HashMap::from_iter([(address, account)]));
+                state.merge_transitions(BundleRetention::Reverts);
+            }
+            state
+                .increment_balances([(Address::with_last_byte(1), 1)])
+                .unwrap();
+            state.merge_transitions(BundleRetention::Reverts);
+            state
+        };
+
+        let mut foreground = build(false);
+        let mut background = build(true);
+        assert!(background.is_merging_in_background());
+        background.wait_for_background_merge();
+        assert!(!background.is_merging_in_background());
+        assert_eq!(background.bundle_size_hint(), foreground.bundle_size_hint());
+        assert_eq!(background.bundle_state, foreground.bundle_state);
+
+        // Foreground merge after background merges keeps their order.
+        let mut background = build(true);
+        background.background_transition_merge = false;
+        background
+            .increment_balances([(Address::with_last_byte(2), 1)])
+            .unwrap();
+        background.merge_transitions(BundleRetention::Reverts);
+        assert!(!background.is_merging_in_background());
+        foreground
+            .increment_balances([(Address::with_last_byte(2), 1)])
+            .unwrap();
+        foreground.merge_transitions(BundleRetention::Reverts);
+
+        let bundle = build(true).take_bundle();
+        assert_eq!(bundle.reverts.len(), 4);
+        assert_eq!(background.take_bundle(), foreground.take_bundle());
+    }
 }
@@ -141,8 +141,11 @@ impl<DB: Database> StateBuilder<DB> {
         }
     }
 
-    /// Starts the thread that will take transitions and do merge to the bundle state
-    /// in the background.
+    /// Merges transitions into the bundle state on a worker thread, so the EVM can continue
+    /// executing while reverts are created.
+    ///
+    /// See [`State::background_transition_merge`] for details. Without the `std` feature
+    /// transitions are merged on the calling thread.
     pub fn with_background_transition_merge(self) -> Self {
         Self {
             with_background_transition_merge: true,
@@ -173,6 +176,10 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            background_transition_merge: self.with_background_transition_merge,
+            #[cfg(feature = "std")]
+            merge_worker: None,
+            snapshots: Default::default(),
         }
     }
 }
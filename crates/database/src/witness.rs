@@ -0,0 +1,207 @@
+//! Execution witness recording.
+use database_interface::Database;
+use primitives::{keccak256, Address, Bytes, HashMap, B256, KECCAK_EMPTY, U256};
+use state::{AccountInfo, Bytecode};
+use std::vec::Vec;
+
+/// Pre-state read during execution, sufficient to re-execute it without the original database.
+///
+/// Contains values of everything that was read and, if it was built with
+/// [`WitnessDB::into_witness_with_proofs`], Merkle-Patricia trie nodes proving accounts and
+/// storage against the pre-state root.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// Accounts without code. `None` means account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots read per account.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Bytecodes by code hash.
+    pub codes: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<u64, B256>,
+    /// Trie nodes by their keccak256 hash.
+    pub state: HashMap<B256, Bytes>,
+}
+
+impl ExecutionWitness {
+    /// Adds trie nodes to the witness.
+    pub fn extend_state(&mut self, nodes: impl IntoIterator<Item = Bytes>) {
+        self.state
+            .extend(nodes.into_iter().map(|node| (keccak256(&node), node)));
+    }
+}
+
+/// Provides Merkle proofs for accounts and storage of the pre-state.
+///
+/// The returned nodes are the concatenation of account proof and storage proofs as returned by
+/// `eth_getProof`. Implemented for closures with the same signature.
+pub trait WitnessProofProvider {
+    /// Proof error.
+    type Error;
+
+    /// Returns trie nodes proving the account and its `slots`.
+    fn proof(&mut self, address: Address, slots: &[U256]) -> Result<Vec<Bytes>, Self::Error>;
+}
+
+impl<F, E> WitnessProofProvider for F
+where
+    F: FnMut(Address, &[U256]) -> Result<Vec<Bytes>, E>,
+{
+    type Error = E;
+
+    fn proof(&mut self, address: Address, slots: &[U256]) -> Result<Vec<Bytes>, Self::Error> {
+        self(address, slots)
+    }
+}
+
+/// A [Database] wrapper that records every read into an [ExecutionWitness].
+///
+/// Put it directly over the database holding the pre-state, below any caching layer such as
+/// [`State`][crate::State] or [`CacheDB`][crate::CacheDB], so only reads that reach the database
+/// are recorded.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessDB<DB> {
+    /// Wrapped database.
+    pub db: DB,
+    witness: ExecutionWitness,
+}
+
+impl<DB> WitnessDB<DB> {
+    /// Wraps the database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: ExecutionWitness::default(),
+        }
+    }
+
+    /// Returns the witness recorded so far.
+    pub fn witness(&self) -> &ExecutionWitness {
+        &self.witness
+    }
+
+    /// Consumes the wrapper and returns the recorded witness without proofs.
+    pub fn into_witness(self) -> ExecutionWitness {
+        self.witness
+    }
+
+    /// Consumes the wrapper and returns the recorded witness together with Merkle proofs of all
+    /// recorded accounts and storage slots.
+    pub fn into_witness_with_proofs<P: WitnessProofProvider>(
+        self,
+        mut provider: P,
+    ) -> Result<ExecutionWitness, P::Error> {
+        let mut witness = self.witness;
+        let mut slots = Vec::new();
+        for address in witness.accounts.keys() {
+            slots.clear();
+            if let Some(storage) = witness.storage.get(address) {
+                slots.extend(storage.keys().copied());
+                slots.sort_unstable();
+            }
+            let nodes = provider.proof(*address, &slots)?;
+            witness
+                .state
+                .extend(nodes.into_iter().map(|node| (keccak256(&node), node)));
+        }
+        Ok(witness)
+    }
+}
+
+impl<DB: Database> Database for WitnessDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        if let Some(info) = &info {
+            if let Some(code) = &info.code {
+                if info.code_hash != KECCAK_EMPTY {
+                    self.witness.codes.insert(info.code_hash, code.clone());
+                }
+            }
+        }
+        self.witness
+            .accounts
+            .insert(address, info.as_ref().map(AccountInfo::copy_without_code));
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        if code_hash != KECCAK_EMPTY {
+            self.witness.codes.insert(code_hash, code.clone());
+        }
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+        // Storage is proven against the account storage root, so the account is needed too.
+        if !self.witness.accounts.contains_key(&address) {
+            self.basic(address)?;
+        }
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.insert(number, hash);
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryDB;
+    use core::convert::Infallible;
+    use primitives::{address, bytes};
+
+    #[test]
+    fn records_reads() {
+        let account = address!("0x0000000000000000000000000000000000000001");
+        let missing = address!("0x0000000000000000000000000000000000000002");
+        let code = Bytecode::new_raw(bytes!("6000"));
+        let info = AccountInfo::new(U256::from(1), 0, code.hash_slow(), code.clone());
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(account, info.clone());
+        db.insert_account_storage(account, U256::from(1), U256::from(2))
+            .unwrap();
+
+        let mut db = WitnessDB::new(db);
+        db.storage(account, U256::from(1)).unwrap();
+        db.storage(account, U256::from(3)).unwrap();
+        db.basic(missing).unwrap();
+        db.code_by_hash(info.code_hash).unwrap();
+        let hash = db.block_hash(7).unwrap();
+
+        let witness = db
+            .into_witness_with_proofs(|address: Address, slots: &[U256]| {
+                Ok::<_, Infallible>(vec![Bytes::copy_from_slice(
+                    &[address.as_slice(), &[slots.len() as u8]].concat(),
+                )])
+            })
+            .unwrap();
+
+        assert_eq!(
+            witness.accounts,
+            HashMap::from_iter([(account, Some(info.copy_without_code())), (missing, None)])
+        );
+        assert_eq!(
+            witness.storage[&account],
+            HashMap::from_iter([(U256::from(1), U256::from(2)), (U256::from(3), U256::ZERO)])
+        );
+        assert_eq!(witness.codes, HashMap::from_iter([(info.code_hash, code)]));
+        assert_eq!(witness.block_hashes, HashMap::from_iter([(7, hash)]));
+        assert_eq!(witness.state.len(), 2);
+        let node = Bytes::copy_from_slice(&[account.as_slice(), &[2]].concat());
+        assert_eq!(witness.state[&keccak256(&node)], node);
+    }
+}
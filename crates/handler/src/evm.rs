@@ -71,3 +71,185 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthPrecompiles;
+    use context::{BlockEnv, CfgEnv, Context, EvmData, JournaledState, TxEnv};
+    use context_interface::result::EVMError;
+    use core::{convert::Infallible, fmt};
+    use database::{
+        components::{BlockHashTable, CodeCache},
+        CacheDB, DatabaseComponents, DatabaseStore, EmptyDB,
+    };
+    use database_interface::{
+        DBErrorMarker, DBErrorWithContext, DatabaseOp, DatabaseRef, ErrorContextDB,
+    };
+    use primitives::{address, bytes, Address, HashSet, TxKind, B256, U256};
+    use specification::hardfork::SpecId;
+    use state::{AccountInfo, Bytecode};
+
+    /// Error of a [PartialDB] call for a value that is not available.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Unavailable(DatabaseOp);
+
+    impl fmt::Display for Unavailable {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?} is not available", self.0)
+        }
+    }
+
+    impl core::error::Error for Unavailable {}
+
+    impl DBErrorMarker for Unavailable {}
+
+    /// Database serving only the available values of the remote database.
+    #[derive(Default)]
+    struct PartialDB {
+        remote: CacheDB<EmptyDB>,
+        available: HashSet<DatabaseOp>,
+    }
+
+    impl PartialDB {
+        fn get<T>(
+            &self,
+            op: DatabaseOp,
+            value: impl FnOnce(&CacheDB<EmptyDB>) -> Result<T, Infallible>,
+        ) -> Result<T, Unavailable> {
+            if !self.available.contains(&op) {
+                return Err(Unavailable(op));
+            }
+            Ok(value(&self.remote).unwrap())
+        }
+    }
+
+    impl Database for PartialDB {
+        type Error = Unavailable;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.get(DatabaseOp::Basic(address), |db| db.basic_ref(address))
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.get(DatabaseOp::CodeByHash(code_hash), |db| {
+                db.code_by_hash_ref(code_hash)
+            })
+        }
+
+        fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.get(DatabaseOp::Storage(address, index), |db| {
+                db.storage_ref(address, index)
+            })
+        }
+
+        fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+            self.get(DatabaseOp::BlockHash(number), |db| {
+                db.block_hash_ref(number)
+            })
+        }
+    }
+
+    #[test]
+    fn database_error_context() {
+        let caller = address!("0x0000000000000000000000000000000000000100");
+        let contract = address!("0x0000000000000000000000000000000000000200");
+        let mut db = PartialDB::default();
+        db.remote
+            .insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        // SLOAD(0)
+        let code = Bytecode::new_raw(bytes!("60005400"));
+        let code_hash = code.hash_slow();
+        db.remote
+            .insert_account_info(contract, AccountInfo::new(U256::ZERO, 1, code_hash, code));
+        // Storage is not available.
+        db.available.extend([
+            DatabaseOp::Basic(Address::ZERO),
+            DatabaseOp::Basic(caller),
+            DatabaseOp::Basic(contract),
+            DatabaseOp::CodeByHash(code_hash),
+        ]);
+
+        type Db = ErrorContextDB<PartialDB>;
+        let ctx: Context<BlockEnv, TxEnv, CfgEnv, Db, JournaledState<Db>, ()> =
+            Context::new(ErrorContextDB::new(db), SpecId::CANCUN);
+        let mut evm = Evm {
+            data: EvmData { ctx, inspector: () },
+            instruction: EthInstructions::default(),
+            precompiles: EthPrecompiles::default(),
+        };
+        let tx = TxEnv {
+            caller,
+            kind: TxKind::Call(contract),
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+
+        let Err(EVMError::Database(error)) = evm.transact(tx) else {
+            panic!("expected database error");
+        };
+        assert_eq!(
+            error,
+            DBErrorWithContext {
+                op: DatabaseOp::Storage(contract, U256::ZERO),
+                depth: Some(1),
+                error: Unavailable(DatabaseOp::Storage(contract, U256::ZERO)),
+            }
+        );
+    }
+
+    #[test]
+    fn commit_to_database_components() {
+        type Accounts = DatabaseStore<CacheDB<EmptyDB>>;
+        type Code = CodeCache<DatabaseStore<EmptyDB>>;
+        type Db = DatabaseComponents<Accounts, Code, Accounts, BlockHashTable>;
+
+        let caller = address!("0x0000000000000000000000000000000000000100");
+        let mut accounts = CacheDB::new(EmptyDB::default());
+        accounts.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        let mut db = DatabaseComponents::new(
+            DatabaseStore(accounts),
+            CodeCache::new(DatabaseStore(EmptyDB::default())),
+            DatabaseStore(CacheDB::new(EmptyDB::default())),
+            BlockHashTable::from_iter([(1, B256::with_last_byte(1))]),
+        );
+
+        // SSTORE(0, 1) and deploy `6001600055`.
+        let init_code = bytes!("60016000556460016000556000526005601bf3");
+        let ctx: Context<BlockEnv, TxEnv, CfgEnv, &mut Db, JournaledState<&mut Db>, ()> =
+            Context::new(&mut db, SpecId::CANCUN);
+        let mut evm = Evm {
+            data: EvmData { ctx, inspector: () },
+            instruction: EthInstructions::default(),
+            precompiles: EthPrecompiles::default(),
+        };
+        let tx = TxEnv {
+            caller,
+            kind: TxKind::Create,
+            value: U256::from(10),
+            data: init_code,
+            gas_limit: 100_000,
+            ..Default::default()
+        };
+        assert!(evm.transact_commit(tx).unwrap().is_success());
+
+        let contract = caller.create(0);
+        let code = Bytecode::new_raw(bytes!("6001600055"));
+        let info = db.basic(contract).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(info.nonce, 1);
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(db.basic(caller).unwrap().unwrap().nonce, 1);
+
+        // Every component holds its part of the changes.
+        assert_eq!(db.code.contracts.get(&info.code_hash), Some(&code));
+        assert_eq!(db.code_by_hash(info.code_hash).unwrap(), code);
+        assert_eq!(db.storage(contract, U256::ZERO).unwrap(), U256::from(1));
+        assert_eq!(
+            db.storage.0.storage_ref(contract, U256::ZERO).unwrap(),
+            U256::from(1)
+        );
+        assert!(db.accounts.0.accounts[&contract].storage.is_empty());
+        assert_eq!(db.block_hash(1).unwrap(), B256::with_last_byte(1));
+    }
+}
@@ -27,4 +27,4 @@ pub use frame_data::{FrameData, FrameResult};
 pub use handler::{EvmTr, EvmTrError, Handler};
 pub use item_or_result::{FrameInitOrResult, FrameOrResult, ItemOrResult};
 pub use mainnet_handler::MainnetHandler;
-pub use precompile_provider::{EthPrecompiles, PrecompileProvider};
+pub use precompile_provider::{EthPrecompiles, PrecompileCall, PrecompileProvider};
@@ -3,7 +3,7 @@ use context::Cfg;
 use context_interface::ContextTr;
 use interpreter::{Gas, InstructionResult, InterpreterResult};
 use precompile::PrecompileError;
-use precompile::{PrecompileSpecId, Precompiles};
+use precompile::{PrecompileResult, PrecompileSpecId, Precompiles};
 use primitives::{Address, Bytes};
 use specification::hardfork::SpecId;
 use std::boxed::Box;
@@ -29,17 +29,60 @@ pub trait PrecompileProvider {
 
     /// Check if the address is a precompile.
     fn contains(&self, address: &Address) -> bool;
+
+    /// Enables or disables recording of the calls returned by [`PrecompileProvider::take_last_call`].
+    ///
+    /// Disabled by default. Enabled by the inspector handler while a transaction is inspected.
+    fn record_calls(&mut self, _record: bool) {}
+
+    /// Take the record of the last precompile executed by [`PrecompileProvider::run`].
+    ///
+    /// Used by inspectors to report precompile calls. Providers that do not record calls, or
+    /// have recording disabled, return `None`.
+    fn take_last_call(&mut self) -> Option<PrecompileCall> {
+        None
+    }
+}
+
+/// Record of a single precompile execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecompileCall {
+    /// Address of the precompile.
+    pub address: Address,
+    /// Input passed to the precompile.
+    pub input: Bytes,
+    /// Gas limit the precompile was called with.
+    pub gas_limit: u64,
+    /// Raw precompile result, containing gas used and output or the error variant.
+    pub result: PrecompileResult,
 }
 
 pub struct EthPrecompiles<CTX> {
     pub precompiles: &'static Precompiles,
+    /// Records executed calls, see [`PrecompileProvider::record_calls`].
+    record_calls: bool,
+    /// Last executed precompile call, see [`PrecompileProvider::take_last_call`].
+    last_call: Option<PrecompileCall>,
     pub _phantom: core::marker::PhantomData<CTX>,
 }
 
+impl<CTX> EthPrecompiles<CTX> {
+    pub fn new(precompiles: &'static Precompiles) -> Self {
+        Self {
+            precompiles,
+            record_calls: false,
+            last_call: None,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
 impl<CTX> Clone for EthPrecompiles<CTX> {
     fn clone(&self) -> Self {
         Self {
             precompiles: self.precompiles,
+            record_calls: self.record_calls,
+            last_call: self.last_call.clone(),
             _phantom: core::marker::PhantomData,
         }
     }
@@ -47,10 +90,9 @@ impl<CTX> Clone for EthPrecompiles<CTX> {
 
 impl<CTX> Default for EthPrecompiles<CTX> {
     fn default() -> Self {
-        Self {
-            precompiles: Precompiles::new(PrecompileSpecId::from_spec_id(SpecId::LATEST)),
-            _phantom: core::marker::PhantomData,
-        }
+        Self::new(Precompiles::new(PrecompileSpecId::from_spec_id(
+            SpecId::LATEST,
+        )))
     }
 }
 
@@ -62,6 +104,7 @@ where
     type Output = InterpreterResult;
     fn set_spec(&mut self, spec: <<Self::Context as ContextTr>::Cfg as Cfg>::Spec) {
         self.precompiles = Precompiles::new(PrecompileSpecId::from_spec_id(spec.into()));
+        self.last_call = None;
     }
 
     fn run(
@@ -71,6 +114,7 @@ where
         bytes: &Bytes,
         gas_limit: u64,
     ) -> Result<Option<InterpreterResult>, PrecompileError> {
+        self.last_call = None;
         let Some(precompile) = self.precompiles.get(address) else {
             return Ok(None);
         };
@@ -81,16 +125,17 @@ where
             output: Bytes::new(),
         };
 
-        match (*precompile)(bytes, gas_limit) {
+        let output = (*precompile)(bytes, gas_limit);
+        match &output {
             Ok(output) => {
                 let underflow = result.gas.record_cost(output.gas_used);
                 assert!(underflow, "Gas underflow is not possible");
                 result.result = InstructionResult::Return;
-                result.output = output.bytes;
+                result.output = output.bytes.clone();
             }
             Err(e) => {
                 if let PrecompileError::Fatal(_) = e {
-                    return Err(e);
+                    return Err(e.clone());
                 }
                 result.result = if e.is_oog() {
                     InstructionResult::PrecompileOOG
@@ -99,6 +144,14 @@ where
                 };
             }
         }
+        if self.record_calls {
+            self.last_call = Some(PrecompileCall {
+                address: *address,
+                input: bytes.clone(),
+                gas_limit,
+                result: output,
+            });
+        }
         Ok(Some(result))
     }
 
@@ -109,4 +162,13 @@ where
     fn contains(&self, address: &Address) -> bool {
         self.precompiles.contains(address)
     }
+
+    fn record_calls(&mut self, record: bool) {
+        self.record_calls = record;
+        self.last_call = None;
+    }
+
+    fn take_last_call(&mut self) -> Option<PrecompileCall> {
+        self.last_call.take()
+    }
 }
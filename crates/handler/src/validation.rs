@@ -28,6 +28,31 @@ pub fn validate_env<CTX: ContextTr, ERROR: From<InvalidHeader> + From<InvalidTra
     validate_tx_env::<CTX, InvalidTransaction>(context, spec).map_err(Into::into)
 }
 
+/// Validates the blob sidecar of an EIP-4844 transaction against its versioned hashes.
+///
+/// Sidecars are not part of the transaction environment, block validators can call this before
+/// execution to reject transactions with invalid blobs early.
+#[cfg(feature = "c-kzg")]
+pub fn validate_eip4844_sidecar(
+    tx: &impl Transaction,
+    blobs: &[precompile::blob_kzg::Blob],
+    commitments: &[precompile::blob_kzg::KzgCommitment],
+    proofs: &[precompile::blob_kzg::KzgProof],
+) -> Result<(), InvalidTransaction> {
+    use precompile::blob_kzg::{verify_blob_sidecar, BlobVerificationError};
+
+    verify_blob_sidecar(tx.blob_versioned_hashes(), blobs, commitments, proofs).map_err(|e| match e
+    {
+        BlobVerificationError::LengthMismatch { .. }
+        | BlobVerificationError::MismatchedVersionedHash { .. } => {
+            InvalidTransaction::BlobSidecarMismatch
+        }
+        BlobVerificationError::InvalidInput(_) | BlobVerificationError::VerificationFailed => {
+            InvalidTransaction::BlobKzgProofInvalid
+        }
+    })
+}
+
 pub fn validate_tx_against_state<
     CTX: ContextTr,
     ERROR: From<InvalidTransaction> + From<<CTX::Db as Database>::Error>,
@@ -5,6 +5,7 @@ use context::{
 };
 use handler::{
     execution, EvmTr, Frame, FrameInitOrResult, FrameOrResult, FrameResult, Handler, ItemOrResult,
+    PrecompileCall,
 };
 use interpreter::{
     interpreter::EthInterpreter,
@@ -84,6 +85,17 @@ pub trait Inspector<CTX, INTR: InterpreterTypes = EthInterpreter> {
         let _ = outcome;
     }
 
+    /// Called when a precompile has been executed, before [`Inspector::call_end`].
+    ///
+    /// `call` contains the precompile address, input, gas limit and the raw precompile result,
+    /// including the [`PrecompileError`][precompile::PrecompileError] variant if it failed.
+    #[inline]
+    fn precompile(&mut self, context: &mut CTX, inputs: &CallInputs, call: &PrecompileCall) {
+        let _ = context;
+        let _ = inputs;
+        let _ = call;
+    }
+
     /// Called when a contract is about to be created.
     ///
     /// If this returns `Some` then the [CreateOutcome] is used to override the result of the creation.
@@ -204,12 +216,16 @@ where
     ) -> Result<FrameResult, Self::Error> {
         let gas_limit = evm.ctx().tx().gas_limit() - init_and_floor_gas.initial_gas;
 
-        // Create first frame action
-        let first_frame = self.inspect_create_first_frame(evm, gas_limit)?;
-        let mut frame_result = match first_frame {
-            ItemOrResult::Item(frame) => self.inspect_run_exec_loop(evm, frame)?,
-            ItemOrResult::Result(result) => result,
-        };
+        // Precompile calls are only recorded for the inspector.
+        evm.record_precompile_calls(true);
+        let frame_result = self.inspect_create_first_frame(evm, gas_limit).and_then(
+            |first_frame| match first_frame {
+                ItemOrResult::Item(frame) => self.inspect_run_exec_loop(evm, frame),
+                ItemOrResult::Result(result) => Ok(result),
+            },
+        );
+        evm.record_precompile_calls(false);
+        let mut frame_result = frame_result?;
 
         self.last_frame_result(evm, &mut frame_result)?;
         Ok(frame_result)
@@ -244,7 +260,9 @@ where
             let (context, inspector) = evm.ctx_inspector();
             inspector.initialize_interp(frame.interpreter(), context);
         } else if let Ok(ItemOrResult::Result(result)) = &mut ret {
+            let precompile_call = evm.take_precompile_call();
             let (context, inspector) = evm.ctx_inspector();
+            precompile_end(context, inspector, &frame_input, precompile_call);
             frame_end(context, inspector, &frame_input, result);
         }
         ret
@@ -285,7 +303,9 @@ where
                             }
                             // Dont pop the frame as new frame was not created.
                             ItemOrResult::Result(mut result) => {
+                                let precompile_call = evm.take_precompile_call();
                                 let (context, inspector) = evm.ctx_inspector();
+                                precompile_end(context, inspector, &init, precompile_call);
                                 frame_end(context, inspector, &init, &mut result);
                                 result
                             }
@@ -336,6 +356,17 @@ fn frame_start<CTX, INTR: InterpreterTypes>(
     None
 }
 
+fn precompile_end<CTX, INTR: InterpreterTypes>(
+    context: &mut CTX,
+    inspector: &mut impl Inspector<CTX, INTR>,
+    frame_input: &FrameInput,
+    precompile_call: Option<PrecompileCall>,
+) {
+    if let (FrameInput::Call(i), Some(call)) = (frame_input, precompile_call) {
+        inspector.precompile(context, i, &call);
+    }
+}
+
 fn frame_end<CTX, INTR: InterpreterTypes>(
     context: &mut CTX,
     inspector: &mut impl Inspector<CTX, INTR>,
@@ -435,3 +466,82 @@ where
 
     next_action
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use database::BenchmarkDB;
+    use handler::{ExecuteEvm, PrecompileProvider};
+    use precompile::{u64_to_address, PrecompileError, PrecompileOutput};
+    use revm::{
+        bytecode::Bytecode,
+        primitives::{Bytes, TxKind},
+        Context, MainBuilder, MainContext,
+    };
+
+    #[derive(Default)]
+    struct PrecompileInspector {
+        calls: Vec<PrecompileCall>,
+    }
+
+    impl<CTX> Inspector<CTX> for PrecompileInspector {
+        fn precompile(&mut self, _: &mut CTX, inputs: &CallInputs, call: &PrecompileCall) {
+            assert_eq!(inputs.bytecode_address, call.address);
+            self.calls.push(call.clone());
+        }
+    }
+
+    fn inspect_precompile(address: Address, input: Bytes) -> Vec<PrecompileCall> {
+        let ctx = Context::mainnet()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_tx_chained(|tx| {
+                tx.kind = TxKind::Call(address);
+                tx.data = input;
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = ctx.build_mainnet_with_inspector(PrecompileInspector::default());
+        evm.inspect_previous().unwrap();
+        core::mem::take(&mut evm.data.inspector.calls)
+    }
+
+    #[test]
+    fn precompile_hook_reports_output() {
+        let input = Bytes::from_static(&[1, 2, 3]);
+        let calls = inspect_precompile(u64_to_address(4), input.clone());
+        assert_eq!(
+            calls,
+            vec![PrecompileCall {
+                address: u64_to_address(4),
+                input: input.clone(),
+                gas_limit: 100_000 - 21_000 - 3 * 16,
+                result: Ok(PrecompileOutput::new(18, input)),
+            }]
+        );
+    }
+
+    #[test]
+    fn precompile_hook_reports_error() {
+        let calls = inspect_precompile(u64_to_address(8), Bytes::from_static(&[0; 10]));
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].result, Err(PrecompileError::Bn128PairLength));
+    }
+
+    #[test]
+    fn precompile_calls_recorded_only_while_inspecting() {
+        let ctx = Context::mainnet()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_tx_chained(|tx| {
+                tx.kind = TxKind::Call(u64_to_address(4));
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = ctx.build_mainnet_with_inspector(PrecompileInspector::default());
+
+        evm.transact_previous().unwrap();
+        assert_eq!(evm.precompiles.take_last_call(), None);
+
+        evm.inspect_previous().unwrap();
+        assert_eq!(evm.data.inspector.calls.len(), 1);
+        assert_eq!(evm.precompiles.take_last_call(), None);
+    }
+}
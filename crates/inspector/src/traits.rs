@@ -2,7 +2,7 @@ use crate::{inspect_instructions, Inspector, JournalExt};
 use context::{setters::ContextSetters, ContextTr, Evm};
 use handler::{
     instructions::InstructionProvider, ContextTrDbError, EthFrame, EvmTr, Frame, FrameInitOrResult,
-    PrecompileProvider,
+    PrecompileCall, PrecompileProvider,
 };
 use interpreter::{
     interpreter::EthInterpreter, FrameInput, Interpreter, InterpreterAction, InterpreterResult,
@@ -18,6 +18,14 @@ pub trait InspectorEvmTr: EvmTr {
 
     fn ctx_inspector(&mut self) -> (&mut Self::Context, &mut Self::Inspector);
 
+    /// Enables or disables recording of precompile calls by the precompile provider.
+    fn record_precompile_calls(&mut self, _record: bool) {}
+
+    /// Take the last precompile call recorded by the precompile provider.
+    fn take_precompile_call(&mut self) -> Option<PrecompileCall> {
+        None
+    }
+
     fn run_inspect_interpreter(
         &mut self,
         interpreter: &mut Interpreter<
@@ -31,6 +39,7 @@ where
     CTX: ContextTr<Journal: JournalExt> + ContextSetters,
     I: InstructionProvider<Context = CTX, Output = InterpreterAction>,
     INSP: Inspector<CTX, I::InterpreterTypes>,
+    P: PrecompileProvider<Context = CTX>,
 {
     type Inspector = INSP;
 
@@ -42,6 +51,14 @@ where
         (&mut self.data.ctx, &mut self.data.inspector)
     }
 
+    fn record_precompile_calls(&mut self, record: bool) {
+        self.precompiles.record_calls(record)
+    }
+
+    fn take_precompile_call(&mut self) -> Option<PrecompileCall> {
+        self.precompiles.take_last_call()
+    }
+
     fn run_inspect_interpreter(
         &mut self,
         interpreter: &mut Interpreter<
@@ -4,7 +4,7 @@ use precompile::{secp256r1, PrecompileError, Precompiles};
 use revm::{
     context::Cfg,
     context_interface::ContextTr,
-    handler::{EthPrecompiles, PrecompileProvider},
+    handler::{EthPrecompiles, PrecompileCall, PrecompileProvider},
     interpreter::InterpreterResult,
 };
 use std::boxed::Box;
@@ -24,10 +24,7 @@ impl<CTX> Clone for OpPrecompileProvider<CTX> {
 impl<CTX> OpPrecompileProvider<CTX> {
     pub fn new(precompiles: &'static Precompiles) -> Self {
         Self {
-            precompile_provider: EthPrecompiles {
-                precompiles,
-                _phantom: core::marker::PhantomData,
-            },
+            precompile_provider: EthPrecompiles::new(precompiles),
         }
     }
 
@@ -117,6 +114,16 @@ where
     fn contains(&self, address: &precompile::Address) -> bool {
         self.precompile_provider.contains(address)
     }
+
+    #[inline]
+    fn record_calls(&mut self, record: bool) {
+        self.precompile_provider.record_calls(record)
+    }
+
+    #[inline]
+    fn take_last_call(&mut self) -> Option<PrecompileCall> {
+        self.precompile_provider.take_last_call()
+    }
 }
 
 impl<CTX> Default for OpPrecompileProvider<CTX> {
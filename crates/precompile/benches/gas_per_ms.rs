@@ -0,0 +1,572 @@
+//! Gas-per-millisecond benchmarks for every precompile in [`Precompiles::latest`].
+//!
+//! The corpus contains a typical input and a set of adversarial inputs for each precompile
+//! (worst-case modexp exponents and moduli, large-round blake2f, maximum size MSMs, ...).
+//! Before running criterion, every case is timed and a table sorted by gas per
+//! millisecond is printed so that mispriced inputs stand out. Cases that are priced at less
+//! than half of the ecrecover throughput are flagged.
+//!
+//! Modexp cases are additionally priced under every modexp gas schedule so that repricing
+//! proposals can be compared against the measured runtime.
+//!
+//! A seeded random corpus is run as a fuzz pass over all precompiles. Set
+//! `PRECOMPILE_FUZZ_SEED` and `PRECOMPILE_FUZZ_CASES` to change the seed and the number of
+//! cases per precompile.
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use primitives::{hex, keccak256, U256};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use revm_precompile::{
+    bls12_381_const::{
+        G1_ADD_ADDRESS, G1_MSM_ADDRESS, G2_ADD_ADDRESS, G2_MSM_ADDRESS, MAP_FP2_TO_G2_ADDRESS,
+        MAP_FP_TO_G1_ADDRESS, PAIRING_ADDRESS,
+    },
+    kzg_point_evaluation::kzg_to_versioned_hash,
+    modexp::{berlin_gas_calc, byzantium_gas_calc, osaka_gas_calc},
+    secp256r1::P256VERIFY_ADDRESS,
+    u64_to_address, Address, Bytes, PrecompileResult, Precompiles,
+};
+use secp256k1::{Message, SecretKey, SECP256K1};
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+/// Gas limit used for every call, a mainnet sized block.
+const GAS_LIMIT: u64 = 30_000_000;
+
+/// Minimal time spent measuring a single corpus case for the summary table.
+const MEASURE_TIME: Duration = Duration::from_millis(20);
+
+/// Default seed of the random corpus.
+const DEFAULT_FUZZ_SEED: u64 = 0x7265_766d;
+
+/// Default number of random inputs generated per precompile.
+const DEFAULT_FUZZ_CASES: usize = 256;
+
+/// Number of slowest random inputs reported after the fuzz pass.
+const FUZZ_REPORT_LEN: usize = 10;
+
+/// Case is flagged if its gas per millisecond is below `anchor / FLAG_RATIO`.
+const FLAG_RATIO: f64 = 2.0;
+
+/// Modexp gas calculation function, see [`byzantium_gas_calc`].
+type ModexpGasCalc = fn(u64, u64, u64, &U256) -> u64;
+
+/// Modexp gas schedules compared in the modexp report.
+const MODEXP_SCHEDULES: [(&str, ModexpGasCalc); 3] = [
+    ("EIP-198", byzantium_gas_calc),
+    ("EIP-2565", berlin_gas_calc),
+    ("EIP-7883", osaka_gas_calc),
+];
+
+const ECRECOVER: Address = u64_to_address(1);
+const SHA256: Address = u64_to_address(2);
+const RIPEMD160: Address = u64_to_address(3);
+const IDENTITY: Address = u64_to_address(4);
+const MODEXP: Address = u64_to_address(5);
+const BN128_ADD: Address = u64_to_address(6);
+const BN128_MUL: Address = u64_to_address(7);
+const BN128_PAIR: Address = u64_to_address(8);
+const BLAKE2F: Address = u64_to_address(9);
+const POINT_EVALUATION: Address = u64_to_address(0x0A);
+
+/// BN128 G1 point used as base for bn128 inputs.
+const BN128_G1: [u8; 64] = hex!(
+    "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9"
+    "063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266"
+);
+
+/// Two pairs whose pairing check succeeds.
+const BN128_PAIRS: [u8; 384] = hex!(
+    "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59"
+    "3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41"
+    "209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7"
+    "04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678"
+    "2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d"
+    "120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550"
+    "111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c"
+    "2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411"
+    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
+);
+
+/// BLS12-381 G1 generator, each coordinate padded to 64 bytes.
+const BLS_G1: [u8; 128] = hex!(
+    "0000000000000000000000000000000017f1d3a73197d7942695638c4fa9ac0f"
+    "c3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+    "0000000000000000000000000000000008b3f481e3aaa0f1a09e30ed741d8ae4"
+    "fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+);
+
+/// BLS12-381 G2 generator, each coordinate padded to 64 bytes.
+const BLS_G2: [u8; 256] = hex!(
+    "00000000000000000000000000000000024aa2b2f08f0a91260805272dc51051"
+    "c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+    "0000000000000000000000000000000013e02b6052719f607dacd3a088274f65"
+    "596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"
+    "000000000000000000000000000000000ce5d527727d6e118cc9cdc6da2e351a"
+    "adfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801"
+    "000000000000000000000000000000000606c4a02ea734cc32acd2b02bc28b99"
+    "cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be"
+);
+
+/// A named input for a precompile.
+struct Case {
+    name: String,
+    address: Address,
+    input: Bytes,
+}
+
+impl Case {
+    fn new(name: impl Into<String>, address: Address, input: impl Into<Bytes>) -> Self {
+        Self {
+            name: name.into(),
+            address,
+            input: input.into(),
+        }
+    }
+
+    fn run(&self) -> PrecompileResult {
+        let precompile = Precompiles::latest()
+            .get(&self.address)
+            .expect("precompile is in the latest set");
+        precompile(&self.input, GAS_LIMIT)
+    }
+}
+
+/// Measured gas and runtime of a single [`Case`].
+struct Measurement {
+    gas_used: u64,
+    per_call: Duration,
+}
+
+impl Measurement {
+    fn gas_per_ms(&self, gas: u64) -> f64 {
+        gas as f64 / (self.per_call.as_nanos().max(1) as f64 / 1_000_000.0)
+    }
+}
+
+/// Runs the case repeatedly for at least `duration` and returns the average runtime.
+///
+/// Returns `None` if the precompile returned an error.
+fn measure(case: &Case, duration: Duration) -> Option<Measurement> {
+    let gas_used = case.run().ok()?.gas_used;
+    let start = Instant::now();
+    let mut iterations = 0u32;
+    loop {
+        let _ = std::hint::black_box(case.run());
+        iterations += 1;
+        if start.elapsed() >= duration {
+            break;
+        }
+    }
+    Some(Measurement {
+        gas_used,
+        per_call: start.elapsed() / iterations,
+    })
+}
+
+/// Encodes modexp input from its parts.
+fn modexp_input(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
+    let mut input = Vec::with_capacity(96 + base.len() + exponent.len() + modulus.len());
+    input.extend_from_slice(&U256::from(base.len()).to_be_bytes::<32>());
+    input.extend_from_slice(&U256::from(exponent.len()).to_be_bytes::<32>());
+    input.extend_from_slice(&U256::from(modulus.len()).to_be_bytes::<32>());
+    input.extend_from_slice(base);
+    input.extend_from_slice(exponent);
+    input.extend_from_slice(modulus);
+    input
+}
+
+/// Decodes modexp header into `(base_len, exp_len, mod_len, exp_highp)`.
+fn modexp_lengths(input: &[u8]) -> (u64, u64, u64, U256) {
+    let mut padded = input.to_vec();
+    padded.resize(96, 0);
+    let len = |i: usize| U256::from_be_slice(&padded[i * 32..(i + 1) * 32]).saturating_to::<u64>();
+    let (base_len, exp_len, mod_len) = (len(0), len(1), len(2));
+
+    let mut exp_highp = [0u8; 32];
+    let exp_highp_len = exp_len.min(32) as usize;
+    let start = 96 + base_len as usize;
+    for (i, byte) in exp_highp[32 - exp_highp_len..].iter_mut().enumerate() {
+        *byte = input.get(start + i).copied().unwrap_or_default();
+    }
+    (base_len, exp_len, mod_len, U256::from_be_bytes(exp_highp))
+}
+
+/// Encodes blake2f input with the given number of rounds.
+fn blake2f_input(rounds: u32) -> Vec<u8> {
+    let mut input = vec![0u8; 213];
+    input[..4].copy_from_slice(&rounds.to_be_bytes());
+    // Initial state of `blake2b` with 64 bytes output.
+    input[4..68].copy_from_slice(&hex!(
+        "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5"
+        "d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b"
+    ));
+    input[68..71].copy_from_slice(b"abc");
+    input[196] = 3;
+    input[212] = 1;
+    input
+}
+
+/// Signs a fixed message and encodes it as ecrecover input.
+fn ecrecover_input() -> Vec<u8> {
+    let hash = keccak256(hex!("1337133713371337"));
+    let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+    let message = Message::from_digest_slice(&hash[..]).unwrap();
+    let (rec_id, signature) = SECP256K1
+        .sign_ecdsa_recoverable(&message, &secret_key)
+        .serialize_compact();
+
+    let mut input = vec![0u8; 128];
+    input[..32].copy_from_slice(&hash[..]);
+    input[63] = i32::from(rec_id) as u8 + 27;
+    input[64..].copy_from_slice(&signature);
+    input
+}
+
+/// Encodes a valid point evaluation input.
+fn point_evaluation_input() -> Vec<u8> {
+    let commitment = hex!("8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f26936857bc3a7c2539ea8ec3a952b7");
+    let z = hex!("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+    let y = hex!("1522a4a7f34e1ea350ae07c29c96c7e79655aa926122e95fe69fcbd932ca49e9");
+    let proof = hex!("a62ad71d14c5719385c0686f1871430475bf3a00f0aa3f7b8dd99a9abc2160744faf0070725e00b60ad9a026a15b1a8c");
+    [
+        &kzg_to_versioned_hash(&commitment)[..],
+        &z,
+        &y,
+        &commitment,
+        &proof,
+    ]
+    .concat()
+}
+
+/// Returns the handcrafted corpus, covering every precompile in the latest spec.
+fn corpus() -> Vec<Case> {
+    let scalar_max = [0xff; 32];
+    let mut cases = vec![
+        // ecrecover
+        Case::new("ecrecover", ECRECOVER, ecrecover_input()),
+        Case::new("ecrecover invalid signature", ECRECOVER, vec![0xff; 128]),
+        // hashes
+        Case::new("sha256 32b", SHA256, vec![0xab; 32]),
+        Case::new("sha256 128KiB", SHA256, vec![0xab; 128 * 1024]),
+        Case::new("ripemd160 32b", RIPEMD160, vec![0xab; 32]),
+        Case::new("ripemd160 128KiB", RIPEMD160, vec![0xab; 128 * 1024]),
+        Case::new("identity 32b", IDENTITY, vec![0xab; 32]),
+        Case::new("identity 128KiB", IDENTITY, vec![0xab; 128 * 1024]),
+        // bn128
+        Case::new("bn128 add", BN128_ADD, [BN128_G1, BN128_G1].concat()),
+        Case::new(
+            "bn128 mul max scalar",
+            BN128_MUL,
+            [&BN128_G1[..], &scalar_max].concat(),
+        ),
+        Case::new("bn128 pair k=2", BN128_PAIR, BN128_PAIRS.to_vec()),
+        Case::new("bn128 pair k=16", BN128_PAIR, BN128_PAIRS.repeat(8)),
+        // blake2f
+        Case::new("blake2f 12 rounds", BLAKE2F, blake2f_input(12)),
+        Case::new("blake2f 1 round", BLAKE2F, blake2f_input(1)),
+        Case::new("blake2f 2^16 rounds", BLAKE2F, blake2f_input(1 << 16)),
+        // point evaluation
+        Case::new(
+            "point evaluation",
+            POINT_EVALUATION,
+            point_evaluation_input(),
+        ),
+        // p256verify
+        Case::new(
+            "p256verify",
+            u64_to_address(P256VERIFY_ADDRESS),
+            hex!(
+                "4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4d"
+                "a73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac"
+                "36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d60"
+                "4aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff3"
+                "7618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e"
+            ),
+        ),
+        Case::new(
+            "p256verify invalid signature",
+            u64_to_address(P256VERIFY_ADDRESS),
+            vec![0xff; 160],
+        ),
+        // BLS12-381
+        Case::new(
+            "bls12 g1 add",
+            u64_to_address(G1_ADD_ADDRESS),
+            [BLS_G1, BLS_G1].concat(),
+        ),
+        Case::new(
+            "bls12 g2 add",
+            u64_to_address(G2_ADD_ADDRESS),
+            [BLS_G2, BLS_G2].concat(),
+        ),
+        Case::new(
+            "bls12 pairing k=1",
+            u64_to_address(PAIRING_ADDRESS),
+            [&BLS_G1[..], &BLS_G2].concat(),
+        ),
+        Case::new(
+            "bls12 pairing k=8",
+            u64_to_address(PAIRING_ADDRESS),
+            [&BLS_G1[..], &BLS_G2].concat().repeat(8),
+        ),
+        Case::new(
+            "bls12 map fp to g1",
+            u64_to_address(MAP_FP_TO_G1_ADDRESS),
+            [&[0u8; 16][..], &[0x11; 48]].concat(),
+        ),
+        Case::new(
+            "bls12 map fp2 to g2",
+            u64_to_address(MAP_FP2_TO_G2_ADDRESS),
+            [&[0u8; 16][..], &[0x11; 48], &[0u8; 16], &[0x12; 48]].concat(),
+        ),
+    ];
+
+    for k in [1, 128] {
+        cases.push(Case::new(
+            format!("bls12 g1 msm k={k}"),
+            u64_to_address(G1_MSM_ADDRESS),
+            [&BLS_G1[..], &scalar_max].concat().repeat(k),
+        ));
+        cases.push(Case::new(
+            format!("bls12 g2 msm k={k}"),
+            u64_to_address(G2_MSM_ADDRESS),
+            [&BLS_G2[..], &scalar_max].concat().repeat(k),
+        ));
+    }
+
+    cases.extend(modexp_corpus());
+    cases
+}
+
+/// Modexp inputs that are cheap under some of the gas schedules but expensive to compute.
+fn modexp_corpus() -> Vec<Case> {
+    let mut cases = Vec::new();
+    let mut push = |name: String, base: &[u8], exponent: &[u8], modulus: &[u8]| {
+        cases.push(Case::new(
+            format!("modexp {name}"),
+            MODEXP,
+            modexp_input(base, exponent, modulus),
+        ))
+    };
+
+    // Typical RSA style verification with small exponent.
+    push(
+        "rsa2048 e=65537".into(),
+        &[0xab; 256],
+        &[1, 0, 1],
+        &[0xf1; 256],
+    );
+
+    // Full width exponents over common sizes, odd and even modulus.
+    for len in [32usize, 64, 128, 256, 512, 1024] {
+        let mut odd = vec![0xff; len];
+        odd[len - 1] = 0xfd;
+        let mut even = vec![0xff; len];
+        even[len - 1] = 0xfe;
+        push(
+            format!("{len}b odd mod"),
+            &vec![0xab; len],
+            &vec![0xff; len.min(64)],
+            &odd,
+        );
+        push(
+            format!("{len}b even mod"),
+            &vec![0xab; len],
+            &vec![0xff; len.min(64)],
+            &even,
+        );
+    }
+
+    // Small moduli hit the minimum gas floor while doing the maximal number of squarings.
+    for len in [1usize, 8, 16] {
+        push(
+            format!("{len}b mod min gas"),
+            &vec![0xab; len],
+            &[0xff; 32],
+            &vec![0xfd; len],
+        );
+    }
+
+    // Long exponent with a tiny modulus is priced per exponent bit.
+    push(
+        "1024b exponent".into(),
+        &[0xab; 8],
+        &[0xff; 1024],
+        &[0xfd; 8],
+    );
+    cases
+}
+
+/// Generates random inputs for every precompile in the latest spec.
+fn fuzz_corpus(rng: &mut StdRng, cases_per_precompile: usize) -> Vec<Case> {
+    let mut addresses: Vec<Address> = Precompiles::latest().addresses().copied().collect();
+    addresses.sort();
+
+    let mut cases = Vec::new();
+    for address in addresses {
+        for i in 0..cases_per_precompile {
+            let input = match address {
+                MODEXP => {
+                    let mut random_len = |max: usize| rng.gen_range(0..=max);
+                    let (base_len, exp_len, mod_len) =
+                        (random_len(1024), random_len(1024), random_len(1024));
+                    let mut random_bytes =
+                        |len: usize| (0..len).map(|_| rng.gen::<u8>() | 1).collect::<Vec<_>>();
+                    modexp_input(
+                        &random_bytes(base_len),
+                        &random_bytes(exp_len),
+                        &random_bytes(mod_len),
+                    )
+                }
+                BLAKE2F => blake2f_input(rng.gen_range(0..=1 << 16)),
+                _ => {
+                    let len = rng.gen_range(0..=1024);
+                    (0..len).map(|_| rng.gen::<u8>()).collect()
+                }
+            };
+            cases.push(Case::new(
+                format!("fuzz 0x{:02x} #{i}", address[19]),
+                address,
+                input,
+            ));
+        }
+    }
+    cases
+}
+
+/// Prints the table of measured cases sorted by gas per millisecond.
+///
+/// Returns the cases that were successfully measured together with their measurements.
+fn report(
+    title: &str,
+    cases: &[Case],
+    duration: Duration,
+    anchor: f64,
+    limit: usize,
+) -> Vec<(usize, Measurement)> {
+    let mut measured: Vec<(usize, Measurement)> = cases
+        .iter()
+        .enumerate()
+        .filter_map(|(i, case)| measure(case, duration).map(|m| (i, m)))
+        .collect();
+    measured.sort_by(|(_, a), (_, b)| {
+        a.gas_per_ms(a.gas_used)
+            .total_cmp(&b.gas_per_ms(b.gas_used))
+    });
+
+    println!("\n{title} (anchor {anchor:.0} gas/ms)");
+    println!(
+        "{:<40} {:>12} {:>14} {:>14}",
+        "case", "gas", "time/call", "gas/ms"
+    );
+    for (i, m) in measured.iter().take(limit) {
+        let gas_per_ms = m.gas_per_ms(m.gas_used);
+        let flag = if gas_per_ms * FLAG_RATIO < anchor {
+            "  <- mispriced"
+        } else {
+            ""
+        };
+        println!(
+            "{:<40} {:>12} {:>14.2?} {:>14.0}{flag}",
+            cases[*i].name, m.gas_used, m.per_call, gas_per_ms
+        );
+    }
+    measured
+}
+
+/// Prints modexp cases priced under every gas schedule.
+fn report_modexp(cases: &[Case], measured: &[(usize, Measurement)], anchor: f64) {
+    println!("\nmodexp pricing (gas/ms, anchor {anchor:.0})");
+    print!("{:<40} {:>14}", "case", "time/call");
+    for (name, _) in MODEXP_SCHEDULES {
+        print!(" {name:>14}");
+    }
+    println!();
+
+    for (i, m) in measured.iter().filter(|(i, _)| cases[*i].address == MODEXP) {
+        let (base_len, exp_len, mod_len, exp_highp) = modexp_lengths(&cases[*i].input);
+        print!("{:<40} {:>14.2?}", cases[*i].name, m.per_call);
+        for (_, gas_calc) in MODEXP_SCHEDULES {
+            let gas = gas_calc(base_len, exp_len, mod_len, &exp_highp);
+            let gas_per_ms = m.gas_per_ms(gas);
+            let flag = if gas_per_ms * FLAG_RATIO < anchor {
+                '!'
+            } else {
+                ' '
+            };
+            print!(" {gas_per_ms:>13.0}{flag}");
+        }
+        println!();
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Benchmarks all precompiles in the latest spec and reports their gas per millisecond.
+pub fn benchmark_gas_per_ms(c: &mut Criterion) {
+    let cases = corpus();
+
+    // Every precompile in the latest spec needs to be covered.
+    for address in Precompiles::latest().addresses() {
+        assert!(
+            cases.iter().any(|case| case.address == *address),
+            "precompile {address} is not covered by the corpus"
+        );
+    }
+    for case in &cases {
+        if let Err(err) = case.run() {
+            panic!("corpus case {} failed: {err}", case.name);
+        }
+    }
+
+    let anchor = measure(&cases[0], MEASURE_TIME)
+        .map(|m| m.gas_per_ms(m.gas_used))
+        .expect("ecrecover anchor succeeds");
+
+    let measured = report(
+        "precompile corpus",
+        &cases,
+        MEASURE_TIME,
+        anchor,
+        cases.len(),
+    );
+    report_modexp(&cases, &measured, anchor);
+
+    let seed = env_or("PRECOMPILE_FUZZ_SEED", DEFAULT_FUZZ_SEED);
+    let cases_per_precompile = env_or("PRECOMPILE_FUZZ_CASES", DEFAULT_FUZZ_CASES);
+    let fuzz_cases = fuzz_corpus(&mut StdRng::seed_from_u64(seed), cases_per_precompile);
+    report(
+        &format!("slowest fuzz cases (seed {seed})"),
+        &fuzz_cases,
+        // Random inputs are only run once, they are too many to be averaged.
+        Duration::ZERO,
+        anchor,
+        FUZZ_REPORT_LEN,
+    );
+
+    let mut group = c.benchmark_group("precompile gas/ms");
+    for case in &cases {
+        let gas_used = case.run().unwrap().gas_used;
+        // Throughput of elements is reported as gas per second.
+        group.throughput(Throughput::Elements(gas_used));
+        group.bench_function(&case.name, |b| b.iter(|| case.run()));
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = benchmark_gas_per_ms
+}
+criterion_main!(benches);
@@ -0,0 +1,223 @@
+//! # Blob sidecar KZG verification
+//!
+//! Block validators verify the blobs of [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844)
+//! transactions against their commitments, proofs and the versioned hashes that are included in
+//! the transaction. Blobs are not part of the transaction environment, so this is done before
+//! execution with [`verify_blob_sidecar`], which batch verifies all proofs of a transaction.
+//!
+//! Only available with the `c-kzg` backend.
+//!
+//! [EIP-7594](https://eips.ethereum.org/EIPS/eip-7594) cell proofs are not supported yet. Their
+//! verification was added in `c-kzg` 2, which links the same native library as the `c-kzg` 1
+//! used by the alloy crates of this workspace, so both cannot be built together.
+use crate::kzg_point_evaluation::kzg_to_versioned_hash;
+use c_kzg::{Blob as CBlob, Bytes48, KzgProof as CKzgProof};
+use core::fmt;
+use primitives::{FixedBytes, B256};
+use std::string::String;
+
+pub use c_kzg::{BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT};
+
+/// Blob of a blob transaction sidecar.
+pub type Blob = [u8; BYTES_PER_BLOB];
+
+/// KZG commitment of a blob.
+pub type KzgCommitment = FixedBytes<48>;
+
+/// KZG proof of a blob.
+pub type KzgProof = FixedBytes<48>;
+
+/// Blob sidecar verification error.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlobVerificationError {
+    /// Number of versioned hashes, commitments, blobs and proofs do not match.
+    LengthMismatch {
+        versioned_hashes: usize,
+        commitments: usize,
+        blobs: usize,
+        proofs: usize,
+    },
+    /// Commitment at `index` does not match the versioned hash of the transaction.
+    MismatchedVersionedHash { index: usize },
+    /// Blob, commitment or proof is not a valid encoding.
+    InvalidInput(String),
+    /// KZG proof verification failed.
+    VerificationFailed,
+}
+
+impl core::error::Error for BlobVerificationError {}
+
+impl fmt::Display for BlobVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                versioned_hashes,
+                commitments,
+                blobs,
+                proofs,
+            } => write!(
+                f,
+                "blob sidecar length mismatch: {versioned_hashes} versioned hashes, \
+                 {commitments} commitments, {blobs} blobs and {proofs} proofs"
+            ),
+            Self::MismatchedVersionedHash { index } => {
+                write!(f, "commitment {index} does not match its versioned hash")
+            }
+            Self::InvalidInput(s) => write!(f, "invalid blob sidecar input: {s}"),
+            Self::VerificationFailed => f.write_str("blob kzg proof verification failed"),
+        }
+    }
+}
+
+/// Checks that every commitment hashes to the versioned hash at the same index.
+pub fn verify_versioned_hashes(
+    versioned_hashes: &[B256],
+    commitments: &[KzgCommitment],
+) -> Result<(), BlobVerificationError> {
+    if versioned_hashes.len() != commitments.len() {
+        return Err(BlobVerificationError::LengthMismatch {
+            versioned_hashes: versioned_hashes.len(),
+            commitments: commitments.len(),
+            blobs: 0,
+            proofs: 0,
+        });
+    }
+    for (index, (hash, commitment)) in versioned_hashes.iter().zip(commitments).enumerate() {
+        if kzg_to_versioned_hash(commitment.as_slice()) != hash.0 {
+            return Err(BlobVerificationError::MismatchedVersionedHash { index });
+        }
+    }
+    Ok(())
+}
+
+/// Verifies the blobs of a transaction against its versioned hashes, e.g.
+/// [`Transaction::blob_versioned_hashes`](context_interface::transaction::Transaction::blob_versioned_hashes).
+///
+/// Commitments are checked against the versioned hashes first, then all blob proofs are verified
+/// in a single batch.
+pub fn verify_blob_sidecar(
+    versioned_hashes: &[B256],
+    blobs: &[Blob],
+    commitments: &[KzgCommitment],
+    proofs: &[KzgProof],
+) -> Result<(), BlobVerificationError> {
+    let len = versioned_hashes.len();
+    if commitments.len() != len || blobs.len() != len || proofs.len() != len {
+        return Err(BlobVerificationError::LengthMismatch {
+            versioned_hashes: len,
+            commitments: commitments.len(),
+            blobs: blobs.len(),
+            proofs: proofs.len(),
+        });
+    }
+    verify_versioned_hashes(versioned_hashes, commitments)?;
+
+    if !verify_blob_kzg_proof_batch(blobs, commitments, proofs)? {
+        return Err(BlobVerificationError::VerificationFailed);
+    }
+    Ok(())
+}
+
+/// Batch verifies blob KZG proofs.
+///
+/// Returns `Ok(false)` if any of the proofs is invalid.
+pub fn verify_blob_kzg_proof_batch(
+    blobs: &[Blob],
+    commitments: &[KzgCommitment],
+    proofs: &[KzgProof],
+) -> Result<bool, BlobVerificationError> {
+    CKzgProof::verify_blob_kzg_proof_batch(
+        as_blobs(blobs),
+        as_bytes48_slice(commitments),
+        as_bytes48_slice(proofs),
+        c_kzg::ethereum_kzg_settings(),
+    )
+    .map_err(|e| BlobVerificationError::InvalidInput(format!("{e:?}")))
+}
+
+#[inline]
+fn as_blobs(blobs: &[Blob]) -> &[CBlob] {
+    // SAFETY: `#[repr(C)] Blob([u8; BYTES_PER_BLOB])`
+    unsafe { core::slice::from_raw_parts(blobs.as_ptr().cast(), blobs.len()) }
+}
+
+#[inline]
+fn as_bytes48_slice(bytes: &[FixedBytes<48>]) -> &[Bytes48] {
+    // SAFETY: `#[repr(C)] Bytes48([u8; 48])` and `#[repr(transparent)] FixedBytes<48>([u8; 48])`
+    unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast(), bytes.len()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use c_kzg::KzgCommitment as CKzgCommitment;
+    use std::boxed::Box;
+
+    /// Returns a blob with valid field elements, a commitment and a proof for it.
+    fn sidecar(seed: u8) -> (Box<Blob>, KzgCommitment, KzgProof) {
+        let mut blob = Box::new([0u8; BYTES_PER_BLOB]);
+        for (i, element) in blob.chunks_mut(BYTES_PER_FIELD_ELEMENT).enumerate() {
+            // The first byte is kept zero so the element is below the modulus.
+            element[31] = seed.wrapping_add(i as u8);
+        }
+        let settings = c_kzg::ethereum_kzg_settings();
+        let c_blob = CBlob::new(*blob);
+        let commitment = CKzgCommitment::blob_to_kzg_commitment(&c_blob, settings).unwrap();
+        let proof =
+            CKzgProof::compute_blob_kzg_proof(&c_blob, &commitment.to_bytes(), settings).unwrap();
+        (
+            blob,
+            FixedBytes(commitment.to_bytes().into_inner()),
+            FixedBytes(proof.to_bytes().into_inner()),
+        )
+    }
+
+    fn versioned_hash(commitment: &KzgCommitment) -> B256 {
+        B256::from(kzg_to_versioned_hash(commitment.as_slice()))
+    }
+
+    #[test]
+    fn verify_sidecar() {
+        let (blob0, commitment0, proof0) = sidecar(1);
+        let (blob1, commitment1, proof1) = sidecar(7);
+        let hashes = [versioned_hash(&commitment0), versioned_hash(&commitment1)];
+        let blobs = [*blob0, *blob1];
+
+        assert_eq!(
+            verify_blob_sidecar(
+                &hashes,
+                &blobs,
+                &[commitment0, commitment1],
+                &[proof0, proof1]
+            ),
+            Ok(())
+        );
+
+        // Swapped proofs fail the batch verification.
+        assert_eq!(
+            verify_blob_sidecar(
+                &hashes,
+                &blobs,
+                &[commitment0, commitment1],
+                &[proof1, proof0]
+            ),
+            Err(BlobVerificationError::VerificationFailed)
+        );
+
+        // Commitments in the wrong order do not match the versioned hashes.
+        assert_eq!(
+            verify_blob_sidecar(
+                &hashes,
+                &blobs,
+                &[commitment1, commitment0],
+                &[proof0, proof1]
+            ),
+            Err(BlobVerificationError::MismatchedVersionedHash { index: 0 })
+        );
+
+        assert!(matches!(
+            verify_blob_sidecar(&hashes, &blobs[..1], &[commitment0], &[proof0]),
+            Err(BlobVerificationError::LengthMismatch { .. })
+        ));
+    }
+}
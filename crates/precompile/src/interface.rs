@@ -37,6 +37,8 @@ pub enum PrecompileError {
     ModexpExpOverflow,
     ModexpBaseOverflow,
     ModexpModOverflow,
+    /// Base, exponent or modulus length is above the EIP-7823 limit
+    ModexpEip7823LimitSize,
     // Bn128 errors
     Bn128FieldPointNotAMember,
     Bn128AffineGFailedToCreate,
@@ -83,6 +85,7 @@ impl fmt::Display for PrecompileError {
             Self::ModexpExpOverflow => "modexp exp overflow",
             Self::ModexpBaseOverflow => "modexp base overflow",
             Self::ModexpModOverflow => "modexp mod overflow",
+            Self::ModexpEip7823LimitSize => "modexp input size exceeds EIP-7823 limit",
             Self::Bn128FieldPointNotAMember => "field point not a member of bn128 curve",
             Self::Bn128AffineGFailedToCreate => "failed to create affine g point for bn128 curve",
             Self::Bn128PairLength => "bn128 invalid pair length",
@@ -9,6 +9,8 @@
 extern crate alloc as std;
 
 pub mod blake2;
+#[cfg(feature = "c-kzg")]
+pub mod blob_kzg;
 #[cfg(feature = "blst")]
 pub mod bls12_381;
 pub mod bls12_381_const;
@@ -20,7 +22,7 @@ pub mod interface;
 pub mod kzg_point_evaluation;
 pub mod modexp;
 pub mod secp256k1;
-#[cfg(feature = "secp256r1")]
+#[cfg(any(feature = "secp256r1", feature = "openssl"))]
 pub mod secp256r1;
 pub mod utilities;
 
@@ -60,6 +62,7 @@ impl Precompiles {
             PrecompileSpecId::BERLIN => Self::berlin(),
             PrecompileSpecId::CANCUN => Self::cancun(),
             PrecompileSpecId::PRAGUE => Self::prague(),
+            PrecompileSpecId::OSAKA => Self::osaka(),
             PrecompileSpecId::LATEST => Self::latest(),
         }
     }
@@ -177,9 +180,26 @@ impl Precompiles {
         })
     }
 
+    /// Returns precompiles for Osaka spec.
+    pub fn osaka() -> &'static Self {
+        static INSTANCE: OnceBox<Precompiles> = OnceBox::new();
+        INSTANCE.get_or_init(|| {
+            let mut precompiles = Self::prague().clone();
+            precompiles.extend([
+                // EIP-7823: Set upper bounds for MODEXP.
+                // EIP-7883: ModExp Gas Cost Increase.
+                modexp::OSAKA,
+            ]);
+            // EIP-7951: Precompile for secp256r1 Curve Support.
+            #[cfg(any(feature = "secp256r1", feature = "openssl"))]
+            precompiles.extend([secp256r1::P256VERIFY_OSAKA]);
+            Box::new(precompiles)
+        })
+    }
+
     /// Returns the precompiles for the latest spec.
     pub fn latest() -> &'static Self {
-        Self::prague()
+        Self::osaka()
     }
 
     /// Returns an iterator over the precompiles addresses.
@@ -275,6 +295,7 @@ pub enum PrecompileSpecId {
     BERLIN,
     CANCUN,
     PRAGUE,
+    OSAKA,
     LATEST,
 }
 
@@ -296,7 +317,8 @@ impl PrecompileSpecId {
             ISTANBUL | MUIR_GLACIER => Self::ISTANBUL,
             BERLIN | LONDON | ARROW_GLACIER | GRAY_GLACIER | MERGE | SHANGHAI => Self::BERLIN,
             CANCUN => Self::CANCUN,
-            PRAGUE | OSAKA => Self::PRAGUE,
+            PRAGUE => Self::PRAGUE,
+            OSAKA => Self::OSAKA,
             LATEST => Self::LATEST,
         }
     }
@@ -13,6 +13,11 @@ pub const BYZANTIUM: PrecompileWithAddress =
 pub const BERLIN: PrecompileWithAddress =
     PrecompileWithAddress(crate::u64_to_address(5), berlin_run);
 
+pub const OSAKA: PrecompileWithAddress = PrecompileWithAddress(crate::u64_to_address(5), osaka_run);
+
+/// Maximum length in bytes of the base, exponent and modulus, introduced in EIP-7823.
+pub const OSAKA_MAX_INPUT_LENGTH: usize = 1024;
+
 /// See: <https://eips.ethereum.org/EIPS/eip-198>
 /// See: <https://etherscan.io/address/0000000000000000000000000000000000000005>
 pub fn byzantium_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
@@ -27,7 +32,29 @@ pub fn berlin_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     })
 }
 
+/// See: <https://eips.ethereum.org/EIPS/eip-7883>
+/// See: <https://eips.ethereum.org/EIPS/eip-7823>
+pub fn osaka_run(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    run_inner_with_bounds::<_, true>(input, gas_limit, 500, |a, b, c, d| {
+        osaka_gas_calc(a, b, c, d)
+    })
+}
+
+/// Calculates the iteration count as defined in EIP-2565.
+///
+/// See [`calculate_iteration_count_with_multiplier`] for the EIP-7883 iteration count.
 pub fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
+    calculate_iteration_count_with_multiplier(exp_length, exp_highp, 8)
+}
+
+/// Calculates the iteration count, `multiplier` is applied per exponent byte above 32 bytes.
+///
+/// `multiplier` is 8 before EIP-7883 and 16 after it.
+pub fn calculate_iteration_count_with_multiplier(
+    exp_length: u64,
+    exp_highp: &U256,
+    multiplier: u64,
+) -> u64 {
     let mut iteration_count: u64 = 0;
 
     if exp_length <= 32 && exp_highp.is_zero() {
@@ -35,14 +62,33 @@ pub fn calculate_iteration_count(exp_length: u64, exp_highp: &U256) -> u64 {
     } else if exp_length <= 32 {
         iteration_count = exp_highp.bit_len() as u64 - 1;
     } else if exp_length > 32 {
-        iteration_count = (8u64.saturating_mul(exp_length - 32))
+        iteration_count = (multiplier.saturating_mul(exp_length - 32))
             .saturating_add(max(1, exp_highp.bit_len() as u64) - 1);
     }
 
     max(iteration_count, 1)
 }
 
+/// Runs the modexp precompile with the given minimal gas and gas calculation function.
+///
+/// Input lengths are not bounded, see [`run_inner_with_bounds`] for EIP-7823.
 pub fn run_inner<F>(input: &[u8], gas_limit: u64, min_gas: u64, calc_gas: F) -> PrecompileResult
+where
+    F: FnOnce(u64, u64, u64, &U256) -> u64,
+{
+    run_inner_with_bounds::<F, false>(input, gas_limit, min_gas, calc_gas)
+}
+
+/// Runs the modexp precompile with the given minimal gas and gas calculation function.
+///
+/// If `OSAKA` is set, the base, exponent and modulus lengths are bounded by
+/// [`OSAKA_MAX_INPUT_LENGTH`] as defined in EIP-7823.
+pub fn run_inner_with_bounds<F, const OSAKA: bool>(
+    input: &[u8],
+    gas_limit: u64,
+    min_gas: u64,
+    calc_gas: F,
+) -> PrecompileResult
 where
     F: FnOnce(u64, u64, u64, &U256) -> u64,
 {
@@ -62,6 +108,14 @@ where
     let exp_len = U256::from_be_bytes(right_pad_with_offset::<32>(input, 32).into_owned());
     let mod_len = U256::from_be_bytes(right_pad_with_offset::<32>(input, 64).into_owned());
 
+    // EIP-7823: Set upper bounds for MODEXP.
+    if OSAKA {
+        let max_len = U256::from(OSAKA_MAX_INPUT_LENGTH);
+        if base_len > max_len || exp_len > max_len || mod_len > max_len {
+            return Err(PrecompileError::ModexpEip7823LimitSize);
+        }
+    }
+
     // Cast base and modulus to usize, it does not make sense to handle larger values
     let Ok(base_len) = usize::try_from(base_len) else {
         return Err(PrecompileError::ModexpBaseOverflow);
@@ -163,6 +217,24 @@ pub fn berlin_gas_calc(
     max(200, gas.saturating_to())
 }
 
+// Calculate gas cost according to EIP 7883:
+// https://eips.ethereum.org/EIPS/eip-7883
+pub fn osaka_gas_calc(base_length: u64, exp_length: u64, mod_length: u64, exp_highp: &U256) -> u64 {
+    fn calculate_multiplication_complexity(base_length: u64, mod_length: u64) -> U256 {
+        let max_length = max(base_length, mod_length);
+        if max_length <= 32 {
+            return U256::from(16);
+        }
+        let words = U256::from(max_length.div_ceil(8));
+        U256::from(2) * words * words
+    }
+
+    let multiplication_complexity = calculate_multiplication_complexity(base_length, mod_length);
+    let iteration_count = calculate_iteration_count_with_multiplier(exp_length, exp_highp, 16);
+    let gas = multiplication_complexity * U256::from(iteration_count);
+    max(500, gas.saturating_to())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,6 +416,11 @@ mod tests {
         21_845, 5_461, 5_461, 87_381,
     ];
 
+    const OSAKA_GAS: [u64; 19] = [
+        453_596, 4_080, 4_080, 4_080, 500, 500, 2_048, 512, 512, 8_192, 2_048, 2_048, 32_768,
+        8_192, 8_192, 131_072, 32_768, 32_768, 524_288,
+    ];
+
     #[test]
     fn test_byzantium_modexp_gas() {
         for (test, &test_gas) in TESTS.iter().zip(BYZANTIUM_GAS.iter()) {
@@ -380,4 +457,65 @@ mod tests {
         let expected: Vec<u8> = Vec::new();
         assert_eq!(res.bytes, expected)
     }
+
+    #[test]
+    fn test_osaka_modexp_gas() {
+        for (test, &test_gas) in TESTS.iter().zip(OSAKA_GAS.iter()) {
+            let input = hex::decode(test.input).unwrap().into();
+            let res = osaka_run(&input, 100_000_000).unwrap();
+            let expected = hex::decode(test.expected).unwrap();
+            assert_eq!(
+                res.gas_used, test_gas,
+                "used gas not matching for test: {}",
+                test.name
+            );
+            assert_eq!(res.bytes, expected, "test:{}", test.name);
+        }
+    }
+
+    #[test]
+    fn test_osaka_modexp_empty_input() {
+        let res = osaka_run(&Bytes::new(), 100_000).unwrap();
+        assert_eq!(res.gas_used, 500);
+        assert!(res.bytes.is_empty());
+    }
+
+    #[test]
+    fn test_osaka_modexp_input_limit() {
+        let header = |base_len: usize, exp_len: usize, mod_len: usize| -> Bytes {
+            [base_len, exp_len, mod_len]
+                .iter()
+                .flat_map(|len| U256::from(*len).to_be_bytes::<32>())
+                .collect::<Vec<u8>>()
+                .into()
+        };
+
+        let max = OSAKA_MAX_INPUT_LENGTH;
+        assert!(osaka_run(&header(max, 1, max), 100_000_000).is_ok());
+        for input in [
+            header(max + 1, 1, 1),
+            header(1, max + 1, 1),
+            header(1, 1, max + 1),
+            // Empty base and modulus are not special cased above the limit.
+            header(0, max + 1, 0),
+        ] {
+            assert_eq!(
+                osaka_run(&input, 100_000_000),
+                Err(PrecompileError::ModexpEip7823LimitSize)
+            );
+        }
+        // Berlin has no limit.
+        assert!(berlin_run(&header(1, 1, max + 1), 100_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_iteration_count_multiplier() {
+        let exp_highp = U256::from(2);
+        assert_eq!(calculate_iteration_count(32, &exp_highp), 1);
+        assert_eq!(calculate_iteration_count(40, &exp_highp), 65);
+        assert_eq!(
+            calculate_iteration_count_with_multiplier(40, &exp_highp, 16),
+            129
+        );
+    }
 }
@@ -1,19 +1,39 @@
-//! # EIP-7212 secp256r1 Precompile
+//! # EIP-7951 secp256r1 Precompile
 //!
 //! This module implements the [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md) precompile for
-//! secp256r1 curve support.
+//! secp256r1 curve support, adopted on mainnet by [EIP-7951](https://eips.ethereum.org/EIPS/eip-7951)
+//! with a higher gas cost.
 //!
 //! The main purpose of this precompile is to verify ECDSA signatures that use the secp256r1, or
-//! P256 elliptic curve. The [`P256VERIFY`] const represents the implementation of this precompile,
-//! with the address that it is currently deployed at.
+//! P256 elliptic curve. The [`P256VERIFY`] const represents the RIP-7212 implementation of this
+//! precompile and [`P256VERIFY_OSAKA`] the EIP-7951 one, both with the address that they are
+//! currently deployed at.
+//!
+//! Signature verification uses the pure Rust `p256` crate with the `secp256r1` feature, or the
+//! faster `openssl` backend with the `openssl` feature, which is preferred if both are enabled.
+//! Without either feature the precompile is not available and not included in the Osaka
+//! precompiles.
+//!
+//! The precompile is activated by the spec only, there is no [`Cfg`](context_interface::Cfg)
+//! toggle for it. Use a custom precompile provider to run Osaka without it.
+#[cfg(feature = "openssl")]
+pub mod openssl;
+#[cfg(feature = "secp256r1")]
+pub mod p256;
+
 use crate::{
     u64_to_address, PrecompileError, PrecompileOutput, PrecompileResult, PrecompileWithAddress,
 };
-use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
-use primitives::{Bytes, B256};
+use primitives::{alloy_primitives::B512, Bytes, B256};
+
+/// Address of the p256verify precompile.
+pub const P256VERIFY_ADDRESS: u64 = 0x100;
 
 /// Base gas fee for secp256r1 p256verify operation.
-const P256VERIFY_BASE: u64 = 3450;
+pub const P256VERIFY_BASE_GAS_FEE: u64 = 3450;
+
+/// Base gas fee for secp256r1 p256verify operation as defined in EIP-7951.
+pub const P256VERIFY_BASE_GAS_FEE_OSAKA: u64 = 6900;
 
 /// Returns the secp256r1 precompile with its address.
 pub fn precompiles() -> impl Iterator<Item = PrecompileWithAddress> {
@@ -22,7 +42,11 @@ pub fn precompiles() -> impl Iterator<Item = PrecompileWithAddress> {
 
 /// [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md#specification) secp256r1 precompile.
 pub const P256VERIFY: PrecompileWithAddress =
-    PrecompileWithAddress(u64_to_address(0x100), p256_verify);
+    PrecompileWithAddress(u64_to_address(P256VERIFY_ADDRESS), p256_verify);
+
+/// [EIP-7951](https://eips.ethereum.org/EIPS/eip-7951) secp256r1 precompile.
+pub const P256VERIFY_OSAKA: PrecompileWithAddress =
+    PrecompileWithAddress(u64_to_address(P256VERIFY_ADDRESS), p256_verify_osaka);
 
 /// secp256r1 precompile logic. It takes the input bytes sent to the precompile
 /// and the gas limit. The output represents the result of verifying the
@@ -34,7 +58,17 @@ pub const P256VERIFY: PrecompileWithAddress =
 /// | :-----------------: | :-: | :-: | :----------: | :----------: |
 /// |          32         | 32  | 32  |     32       |      32      |
 pub fn p256_verify(input: &Bytes, gas_limit: u64) -> PrecompileResult {
-    if P256VERIFY_BASE > gas_limit {
+    p256_verify_inner(input, gas_limit, P256VERIFY_BASE_GAS_FEE)
+}
+
+/// secp256r1 precompile logic with the EIP-7951 gas cost, see [`p256_verify`].
+pub fn p256_verify_osaka(input: &Bytes, gas_limit: u64) -> PrecompileResult {
+    p256_verify_inner(input, gas_limit, P256VERIFY_BASE_GAS_FEE_OSAKA)
+}
+
+/// secp256r1 precompile logic with the given gas cost.
+pub fn p256_verify_inner(input: &Bytes, gas_limit: u64, gas_cost: u64) -> PrecompileResult {
+    if gas_cost > gas_limit {
         return Err(PrecompileError::OutOfGas);
     }
     let result = if verify_impl(input).is_some() {
@@ -42,7 +76,7 @@ pub fn p256_verify(input: &Bytes, gas_limit: u64) -> PrecompileResult {
     } else {
         Bytes::new()
     };
-    Ok(PrecompileOutput::new(P256VERIFY_BASE, result))
+    Ok(PrecompileOutput::new(gas_cost, result))
 }
 
 /// Returns `Some(())` if the signature included in the input byte slice is
@@ -53,23 +87,21 @@ pub fn verify_impl(input: &[u8]) -> Option<()> {
     }
 
     // msg signed (msg is already the hash of the original message)
-    let msg = &input[..32];
+    let msg = <&B256>::try_from(&input[..32]).unwrap();
     // r, s: signature
-    let sig = &input[32..96];
+    let sig = <&B512>::try_from(&input[32..96]).unwrap();
     // x, y: public key
-    let pk = &input[96..160];
+    let pk = <&B512>::try_from(&input[96..160]).unwrap();
 
-    // Prepend 0x04 to the public key: uncompressed form
-    let mut uncompressed_pk = [0u8; 65];
-    uncompressed_pk[0] = 0x04;
-    uncompressed_pk[1..].copy_from_slice(pk);
-
-    // Can fail only if the input is not exact length.
-    let signature = Signature::from_slice(sig).ok()?;
-    // Can fail if the input is not valid, so we have to propagate the error.
-    let public_key = VerifyingKey::from_sec1_bytes(&uncompressed_pk).ok()?;
+    verify_signature(msg, sig, pk)
+}
 
-    public_key.verify_prehash(msg, &signature).ok()
+cfg_if::cfg_if! {
+    if #[cfg(feature = "openssl")] {
+        pub use self::openssl::verify_signature;
+    } else {
+        pub use self::p256::verify_signature;
+    }
 }
 
 #[cfg(test)]
@@ -117,6 +149,31 @@ mod test {
 
         assert!(result.is_err());
         assert_eq!(result.err(), Some(PrecompileError::OutOfGas));
+
+        let result = p256_verify_osaka(&input, 6_899);
+        assert_eq!(result.err(), Some(PrecompileError::OutOfGas));
+    }
+
+    #[rstest]
+    #[case::ok("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d604aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e", true)]
+    // r = 0
+    #[case::fail_zero_r("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4d000000000000000000000000000000000000000000000000000000000000000036dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d604aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e", false)]
+    // s = n
+    #[case::fail_s_eq_n("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cacffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc6325514aebd3099c618202fcfe16ae7770b0c49ab5eadf74b754204a3bb6060e44eff37618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e", false)]
+    // public key is the point at infinity
+    #[case::fail_infinity("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d6000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000", false)]
+    // public key x coordinate is not a field element
+    #[case::fail_x_eq_p("4cee90eb86eaa050036147a12d49004b6b9c72bd725d39d4785011fe190f0b4da73bd4903f0ce3b639bbbf6e8e80d16931ff4bcf5993d58468e8fb19086e8cac36dbcd03009df8c59286b162af3bd7fcc0450c9aa81be5d10d312af6c66b1d60ffffffff00000001000000000000000000000000ffffffffffffffffffffffff7618b065f9832de4ca6ca971a7a1adc826d0f7c00181a5fb2ddf79ae00b4e10e", false)]
+    fn test_sig_verify_osaka(#[case] input: &str, #[case] expect_success: bool) {
+        let input = Bytes::from_hex(input).unwrap();
+        let outcome = p256_verify_osaka(&input, 10_000).unwrap();
+        assert_eq!(outcome.gas_used, P256VERIFY_BASE_GAS_FEE_OSAKA);
+        let expected_result = if expect_success {
+            B256::with_last_byte(1).into()
+        } else {
+            Bytes::new()
+        };
+        assert_eq!(outcome.bytes, expected_result);
     }
 
     #[rstest]
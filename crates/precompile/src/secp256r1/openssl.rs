@@ -0,0 +1,26 @@
+use openssl::{
+    bn::BigNum,
+    ec::{EcGroup, EcKey},
+    ecdsa::EcdsaSig,
+    nid::Nid,
+};
+use primitives::{alloy_primitives::B512, B256};
+
+pub fn verify_signature(msg: &B256, sig: &B512, pk: &B512) -> Option<()> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).ok()?;
+
+    // Fails if the coordinates are not field elements or the point is not on the curve.
+    let x = BigNum::from_slice(&pk[..32]).ok()?;
+    let y = BigNum::from_slice(&pk[32..]).ok()?;
+    let public_key = EcKey::from_public_key_affine_coordinates(&group, &x, &y).ok()?;
+
+    // `r` and `s` are checked to be in `[1, n)` by the verification.
+    let r = BigNum::from_slice(&sig[..32]).ok()?;
+    let s = BigNum::from_slice(&sig[32..]).ok()?;
+    let signature = EcdsaSig::from_private_components(r, s).ok()?;
+
+    signature
+        .verify(msg.as_slice(), &public_key)
+        .ok()?
+        .then_some(())
+}
@@ -0,0 +1,17 @@
+use p256::ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey};
+use primitives::{alloy_primitives::B512, B256};
+
+pub fn verify_signature(msg: &B256, sig: &B512, pk: &B512) -> Option<()> {
+    // Can fail only if the input is not exact length or if `r` or `s` are not in `[1, n)`.
+    let signature = Signature::from_slice(sig.as_slice()).ok()?;
+
+    // Prepend 0x04 to the public key: uncompressed form
+    let mut uncompressed_pk = [0u8; 65];
+    uncompressed_pk[0] = 0x04;
+    uncompressed_pk[1..].copy_from_slice(pk.as_slice());
+
+    // Can fail if the input is not valid, so we have to propagate the error.
+    let public_key = VerifyingKey::from_sec1_bytes(&uncompressed_pk).ok()?;
+
+    public_key.verify_prehash(msg.as_slice(), &signature).ok()
+}
@@ -9,7 +9,7 @@ pub const BLOCK_HASH_HISTORY: u64 = 256;
 ///
 /// # Note
 /// This is named `HISTORY_SERVE_WINDOW` in the EIP.
-pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
+pub const BLOCKHASH_SERVE_WINDOW: usize = 8191;
 
 /// EIP-2935: Serve historical block hashes from state
 ///
@@ -17,7 +17,7 @@ pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
 ///
 /// # Note
 /// This is named `HISTORY_STORAGE_ADDRESS` in the EIP.
-pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("0F792be4B0c0cb4DAE440Ef133E90C0eCD48CCCC");
+pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
 
 /// The address of precompile 3, which is handled specially in a few places
 pub const PRECOMPILE3: Address =